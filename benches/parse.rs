@@ -63,6 +63,31 @@ macro_rules! bench_file {
                 )
             });
 
+            #[cfg(feature = "arena")]
+            let b = b.with_function("simd_json::to_arena_value", |b, data| {
+                b.iter_batched(
+                    || data.clone(),
+                    |mut bytes| {
+                        let arena = simd_json::Arena::new();
+                        simd_json::to_arena_value(&mut bytes, &arena).unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+
+            #[cfg(feature = "arena")]
+            let b = b.with_function("simd_json::to_arena_value (reset)", |b, data| {
+                let mut arena = simd_json::Arena::new();
+                b.iter_batched(
+                    || data.clone(),
+                    |mut bytes| {
+                        arena.reset();
+                        simd_json::to_arena_value(&mut bytes, &arena).unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+
             #[cfg(feature = "bench-serde")]
             let b = b.with_function("serde_json::from_slice", |b, data| {
                 b.iter_batched(
@@ -0,0 +1,67 @@
+#[macro_use]
+extern crate criterion;
+
+use core::time::Duration;
+
+#[cfg(feature = "jemallocator")]
+#[global_allocator]
+static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
+
+#[cfg(feature = "bench-serde")]
+use serde_json;
+
+use criterion::{Criterion, ParameterizedBenchmark, Throughput};
+use simd_json;
+use simd_json::prelude::*;
+use std::fs::File;
+use std::io::Read;
+
+macro_rules! bench_file {
+    ($name:ident) => {
+        fn $name(c: &mut Criterion) {
+            let core_ids = core_affinity::get_core_ids().unwrap();
+            core_affinity::set_for_current(core_ids[0]);
+
+            let mut vec = Vec::new();
+            File::open(concat!("data/", stringify!($name), ".json"))
+                .unwrap()
+                .read_to_end(&mut vec)
+                .unwrap();
+            let value = simd_json::to_owned_value(&mut vec).unwrap();
+            let len = value.encode().len();
+
+            let b = ParameterizedBenchmark::new(
+                "simd_json::encode",
+                |b, value| b.iter(|| value.encode()),
+                vec![value],
+            )
+            .warm_up_time(Duration::from_secs(1))
+            .measurement_time(Duration::from_secs(20));
+
+            let b = b.with_function("simd_json::encode_pp", |b, value| {
+                b.iter(|| value.encode_pp())
+            });
+
+            let b = b.with_function("simd_json::serde::to_vec", |b, value| {
+                b.iter(|| simd_json::serde::to_vec(value).unwrap())
+            });
+
+            #[cfg(feature = "bench-serde")]
+            let b = b.with_function("serde_json::to_vec", |b, value| {
+                b.iter(|| serde_json::to_vec(value).unwrap())
+            });
+
+            c.bench(
+                stringify!($name),
+                b.throughput(move |_| Throughput::Bytes(len as u64)),
+            );
+        }
+    };
+}
+
+bench_file!(citm_catalog);
+bench_file!(twitter);
+bench_file!(twitterescaped);
+
+criterion_group!(benches, citm_catalog, twitter, twitterescaped);
+criterion_main!(benches);
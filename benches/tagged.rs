@@ -0,0 +1,142 @@
+#[macro_use]
+extern crate criterion;
+
+use core::time::Duration;
+
+#[cfg(feature = "jemallocator")]
+#[global_allocator]
+static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
+
+#[cfg(feature = "bench-serde")]
+use serde_json;
+
+use criterion::{BatchSize, Criterion, ParameterizedBenchmark, Throughput};
+use serde::Deserialize;
+use simd_json;
+use simd_json::serde::{Tagged, TaggedEnum};
+
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+#[allow(dead_code)]
+enum Event {
+    Click { x: u64, y: u64, target: String },
+    Key { code: u32, modifiers: Vec<String> },
+    Scroll { delta: f64 },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "t", content = "c")]
+#[allow(dead_code)]
+enum Adjacent {
+    Click { x: u64, y: u64, target: String },
+    Key { code: u32, modifiers: Vec<String> },
+    Scroll { delta: f64 },
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum TapeEvent {
+    Click { x: u64, y: u64, target: String },
+    Key { code: u32, modifiers: Vec<String> },
+    Scroll { delta: f64 },
+}
+
+impl TaggedEnum for TapeEvent {
+    const TAG: &'static str = "type";
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum TapeAdjacent {
+    Click { x: u64, y: u64, target: String },
+    Key { code: u32, modifiers: Vec<String> },
+    Scroll { delta: f64 },
+}
+
+impl TaggedEnum for TapeAdjacent {
+    const TAG: &'static str = "t";
+    const CONTENT: Option<&'static str> = Some("c");
+}
+
+fn events(adjacent: bool) -> Vec<u8> {
+    let events: Vec<String> = (0..10_000)
+        .map(|i| {
+            let (tag, content) = match i % 3 {
+                0 => (
+                    "Click",
+                    format!(r#""x":{},"y":{},"target":"button-{}""#, i, i * 2, i % 7),
+                ),
+                1 => (
+                    "Key",
+                    format!(r#""code":{},"modifiers":["shift","ctrl"]"#, i % 128),
+                ),
+                _ => ("Scroll", format!(r#""delta":{}.5"#, i % 100)),
+            };
+            // put the tag last so serde can't read it before the content
+            if adjacent {
+                format!(r#"{{"c":{{{}}},"t":"{}"}}"#, content, tag)
+            } else {
+                format!(r#"{{{},"type":"{}"}}"#, content, tag)
+            }
+        })
+        .collect();
+    format!("[{}]", events.join(",")).into_bytes()
+}
+
+macro_rules! bench_tagged {
+    ($name:ident, $adjacent:expr, $event:ty, $tape:ty) => {
+        fn $name(c: &mut Criterion) {
+            let core_ids = core_affinity::get_core_ids().unwrap();
+            core_affinity::set_for_current(core_ids[0]);
+
+            let b = ParameterizedBenchmark::new(
+                "simd_json::serde::from_slice",
+                |b, data| {
+                    b.iter_batched(
+                        || data.clone(),
+                        |mut bytes| {
+                            let _: Vec<$event> = simd_json::serde::from_slice(&mut bytes).unwrap();
+                        },
+                        BatchSize::SmallInput,
+                    )
+                },
+                vec![events($adjacent)],
+            )
+            .warm_up_time(Duration::from_secs(1))
+            .measurement_time(Duration::from_secs(20));
+
+            let b = b.with_function("simd_json::serde::Tagged", |b, data| {
+                b.iter_batched(
+                    || data.clone(),
+                    |mut bytes| {
+                        let _: Vec<Tagged<$tape>> =
+                            simd_json::serde::from_slice(&mut bytes).unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+
+            #[cfg(feature = "bench-serde")]
+            let b = b.with_function("serde_json::from_slice", |b, data| {
+                b.iter_batched(
+                    || data.clone(),
+                    |bytes| {
+                        let _: Vec<$event> = serde_json::from_slice(&bytes).unwrap();
+                    },
+                    BatchSize::SmallInput,
+                )
+            });
+
+            c.bench(
+                stringify!($name),
+                b.throughput(|data| Throughput::Bytes(data.len() as u64)),
+            );
+        }
+    };
+}
+
+bench_tagged!(internally_tagged, false, Event, TapeEvent);
+bench_tagged!(adjacently_tagged, true, Adjacent, TapeAdjacent);
+
+criterion_group!(benches, internally_tagged, adjacently_tagged);
+criterion_main!(benches);
@@ -0,0 +1,258 @@
+use syn::{Attribute, Error, ExprPath, Ident, Lit, Meta, NestedMeta, Result};
+
+/// How the names of fields or variants are rewritten
+#[derive(Clone, Copy)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn from_str(rule: &str) -> Option<Self> {
+        Some(match rule {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            _ => return None,
+        })
+    }
+
+    /// Renames a `snake_case` field
+    pub(crate) fn field(self, name: &str) -> String {
+        match self {
+            Self::Lower | Self::Snake => name.to_string(),
+            Self::Upper | Self::ScreamingSnake => name.to_ascii_uppercase(),
+            Self::Pascal => name
+                .split('_')
+                .map(|part| {
+                    let mut chars = part.chars();
+                    chars.next().map_or_else(String::new, |c| {
+                        c.to_ascii_uppercase().to_string() + chars.as_str()
+                    })
+                })
+                .collect(),
+            Self::Camel => {
+                let pascal = Self::Pascal.field(name);
+                let mut chars = pascal.chars();
+                chars.next().map_or_else(String::new, |c| {
+                    c.to_ascii_lowercase().to_string() + chars.as_str()
+                })
+            }
+            Self::Kebab => name.replace('_', "-"),
+            Self::ScreamingKebab => name.replace('_', "-").to_ascii_uppercase(),
+        }
+    }
+
+    /// Renames a `PascalCase` variant
+    pub(crate) fn variant(self, name: &str) -> String {
+        match self {
+            Self::Lower => name.to_ascii_lowercase(),
+            Self::Upper => name.to_ascii_uppercase(),
+            Self::Pascal => name.to_string(),
+            Self::Camel => {
+                let mut chars = name.chars();
+                chars.next().map_or_else(String::new, |c| {
+                    c.to_ascii_lowercase().to_string() + chars.as_str()
+                })
+            }
+            Self::Snake | Self::ScreamingSnake | Self::Kebab | Self::ScreamingKebab => {
+                let mut snake = String::with_capacity(name.len() + 4);
+                for (i, c) in name.char_indices() {
+                    if c.is_uppercase() && i > 0 {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                self.field(&snake)
+            }
+        }
+    }
+}
+
+/// A default for a missing field
+pub(crate) enum FieldDefault {
+    Trait,
+    Path(ExprPath),
+}
+
+/// Attributes on a struct or enum
+#[derive(Default)]
+pub(crate) struct Container {
+    pub(crate) rename_all: Option<RenameRule>,
+    pub(crate) deny_unknown_fields: bool,
+    pub(crate) default: bool,
+}
+
+/// Attributes on a field or variant
+#[derive(Default)]
+pub(crate) struct Field {
+    pub(crate) rename_serialize: Option<String>,
+    pub(crate) rename_deserialize: Option<String>,
+    pub(crate) default: Option<FieldDefault>,
+    pub(crate) skip_serializing: bool,
+    pub(crate) skip_deserializing: bool,
+    pub(crate) skip_serializing_if: Option<ExprPath>,
+}
+
+/// Calls `f` for every entry in `#[serde(...)]` and `#[simd_json(...)]`.
+/// `f` returns `false` for entries it doesn't know, those are an error
+/// for `simd_json` while `serde` is left to validate its own.
+fn for_each<F>(attrs: &[Attribute], mut f: F) -> Result<()>
+where
+    F: FnMut(&Meta) -> Result<bool>,
+{
+    for attr in attrs {
+        let ours = attr.path.is_ident("simd_json");
+        if !ours && !attr.path.is_ident("serde") {
+            continue;
+        }
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            other => return Err(Error::new_spanned(other, "expected a list of attributes")),
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(meta) => {
+                    if !f(meta)? && ours {
+                        return Err(Error::new_spanned(meta, "unknown simd_json attribute"));
+                    }
+                }
+                NestedMeta::Lit(literal) => {
+                    return Err(Error::new_spanned(literal, "expected an attribute"))
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unsupported(meta: &Meta) -> Error {
+    Error::new_spanned(meta, "not supported by the simd_json derives")
+}
+
+fn string(lit: &Lit) -> Result<String> {
+    match lit {
+        Lit::Str(s) => Ok(s.value()),
+        other => Err(Error::new_spanned(other, "expected a string")),
+    }
+}
+
+fn path(lit: &Lit) -> Result<ExprPath> {
+    match lit {
+        Lit::Str(s) => s.parse(),
+        other => Err(Error::new_spanned(other, "expected a path in a string")),
+    }
+}
+
+impl Container {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut container = Self::default();
+        for_each(attrs, |meta| {
+            match meta {
+                Meta::NameValue(nv) if nv.path.is_ident("rename_all") => {
+                    let rule = string(&nv.lit)?;
+                    container.rename_all = Some(RenameRule::from_str(&rule).ok_or_else(|| {
+                        Error::new_spanned(&nv.lit, format!("unknown rename rule `{}`", rule))
+                    })?);
+                }
+                Meta::Path(p) if p.is_ident("deny_unknown_fields") => {
+                    container.deny_unknown_fields = true;
+                }
+                Meta::Path(p) if p.is_ident("default") => container.default = true,
+                Meta::NameValue(nv) if nv.path.is_ident("tag") || nv.path.is_ident("content") => {
+                    return Err(unsupported(meta));
+                }
+                Meta::Path(p) if p.is_ident("untagged") => return Err(unsupported(meta)),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(container)
+    }
+
+    /// The JSON key of a field
+    pub(crate) fn field_name(&self, ident: &Ident, rename: Option<String>) -> String {
+        rename.unwrap_or_else(|| {
+            let name = ident.to_string();
+            let name = name.trim_start_matches("r#");
+            self.rename_all
+                .map_or_else(|| name.to_string(), |rule| rule.field(name))
+        })
+    }
+
+    /// The JSON string of a variant
+    pub(crate) fn variant_name(&self, ident: &Ident, rename: Option<String>) -> String {
+        rename.unwrap_or_else(|| {
+            let name = ident.to_string();
+            self.rename_all
+                .map_or_else(|| name.clone(), |rule| rule.variant(&name))
+        })
+    }
+}
+
+impl Field {
+    pub(crate) fn from_attrs(attrs: &[Attribute]) -> Result<Self> {
+        let mut field = Self::default();
+        for_each(attrs, |meta| {
+            match meta {
+                Meta::NameValue(nv) if nv.path.is_ident("rename") => {
+                    let name = string(&nv.lit)?;
+                    field.rename_serialize = Some(name.clone());
+                    field.rename_deserialize = Some(name);
+                }
+                Meta::List(list) if list.path.is_ident("rename") => {
+                    for nested in &list.nested {
+                        match nested {
+                            NestedMeta::Meta(Meta::NameValue(nv))
+                                if nv.path.is_ident("serialize") =>
+                            {
+                                field.rename_serialize = Some(string(&nv.lit)?);
+                            }
+                            NestedMeta::Meta(Meta::NameValue(nv))
+                                if nv.path.is_ident("deserialize") =>
+                            {
+                                field.rename_deserialize = Some(string(&nv.lit)?);
+                            }
+                            other => {
+                                return Err(Error::new_spanned(
+                                    other,
+                                    "expected `serialize` or `deserialize`",
+                                ))
+                            }
+                        }
+                    }
+                }
+                Meta::Path(p) if p.is_ident("default") => field.default = Some(FieldDefault::Trait),
+                Meta::NameValue(nv) if nv.path.is_ident("default") => {
+                    field.default = Some(FieldDefault::Path(path(&nv.lit)?));
+                }
+                Meta::Path(p) if p.is_ident("skip") => {
+                    field.skip_serializing = true;
+                    field.skip_deserializing = true;
+                }
+                Meta::Path(p) if p.is_ident("skip_serializing") => field.skip_serializing = true,
+                Meta::Path(p) if p.is_ident("skip_deserializing") => {
+                    field.skip_deserializing = true;
+                }
+                Meta::NameValue(nv) if nv.path.is_ident("skip_serializing_if") => {
+                    field.skip_serializing_if = Some(path(&nv.lit)?);
+                }
+                Meta::Path(p) if p.is_ident("flatten") => return Err(unsupported(meta)),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(field)
+    }
+}
@@ -0,0 +1,227 @@
+use crate::attrs::{Container, Field, FieldDefault};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use std::collections::BTreeMap;
+use syn::{
+    parse_quote, Data, DataEnum, DeriveInput, Error, Fields, FieldsNamed, GenericParam, Generics,
+    Lifetime, LitByteStr, Result,
+};
+
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = Container::from_attrs(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => named(&container, fields)?,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                ::simd_json::derive::Deserialize::from_tape(__de).map(Self)
+            },
+            Fields::Unnamed(fields) => {
+                let len = fields.unnamed.len();
+                let items = fields.unnamed.iter().map(|_| {
+                    quote! { ::simd_json::derive::Deserialize::from_tape(__de)? }
+                });
+                quote! {
+                    match __de.next_node()? {
+                        ::simd_json::Node::Array(#len, _) => Ok(Self(#(#items),*)),
+                        _ => Err(::simd_json::Deserializer::node_error(
+                            __de.node_index(),
+                            ::simd_json::ErrorType::ExpectedArray,
+                        )),
+                    }
+                }
+            }
+            Fields::Unit => quote! {
+                match __de.next_node()? {
+                    ::simd_json::Node::Static(::simd_json::StaticNode::Null) => Ok(Self),
+                    _ => Err(::simd_json::Deserializer::node_error(
+                        __de.node_index(),
+                        ::simd_json::ErrorType::ExpectedNull,
+                    )),
+                }
+            },
+        },
+        Data::Enum(e) => variants(&container, e)?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "unions are not supported by the simd_json derives",
+            ))
+        }
+    };
+
+    let (input_lifetime, generics) = generics(&input.generics);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let name = &input.ident;
+    Ok(quote! {
+        impl #impl_generics ::simd_json::derive::Deserialize<#input_lifetime> for #name #ty_generics #where_clause {
+            #[inline]
+            fn from_tape(
+                __de: &mut ::simd_json::Deserializer<#input_lifetime>,
+            ) -> ::simd_json::Result<Self> {
+                #body
+            }
+        }
+    })
+}
+
+/// Picks the lifetime of the input and adds the bounds for the impl.
+///
+/// Types that borrow from the input use their first lifetime, the other
+/// lifetimes have to be outlived by it.
+fn generics(generics: &Generics) -> (Lifetime, Generics) {
+    let mut generics = generics.clone();
+    let input = if let Some(l) = generics.lifetimes().next() {
+        l.lifetime.clone()
+    } else {
+        let l = Lifetime::new("'__input", Span::call_site());
+        generics.params.insert(0, parse_quote!(#l));
+        l
+    };
+    for param in &mut generics.params {
+        match param {
+            GenericParam::Lifetime(l) if l.lifetime != input => l.bounds.push(input.clone()),
+            GenericParam::Type(t) => t
+                .bounds
+                .push(parse_quote!(::simd_json::derive::Deserialize<#input>)),
+            _ => (),
+        }
+    }
+    (input, generics)
+}
+
+fn named(container: &Container, fields: &FieldsNamed) -> Result<TokenStream> {
+    let mut slots = Vec::new();
+    let mut keys: BTreeMap<usize, Vec<TokenStream>> = BTreeMap::new();
+    let mut values = Vec::new();
+    for (i, field) in fields.named.iter().enumerate() {
+        let attrs = Field::from_attrs(&field.attrs)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let default = match attrs.default {
+            Some(FieldDefault::Trait) => Some(quote! { ::std::default::Default::default() }),
+            Some(FieldDefault::Path(path)) => Some(quote! { #path() }),
+            None if container.default => Some(quote! { __default.#ident }),
+            None => None,
+        };
+        if attrs.skip_deserializing {
+            let default = default.unwrap_or_else(|| quote! { ::std::default::Default::default() });
+            values.push(quote! { #ident: #default });
+            continue;
+        }
+
+        let slot = format_ident!("__field{}", i);
+        let name = container.field_name(ident, attrs.rename_deserialize);
+        let bytes = LitByteStr::new(name.as_bytes(), Span::call_site());
+        slots.push(quote! { let mut #slot: ::std::option::Option<#ty> = None; });
+        keys.entry(name.len()).or_default().push(quote! {
+            #bytes => {
+                #slot = Some(::simd_json::derive::Deserialize::from_tape(__de)?);
+                continue;
+            }
+        });
+        let missing = default.unwrap_or_else(|| {
+            quote! {
+                match <#ty as ::simd_json::derive::Deserialize>::missing() {
+                    Some(v) => v,
+                    None => return Err(::simd_json::Deserializer::node_error(
+                        __node,
+                        ::simd_json::ErrorType::MissingField(#name),
+                    )),
+                }
+            }
+        });
+        values.push(quote! {
+            #ident: match #slot {
+                Some(v) => v,
+                None => #missing,
+            }
+        });
+    }
+
+    // Keys are matched by their length first and their bytes second
+    let lengths = keys.iter().map(|(len, arms)| {
+        quote! {
+            #len => match __key.as_bytes() {
+                #(#arms)*
+                _ => (),
+            },
+        }
+    });
+    let unknown = if container.deny_unknown_fields {
+        quote! {
+            return Err(::simd_json::Deserializer::node_error(
+                __de.node_index(),
+                ::simd_json::ErrorType::UnknownField(__key.to_string()),
+            ));
+        }
+    } else {
+        quote! { __de.skip_value()?; }
+    };
+    let default = if container.default {
+        quote! { let __default: Self = ::std::default::Default::default(); }
+    } else {
+        quote! {}
+    };
+    // Missing fields are reported at the node of the object
+    Ok(quote! {
+        let __len = match __de.next_node()? {
+            ::simd_json::Node::Object(len, _) => len,
+            _ => return Err(::simd_json::Deserializer::node_error(
+                __de.node_index(),
+                ::simd_json::ErrorType::ExpectedMap,
+            )),
+        };
+        let __node = __de.node_index();
+        #(#slots)*
+        for _ in 0..__len {
+            let __key = match __de.next_node()? {
+                ::simd_json::Node::String(key) => key,
+                _ => return Err(::simd_json::Deserializer::node_error(
+                    __de.node_index(),
+                    ::simd_json::ErrorType::ExpectedString,
+                )),
+            };
+            match __key.len() {
+                #(#lengths)*
+                _ => (),
+            }
+            #unknown
+        }
+        #default
+        Ok(Self {
+            #(#values),*
+        })
+    })
+}
+
+fn variants(container: &Container, data: &DataEnum) -> Result<TokenStream> {
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "the simd_json derives only support unit variants",
+            ));
+        }
+        let attrs = Field::from_attrs(&variant.attrs)?;
+        let ident = &variant.ident;
+        let name = container.variant_name(ident, attrs.rename_deserialize);
+        arms.push(quote! { #name => Ok(Self::#ident), });
+    }
+    Ok(quote! {
+        match __de.next_node()? {
+            ::simd_json::Node::String(s) => match s {
+                #(#arms)*
+                _ => Err(::simd_json::Deserializer::node_error(
+                    __de.node_index(),
+                    ::simd_json::ErrorType::ExpectedEnum,
+                )),
+            },
+            _ => Err(::simd_json::Deserializer::node_error(
+                __de.node_index(),
+                ::simd_json::ErrorType::ExpectedString,
+            )),
+        }
+    })
+}
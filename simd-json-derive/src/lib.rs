@@ -0,0 +1,45 @@
+//! Derives for the tape native `Serialize` and `Deserialize` traits of
+//! `simd_json::derive`.
+//!
+//! Use them through `simd_json::derive` with the `derive` feature of
+//! `simd-json` enabled, the generated code refers to `::simd_json`.
+//!
+//! Structs with named fields map to objects, newtype structs to their
+//! content, tuple structs to arrays and unit structs to `null`. Enums
+//! are supported as long as all variants are units, they map to strings.
+//! Names can be changed and fields defaulted or skipped with the common
+//! serde attributes in `#[serde(...)]` or `#[simd_json(...)]`.
+
+#![deny(warnings)]
+#![warn(unused_extern_crates)]
+#![deny(
+    clippy::all,
+    clippy::unwrap_used,
+    clippy::unnecessary_unwrap,
+    clippy::pedantic
+)]
+
+mod attrs;
+mod de;
+mod ser;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derives `simd_json::derive::Serialize`
+#[proc_macro_derive(Serialize, attributes(simd_json, serde))]
+pub fn derive_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    ser::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `simd_json::derive::Deserialize`
+#[proc_macro_derive(Deserialize, attributes(simd_json, serde))]
+pub fn derive_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    de::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
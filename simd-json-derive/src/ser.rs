@@ -0,0 +1,191 @@
+use crate::attrs::{Container, Field};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, Data, DataEnum, DeriveInput, Error, Fields, FieldsNamed, GenericParam, Index,
+    LitByteStr, Result,
+};
+
+pub(crate) fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = Container::from_attrs(&input.attrs)?;
+    let body = match &input.data {
+        Data::Struct(s) => match &s.fields {
+            Fields::Named(fields) => named(&container, fields)?,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                ::simd_json::derive::Serialize::json_write(&self.0, __g)
+            },
+            Fields::Unnamed(fields) => array((0..fields.unnamed.len()).map(|i| {
+                let i = Index::from(i);
+                quote! { &self.#i }
+            })),
+            Fields::Unit => quote! { __g.write(b"null") },
+        },
+        Data::Enum(e) => variants(&container, e)?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                input,
+                "unions are not supported by the simd_json derives",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    for param in &mut generics.params {
+        if let GenericParam::Type(t) = param {
+            t.bounds.push(parse_quote!(::simd_json::derive::Serialize));
+        }
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &input.ident;
+    Ok(quote! {
+        impl #impl_generics ::simd_json::derive::Serialize for #name #ty_generics #where_clause {
+            #[inline]
+            fn json_write<__G>(&self, __g: &mut __G) -> ::std::io::Result<()>
+            where
+                __G: ::simd_json::derive::BaseGenerator,
+            {
+                #body
+            }
+        }
+    })
+}
+
+/// Writes a quoted key, keys that need escaping are escaped at runtime.
+pub(crate) fn key(name: &str) -> TokenStream {
+    if name
+        .bytes()
+        .all(|b| (0x20..0x7f).contains(&b) && b != b'"' && b != b'\\')
+    {
+        let quoted = LitByteStr::new(
+            format!("\"{}\"", name).as_bytes(),
+            proc_macro2::Span::call_site(),
+        );
+        quote! { __g.write(#quoted)? }
+    } else {
+        quote! { __g.write_simple_string(#name)? }
+    }
+}
+
+/// Writes a list of `&impl Serialize` expressions as array
+fn array<I>(items: I) -> TokenStream
+where
+    I: Iterator<Item = TokenStream>,
+{
+    let writes = items.enumerate().map(|(i, item)| {
+        let comma = if i == 0 {
+            quote! {}
+        } else {
+            quote! { __g.write(b",")?; }
+        };
+        quote! {
+            #comma
+            __g.new_line()?;
+            ::simd_json::derive::Serialize::json_write(#item, __g)?;
+        }
+    });
+    quote! {
+        __g.write(b"[")?;
+        __g.indent();
+        #(#writes)*
+        __g.dedent();
+        __g.new_line()?;
+        __g.write(b"]")
+    }
+}
+
+fn named(container: &Container, fields: &FieldsNamed) -> Result<TokenStream> {
+    let mut entries = Vec::new();
+    for field in &fields.named {
+        let attrs = Field::from_attrs(&field.attrs)?;
+        if attrs.skip_serializing {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named field");
+        let name = container.field_name(ident, attrs.rename_serialize);
+        entries.push((ident, key(&name), attrs.skip_serializing_if));
+    }
+    if entries.is_empty() {
+        return Ok(quote! { __g.write(b"{}") });
+    }
+
+    // Without conditional fields we know which one comes first
+    let conditional = entries.iter().any(|(_, _, skip)| skip.is_some());
+    let writes = entries.iter().enumerate().map(|(i, (ident, key, skip))| {
+        let comma = if conditional {
+            quote! {
+                if __first {
+                    __first = false;
+                } else {
+                    __g.write(b",")?;
+                }
+            }
+        } else if i == 0 {
+            quote! {}
+        } else {
+            quote! { __g.write(b",")?; }
+        };
+        let write = quote! {
+            #comma
+            __g.new_line()?;
+            #key;
+            __g.write_min(b": ", b':')?;
+            ::simd_json::derive::Serialize::json_write(&self.#ident, __g)?;
+        };
+        if let Some(skip) = skip {
+            quote! {
+                if !#skip(&self.#ident) {
+                    #write
+                }
+            }
+        } else {
+            write
+        }
+    });
+    let (first, end) = if conditional {
+        (
+            quote! { let mut __first = true; },
+            quote! {
+                if !__first {
+                    __g.new_line()?;
+                }
+            },
+        )
+    } else {
+        (quote! {}, quote! { __g.new_line()?; })
+    };
+    Ok(quote! {
+        #first
+        __g.write(b"{")?;
+        __g.indent();
+        #(#writes)*
+        __g.dedent();
+        #end
+        __g.write(b"}")
+    })
+}
+
+fn variants(container: &Container, data: &DataEnum) -> Result<TokenStream> {
+    let mut arms = Vec::new();
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "the simd_json derives only support unit variants",
+            ));
+        }
+        let attrs = Field::from_attrs(&variant.attrs)?;
+        let ident = &variant.ident;
+        let name = container.variant_name(ident, attrs.rename_serialize);
+        let key = key(&name);
+        arms.push(quote! { Self::#ident => #key, });
+    }
+    if arms.is_empty() {
+        return Ok(quote! { match *self {} });
+    }
+    Ok(quote! {
+        match self {
+            #(#arms)*
+        }
+        Ok(())
+    })
+}
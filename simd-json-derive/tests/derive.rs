@@ -0,0 +1,244 @@
+use simd_json::derive::{Deserialize, Serialize};
+use simd_json::{Deserializer, ErrorType};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Point {
+    x: i64,
+    y: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+struct Event<'input> {
+    event_type: &'input str,
+    #[serde(rename = "pos")]
+    position: Option<Point>,
+    #[serde(default)]
+    tags: Vec<Cow<'input, str>>,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    count: u32,
+    #[serde(skip)]
+    cached: Option<String>,
+    #[simd_json(skip_serializing)]
+    secret: Option<String>,
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_one(n: &u32) -> bool {
+    *n == 1
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Clone, Copy)]
+#[simd_json(rename_all = "snake_case")]
+enum Kind {
+    KeyPress,
+    #[simd_json(rename = "click")]
+    MouseClick,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Wrapper(Kind);
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Pair(u8, String);
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Unit;
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[simd_json(deny_unknown_fields)]
+struct Strict {
+    a: u8,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq, Default)]
+#[serde(default)]
+struct Defaults {
+    a: u8,
+    b: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Generic<T> {
+    items: Vec<T>,
+    map: HashMap<String, T>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Keys {
+    #[serde(rename = "a\"b")]
+    quoted: u8,
+    #[serde(rename = "ä")]
+    unicode: u8,
+    r#type: u8,
+}
+
+#[test]
+fn named() {
+    let mut d = br#"{
+        "eventType": "click",
+        "unknown": {"nested": [1, 2, {"deep": null}]},
+        "pos": {"y": 2, "x": 1},
+        "tags": ["a", "b\nc"],
+        "cached": "ignored",
+        "secret": "s3cr3t"
+    }"#
+    .to_vec();
+    let e = Event::from_slice(&mut d).unwrap();
+    assert_eq!(
+        e,
+        Event {
+            event_type: "click",
+            position: Some(Point { x: 1, y: 2 }),
+            tags: vec![Cow::Borrowed("a"), Cow::Owned("b\nc".to_string())],
+            count: 1,
+            cached: None,
+            secret: Some("s3cr3t".to_string()),
+        }
+    );
+    assert_eq!(
+        e.json_string().unwrap(),
+        r#"{"eventType":"click","pos":{"x":1,"y":2},"tags":["a","b\nc"]}"#
+    );
+
+    let mut d = br#"{"eventType": "key", "count": 3}"#.to_vec();
+    let e = Event::from_slice(&mut d).unwrap();
+    assert_eq!(e.position, None);
+    assert!(e.tags.is_empty());
+    assert_eq!(e.count, 3);
+    assert_eq!(
+        e.json_string().unwrap(),
+        r#"{"eventType":"key","pos":null,"tags":[],"count":3}"#
+    );
+}
+
+#[test]
+fn pretty() {
+    let p = Point { x: 1, y: -2 };
+    assert_eq!(
+        p.json_string_pp().unwrap(),
+        "{\n  \"x\": 1,\n  \"y\": -2\n}"
+    );
+    let e = Event {
+        event_type: "e",
+        count: 1,
+        ..Event::default()
+    };
+    assert_eq!(
+        e.json_string_pp().unwrap(),
+        "{\n  \"eventType\": \"e\",\n  \"pos\": null,\n  \"tags\": []\n}"
+    );
+}
+
+#[test]
+fn missing_field() {
+    let mut d = br#"{"x": 1}"#.to_vec();
+    let e = Point::from_slice(&mut d).unwrap_err();
+    assert_eq!(e, Deserializer::node_error(1, ErrorType::MissingField("y")));
+
+    let mut d = br#"{"count": 1}"#.to_vec();
+    assert!(Event::from_slice(&mut d).is_err());
+}
+
+#[test]
+fn wrong_types() {
+    let mut d = br#"{"x": 1, "y": "2"}"#.to_vec();
+    assert_eq!(
+        Point::from_slice(&mut d).unwrap_err(),
+        Deserializer::node_error(5, ErrorType::ExpectedSigned)
+    );
+    let mut d = br#"[1, 2]"#.to_vec();
+    assert!(Point::from_slice(&mut d).is_err());
+    let mut d = br#"[1, "a", 3]"#.to_vec();
+    assert!(Pair::from_slice(&mut d).is_err());
+    let mut d = br#""nope""#.to_vec();
+    assert!(Kind::from_slice(&mut d).is_err());
+}
+
+#[test]
+fn unknown_fields() {
+    let mut d = br#"{"a": 1}"#.to_vec();
+    assert_eq!(Strict::from_slice(&mut d).unwrap(), Strict { a: 1 });
+    let mut d = br#"{"a": 1, "b": 2}"#.to_vec();
+    let e = Strict::from_slice(&mut d).unwrap_err();
+    assert_eq!(
+        e,
+        Deserializer::node_error(4, ErrorType::UnknownField("b".to_string()))
+    );
+}
+
+#[test]
+fn container_default() {
+    let mut d = br#"{"b": "x"}"#.to_vec();
+    assert_eq!(
+        Defaults::from_slice(&mut d).unwrap(),
+        Defaults {
+            a: 0,
+            b: "x".to_string()
+        }
+    );
+}
+
+#[test]
+fn enums_and_tuples() {
+    let mut d = br#"["key_press", "click"]"#.to_vec();
+    let kinds = Vec::<Kind>::from_slice(&mut d).unwrap();
+    assert_eq!(kinds, vec![Kind::KeyPress, Kind::MouseClick]);
+    assert_eq!(kinds.json_string().unwrap(), r#"["key_press","click"]"#);
+
+    let mut d = br#""click""#.to_vec();
+    let w = Wrapper::from_slice(&mut d).unwrap();
+    assert_eq!(w, Wrapper(Kind::MouseClick));
+    assert_eq!(w.json_string().unwrap(), r#""click""#);
+
+    let mut d = br#"[1, "two"]"#.to_vec();
+    let p = Pair::from_slice(&mut d).unwrap();
+    assert_eq!(p, Pair(1, "two".to_string()));
+    assert_eq!(p.json_string().unwrap(), r#"[1,"two"]"#);
+
+    let mut d = br#"null"#.to_vec();
+    assert_eq!(Unit::from_slice(&mut d).unwrap(), Unit);
+    assert_eq!(Unit.json_string().unwrap(), "null");
+}
+
+#[test]
+fn generics() {
+    let mut d = br#"{"items": [1, 2], "map": {"a": 3}}"#.to_vec();
+    let g = Generic::<u8>::from_slice(&mut d).unwrap();
+    assert_eq!(g.items, vec![1, 2]);
+    assert_eq!(g.map["a"], 3);
+    assert_eq!(g.json_string().unwrap(), r#"{"items":[1,2],"map":{"a":3}}"#);
+}
+
+#[test]
+fn escaped_keys() {
+    let mut d = r#"{"a\"b": 1, "ä": 2, "type": 3}"#.as_bytes().to_vec();
+    let k = Keys::from_slice(&mut d).unwrap();
+    assert_eq!(
+        k,
+        Keys {
+            quoted: 1,
+            unicode: 2,
+            r#type: 3
+        }
+    );
+    assert_eq!(k.json_string().unwrap(), r#"{"a\"b":1,"ä":2,"type":3}"#);
+}
+
+#[test]
+fn matches_serde_json() {
+    let mut d = br#"{"x": -1, "y": 9223372036854775807}"#.to_vec();
+    let p = Point::from_slice(&mut d).unwrap();
+    let s = p.json_string().unwrap();
+    let v: serde_json::Value = serde_json::from_str(&s).unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!({"x": -1, "y": 9_223_372_036_854_775_807_i64})
+    );
+}
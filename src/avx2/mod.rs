@@ -1,2 +1,3 @@
 pub mod deser;
+pub mod ser;
 pub mod stage1;
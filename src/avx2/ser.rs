@@ -0,0 +1,32 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_max_epu8, _mm256_movemask_epi8,
+    _mm256_or_si256, _mm256_set1_epi8,
+};
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_max_epu8, _mm256_movemask_epi8,
+    _mm256_or_si256, _mm256_set1_epi8,
+};
+
+use std::mem;
+
+/// Number of bytes `escape_bits` looks at
+pub(crate) const ESCAPE_BLOCK: usize = 32;
+
+/// Returns a bit for every byte in the `ESCAPE_BLOCK` bytes at `ptr` that
+/// has to be escaped in a JSON string, those are quotes, backslashes and
+/// control characters.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+#[allow(clippy::cast_possible_wrap, clippy::cast_ptr_alignment)]
+pub(crate) unsafe fn escape_bits(ptr: *const u8) -> u32 {
+    let data: __m256i = _mm256_loadu_si256(ptr.cast::<__m256i>());
+    let bs_or_quote = _mm256_or_si256(
+        _mm256_cmpeq_epi8(data, _mm256_set1_epi8(b'\\' as i8)),
+        _mm256_cmpeq_epi8(data, _mm256_set1_epi8(b'"' as i8)),
+    );
+    // only bytes up to 0x1f are left unchanged by the unsigned maximum
+    let control_range = _mm256_set1_epi8(0x1f);
+    let control = _mm256_cmpeq_epi8(_mm256_max_epu8(data, control_range), control_range);
+    static_cast_u32!(_mm256_movemask_epi8(_mm256_or_si256(bs_or_quote, control)))
+}
@@ -0,0 +1,353 @@
+//! Canonical JSON as defined by RFC 8785, the JSON Canonicalization Scheme.
+//!
+//! Object members are sorted by the UTF-16 code units of their keys, no
+//! whitespace is written, strings are escaped as little as possible and
+//! numbers are written the way ECMAScript converts a double to a string.
+
+use crate::value::{borrowed, owned};
+use crate::{Error, ErrorType, Escape, Result, StaticNode};
+use std::cmp::Ordering;
+use std::io::Write;
+
+macro_rules! iomap {
+    ($e:expr) => {
+        ($e).map_err(|err| Error::generic(ErrorType::Io(err)))
+    };
+}
+
+/// Largest integer that survives the conversion to a double unchanged
+const MAX_SAFE_INTEGER: i64 = 1 << 53;
+
+/// Orders keys by their UTF-16 code units
+fn cmp_keys(a: &str, b: &str) -> Ordering {
+    a.encode_utf16().cmp(b.encode_utf16())
+}
+
+fn write_string<W: Write>(w: &mut W, s: &str) -> Result<()> {
+    stry!(iomap!(w.write_all(b"\"")));
+    stry!(iomap!(Escape::new().write_content(w, s)));
+    iomap!(w.write_all(b"\""))
+}
+
+#[allow(clippy::cast_precision_loss, clippy::cast_sign_loss)]
+fn write_static<W: Write>(w: &mut W, s: &StaticNode) -> Result<()> {
+    match *s {
+        StaticNode::Null => iomap!(w.write_all(b"null")),
+        StaticNode::Bool(true) => iomap!(w.write_all(b"true")),
+        StaticNode::Bool(false) => iomap!(w.write_all(b"false")),
+        StaticNode::I64(n) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&n) => {
+            iomap!(w.write_all(n.to_string().as_bytes()))
+        }
+        StaticNode::I64(n) => write_f64(w, n as f64),
+        StaticNode::U64(n) if n <= MAX_SAFE_INTEGER as u64 => {
+            iomap!(w.write_all(n.to_string().as_bytes()))
+        }
+        StaticNode::U64(n) => write_f64(w, n as f64),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(n) => write_f64(w, n as f64),
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(n) => write_f64(w, n as f64),
+        StaticNode::F64(n) => write_f64(w, n),
+    }
+}
+
+/// Writes a double the way ECMAScript's `Number.prototype.toString` does,
+/// NaN and the infinities can't be represented and are an error.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+fn write_f64<W: Write>(w: &mut W, n: f64) -> Result<()> {
+    if !n.is_finite() {
+        return Err(Error::generic(ErrorType::InvalidNumber));
+    }
+    if n == 0.0 {
+        // this includes -0
+        return iomap!(w.write_all(b"0"));
+    }
+    if n < 0.0 {
+        stry!(iomap!(w.write_all(b"-")));
+    }
+    // ryu finds the same shortest digits ECMAScript asks for, we only
+    // have to lay them out differently
+    let mut buffer = ryu::Buffer::new();
+    let s = buffer.format_finite(n.abs()).as_bytes();
+    let (mantissa, exp) = match s.iter().position(|c| *c == b'e') {
+        Some(e) => {
+            let exp = unsafe { std::str::from_utf8_unchecked(&s[e + 1..]) };
+            (&s[..e], exp.parse::<i32>().unwrap_or_default())
+        }
+        None => (s, 0),
+    };
+    let mut digits = [0_u8; 32];
+    let mut len = 0;
+    let mut point = None;
+    for c in mantissa {
+        if *c == b'.' {
+            point = Some(len);
+        } else {
+            digits[len] = *c;
+            len += 1;
+        }
+    }
+    // with the digits d1 to dk the value is 0.d1d2...dk * 10^point
+    let mut point = point.unwrap_or(len) as i32 + exp;
+    let mut first = 0;
+    while digits[first] == b'0' {
+        first += 1;
+        point -= 1;
+    }
+    while digits[len - 1] == b'0' {
+        len -= 1;
+    }
+    let digits = &digits[first..len];
+    let count = digits.len() as i32;
+
+    if count <= point && point <= 21 {
+        stry!(iomap!(w.write_all(digits)));
+        for _ in count..point {
+            stry!(iomap!(w.write_all(b"0")));
+        }
+        Ok(())
+    } else if 0 < point && point <= 21 {
+        stry!(iomap!(w.write_all(&digits[..point as usize])));
+        stry!(iomap!(w.write_all(b".")));
+        iomap!(w.write_all(&digits[point as usize..]))
+    } else if -6 < point && point <= 0 {
+        stry!(iomap!(w.write_all(b"0.")));
+        for _ in point..0 {
+            stry!(iomap!(w.write_all(b"0")));
+        }
+        iomap!(w.write_all(digits))
+    } else {
+        stry!(iomap!(w.write_all(&digits[..1])));
+        if count > 1 {
+            stry!(iomap!(w.write_all(b".")));
+            stry!(iomap!(w.write_all(&digits[1..])));
+        }
+        let exp = point - 1;
+        let sign: &[u8] = if exp > 0 { b"e+" } else { b"e-" };
+        stry!(iomap!(w.write_all(sign)));
+        iomap!(w.write_all(exp.abs().to_string().as_bytes()))
+    }
+}
+
+fn write_owned<W: Write, S>(w: &mut W, v: &owned::HashedValue<S>) -> Result<()> {
+    match v {
+        owned::HashedValue::Static(s) => write_static(w, s),
+        owned::HashedValue::String(s) => write_string(w, s),
+        owned::HashedValue::Array(a) => {
+            stry!(iomap!(w.write_all(b"[")));
+            for (i, e) in a.iter().enumerate() {
+                if i > 0 {
+                    stry!(iomap!(w.write_all(b",")));
+                }
+                stry!(write_owned(w, e));
+            }
+            iomap!(w.write_all(b"]"))
+        }
+        owned::HashedValue::Object(o) => {
+            let mut members: Vec<_> = o.iter().collect();
+            members.sort_by(|(a, _), (b, _)| cmp_keys(a, b));
+            stry!(iomap!(w.write_all(b"{")));
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    stry!(iomap!(w.write_all(b",")));
+                }
+                stry!(write_string(w, k));
+                stry!(iomap!(w.write_all(b":")));
+                stry!(write_owned(w, v));
+            }
+            iomap!(w.write_all(b"}"))
+        }
+    }
+}
+
+fn write_borrowed<W: Write, S>(w: &mut W, v: &borrowed::HashedValue<S>) -> Result<()> {
+    match v {
+        borrowed::HashedValue::Static(s) => write_static(w, s),
+        borrowed::HashedValue::String(s) => write_string(w, s),
+        borrowed::HashedValue::Array(a) => {
+            stry!(iomap!(w.write_all(b"[")));
+            for (i, e) in a.iter().enumerate() {
+                if i > 0 {
+                    stry!(iomap!(w.write_all(b",")));
+                }
+                stry!(write_borrowed(w, e));
+            }
+            iomap!(w.write_all(b"]"))
+        }
+        borrowed::HashedValue::Object(o) => {
+            let mut members: Vec<_> = o.iter().collect();
+            members.sort_by(|(a, _), (b, _)| cmp_keys(a, b));
+            stry!(iomap!(w.write_all(b"{")));
+            for (i, (k, v)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    stry!(iomap!(w.write_all(b",")));
+                }
+                stry!(write_string(w, k));
+                stry!(iomap!(w.write_all(b":")));
+                stry!(write_borrowed(w, v));
+            }
+            iomap!(w.write_all(b"}"))
+        }
+    }
+}
+
+impl<S> owned::HashedValue<S> {
+    /// Encodes the value as canonical JSON (RFC 8785), equal values
+    /// always produce the same bytes.
+    ///
+    /// Integers are written as doubles like all JSON numbers in the
+    /// scheme, so those beyond ±2^53 are rounded.
+    ///
+    /// ```
+    /// use simd_json::json;
+    ///
+    /// let v = json!({"b": [1e21, 0.5], "a": "€"});
+    /// assert_eq!(v.encode_canonical().unwrap(), r#"{"a":"€","b":[1e+21,0.5]}"#);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value contains NaN or an infinity
+    pub fn encode_canonical(&self) -> Result<String> {
+        let mut json = Vec::with_capacity(512);
+        stry!(self.write_canonical(&mut json));
+        Ok(unsafe { String::from_utf8_unchecked(json) })
+    }
+
+    /// Writes the value as canonical JSON (RFC 8785) to `w`, see
+    /// `encode_canonical`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value contains NaN or an infinity or
+    /// writing fails
+    pub fn write_canonical<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_owned(w, self)
+    }
+}
+
+impl<S> borrowed::HashedValue<'_, S> {
+    /// Encodes the value as canonical JSON (RFC 8785), equal values
+    /// always produce the same bytes.
+    ///
+    /// Integers are written as doubles like all JSON numbers in the
+    /// scheme, so those beyond ±2^53 are rounded.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value contains NaN or an infinity
+    pub fn encode_canonical(&self) -> Result<String> {
+        let mut json = Vec::with_capacity(512);
+        stry!(self.write_canonical(&mut json));
+        Ok(unsafe { String::from_utf8_unchecked(json) })
+    }
+
+    /// Writes the value as canonical JSON (RFC 8785) to `w`, see
+    /// `encode_canonical`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value contains NaN or an infinity or
+    /// writing fails
+    pub fn write_canonical<W: Write>(&self, w: &mut W) -> Result<()> {
+        write_borrowed(w, self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::OwnedValue;
+
+    fn number(bits: u64) -> String {
+        let mut w = Vec::new();
+        write_f64(&mut w, f64::from_bits(bits)).unwrap();
+        String::from_utf8(w).unwrap()
+    }
+
+    #[test]
+    fn numbers() {
+        // the samples from appendix B of RFC 8785
+        let samples = [
+            (0x0000_0000_0000_0000, "0"),
+            (0x8000_0000_0000_0000, "0"),
+            (0x0000_0000_0000_0001, "5e-324"),
+            (0x8000_0000_0000_0001, "-5e-324"),
+            (0x7fef_ffff_ffff_ffff, "1.7976931348623157e+308"),
+            (0xffef_ffff_ffff_ffff, "-1.7976931348623157e+308"),
+            (0x4340_0000_0000_0000, "9007199254740992"),
+            (0xc340_0000_0000_0000, "-9007199254740992"),
+            (0x4430_0000_0000_0000, "295147905179352830000"),
+            (0x44b5_2d02_c7e1_4af5, "9.999999999999997e+22"),
+            (0x44b5_2d02_c7e1_4af6, "1e+23"),
+            (0x44b5_2d02_c7e1_4af7, "1.0000000000000001e+23"),
+            (0x444b_1ae4_d6e2_ef4e, "999999999999999700000"),
+            (0x444b_1ae4_d6e2_ef4f, "999999999999999900000"),
+            (0x444b_1ae4_d6e2_ef50, "1e+21"),
+            (0x3eb0_c6f7_a0b5_ed8c, "9.999999999999997e-7"),
+            (0x3eb0_c6f7_a0b5_ed8d, "0.000001"),
+            (0x41b3_de43_5555_5553, "333333333.3333332"),
+            (0x41b3_de43_5555_5554, "333333333.33333325"),
+            (0x41b3_de43_5555_5555, "333333333.3333333"),
+            (0x41b3_de43_5555_5556, "333333333.3333334"),
+            (0x41b3_de43_5555_5557, "333333333.33333343"),
+            (0xbecb_f647_612f_3696, "-0.0000033333333333333333"),
+            (0x4314_3ff3_c1cb_0959, "1424953923781206.2"),
+        ];
+        for (bits, expected) in &samples {
+            assert_eq!(number(*bits), *expected, "{:x}", bits);
+        }
+        assert_eq!(number(4.5_f64.to_bits()), "4.5");
+        assert_eq!(number(0.002_f64.to_bits()), "0.002");
+        assert_eq!(number(100.0_f64.to_bits()), "100");
+        assert!(write_f64(&mut Vec::new(), f64::NAN).is_err());
+        assert!(write_f64(&mut Vec::new(), f64::NEG_INFINITY).is_err());
+    }
+
+    #[test]
+    fn integers() {
+        let v = OwnedValue::from(vec![
+            OwnedValue::from(-42_i64),
+            OwnedValue::from(9_007_199_254_740_993_u64),
+            OwnedValue::from(u64::MAX),
+        ]);
+        assert_eq!(
+            v.encode_canonical().unwrap(),
+            "[-42,9007199254740992,18446744073709552000]"
+        );
+    }
+
+    #[test]
+    fn key_order() {
+        // sorted by UTF-16 code units, so the surrogate pair of U+1F600
+        // comes before U+FB33
+        let mut d =
+            br#"{"\u20ac":1,"\r":2,"\ufb33":3,"1":4,"\ud83d\ude00":5,"\u0080":6,"\u00f6":7}"#
+                .to_vec();
+        let v = crate::to_borrowed_value(&mut d).unwrap();
+        assert_eq!(
+            v.encode_canonical().unwrap(),
+            "{\"\\r\":2,\"1\":4,\"\u{80}\":6,\"\u{f6}\":7,\"\u{20ac}\":1,\"\u{1f600}\":5,\"\u{fb33}\":3}"
+        );
+    }
+
+    #[test]
+    fn rfc_example() {
+        let mut d = br#"{
+            "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001],
+            "string": "\u20ac$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+            "literals": [null, true, false]
+        }"#
+        .to_vec();
+        let expected = r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"string":"€$\u000f\nA'B\"\\\\\"/"}"#;
+        let v = crate::to_owned_value(&mut d.clone()).unwrap();
+        assert_eq!(v.encode_canonical().unwrap(), expected);
+        let v = crate::to_borrowed_value(&mut d).unwrap();
+        assert_eq!(v.encode_canonical().unwrap(), expected);
+    }
+}
@@ -0,0 +1,619 @@
+//! Serialization and deserialization without serde.
+//!
+//! The `Serialize` and `Deserialize` derives from `simd-json-derive`
+//! generate code that reads fields straight from the tape of a
+//! [`Deserializer`] and writes JSON straight into a [`BaseGenerator`],
+//! without going through serde's visitors. They understand the common
+//! serde attributes (`rename`, `rename_all`, `default`, `skip`,
+//! `skip_serializing`, `skip_deserializing`, `skip_serializing_if` and
+//! `deny_unknown_fields`) in both `#[serde(...)]` and `#[simd_json(...)]`
+//! form, so a type can derive both. Missing `Option` fields are `None`.
+//! Enums are limited to unit variants.
+//!
+//! Errors carry the index of the node they were encountered at on the
+//! tape instead of a byte offset, see [`Deserializer::node_error`].
+//!
+//! ```
+//! use simd_json::derive::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize, Debug, PartialEq)]
+//! #[simd_json(rename_all = "camelCase")]
+//! struct User<'input> {
+//!     user_name: &'input str,
+//!     #[simd_json(rename = "mail")]
+//!     email: Option<String>,
+//!     #[simd_json(default)]
+//!     logins: u64,
+//! }
+//!
+//! let mut d = br#"{"userName": "badger", "ignored": [1, 2, 3]}"#.to_vec();
+//! let user = User::from_slice(&mut d).unwrap();
+//! assert_eq!(
+//!     user,
+//!     User { user_name: "badger", email: None, logins: 0 }
+//! );
+//! assert_eq!(
+//!     user.json_string().unwrap(),
+//!     r#"{"userName":"badger","mail":null,"logins":0}"#
+//! );
+//! ```
+
+use crate::format::{CompactGenerator, FormattedGenerator};
+use crate::{stry, Deserializer, ErrorType, Escape, Node, PrettyFormatter, Result, StaticNode};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::io::{self, Write};
+use value_trait::ValueAccess;
+
+pub use simd_json_derive::{Deserialize, Serialize};
+pub use value_trait::generator::BaseGenerator;
+
+/// A type that can be written as JSON.
+pub trait Serialize {
+    /// Writes `self` as JSON into a generator.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to the generator fails.
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator;
+
+    /// Writes `self` as compact JSON to a writer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    #[inline]
+    fn json_write_to<W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.json_write(&mut CompactGenerator::new(w, Escape::new()))
+    }
+
+    /// Writes `self` as pretty printed JSON to a writer.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails.
+    #[inline]
+    fn json_write_pp_to<W>(&self, w: &mut W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        self.json_write(&mut FormattedGenerator::new(
+            w,
+            PrettyFormatter::new(),
+            Escape::new(),
+        ))
+    }
+
+    /// Encodes `self` as compact JSON.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an implementation of `json_write` fails.
+    #[inline]
+    fn json_vec(&self) -> io::Result<Vec<u8>> {
+        let mut v = Vec::with_capacity(512);
+        stry!(self.json_write_to(&mut v));
+        Ok(v)
+    }
+
+    /// Encodes `self` as a compact JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an implementation of `json_write` fails.
+    #[inline]
+    fn json_string(&self) -> io::Result<String> {
+        let v = stry!(self.json_vec());
+        // the generators only ever write valid UTF-8
+        Ok(unsafe { String::from_utf8_unchecked(v) })
+    }
+
+    /// Encodes `self` as a pretty printed JSON string.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if an implementation of `json_write` fails.
+    #[inline]
+    fn json_string_pp(&self) -> io::Result<String> {
+        let mut v = Vec::with_capacity(512);
+        stry!(self.json_write_pp_to(&mut v));
+        // the generators only ever write valid UTF-8
+        Ok(unsafe { String::from_utf8_unchecked(v) })
+    }
+}
+
+/// A type that can be read from the tape of a [`Deserializer`].
+pub trait Deserialize<'input>: Sized {
+    /// Reads the next value from the tape and moves the cursor past it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the value on the tape doesn't match `Self`.
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self>;
+
+    /// The value of a field of this type when its key is missing, `None`
+    /// makes the field required.
+    #[inline]
+    #[must_use]
+    fn missing() -> Option<Self> {
+        None
+    }
+
+    /// Parses a byte slice, the slice is rewritten in the process.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is invalid JSON or doesn't match `Self`.
+    #[inline]
+    fn from_slice(json: &'input mut [u8]) -> Result<Self> {
+        let mut de = stry!(Deserializer::from_slice(json));
+        Self::from_tape(&mut de)
+    }
+
+    /// Parses a string, the string is rewritten in the process.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is invalid JSON or doesn't match `Self`.
+    #[inline]
+    fn from_str(json: &'input mut str) -> Result<Self> {
+        Self::from_slice(unsafe { json.as_bytes_mut() })
+    }
+}
+
+impl<T> Serialize for &T
+where
+    T: Serialize + ?Sized,
+{
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        T::json_write(self, g)
+    }
+}
+
+impl<T> Serialize for Box<T>
+where
+    T: Serialize + ?Sized,
+{
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        T::json_write(self, g)
+    }
+}
+
+impl<'input, T> Deserialize<'input> for Box<T>
+where
+    T: Deserialize<'input>,
+{
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        T::from_tape(de).map(Box::new)
+    }
+}
+
+impl Serialize for () {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write(b"null")
+    }
+}
+
+impl<'input> Deserialize<'input> for () {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::Static(StaticNode::Null) => Ok(()),
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedNull,
+            )),
+        }
+    }
+}
+
+impl Serialize for bool {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write(if *self { b"true" } else { b"false" })
+    }
+}
+
+impl<'input> Deserialize<'input> for bool {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::Static(s) => s.as_bool().ok_or_else(|| {
+                Deserializer::node_error(de.node_index(), ErrorType::ExpectedBoolean)
+            }),
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedBoolean,
+            )),
+        }
+    }
+}
+
+macro_rules! int {
+    ($t:ty, $as:ident, $error:ident) => {
+        impl Serialize for $t {
+            #[inline]
+            fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+            where
+                G: BaseGenerator,
+            {
+                g.write_int(*self)
+            }
+        }
+
+        impl<'input> Deserialize<'input> for $t {
+            #[inline]
+            fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+                match stry!(de.next_node()) {
+                    Node::Static(s) => s.$as().ok_or_else(|| {
+                        Deserializer::node_error(de.node_index(), ErrorType::$error)
+                    }),
+                    _ => Err(Deserializer::node_error(de.node_index(), ErrorType::$error)),
+                }
+            }
+        }
+    };
+}
+
+int!(i8, as_i8, ExpectedSigned);
+int!(i16, as_i16, ExpectedSigned);
+int!(i32, as_i32, ExpectedSigned);
+int!(i64, as_i64, ExpectedSigned);
+int!(u8, as_u8, ExpectedUnsigned);
+int!(u16, as_u16, ExpectedUnsigned);
+int!(u32, as_u32, ExpectedUnsigned);
+int!(u64, as_u64, ExpectedUnsigned);
+int!(usize, as_usize, ExpectedUnsigned);
+
+impl Serialize for f64 {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write_float(*self)
+    }
+}
+
+impl<'input> Deserialize<'input> for f64 {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::Static(s) => s
+                .cast_f64()
+                .ok_or_else(|| Deserializer::node_error(de.node_index(), ErrorType::ExpectedFloat)),
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedFloat,
+            )),
+        }
+    }
+}
+
+impl Serialize for f32 {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write_float(f64::from(*self))
+    }
+}
+
+impl<'input> Deserialize<'input> for f32 {
+    #[inline]
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        f64::from_tape(de).map(|f| f as f32)
+    }
+}
+
+impl Serialize for str {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write_string(self)
+    }
+}
+
+impl Serialize for String {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write_string(self)
+    }
+}
+
+impl Serialize for Cow<'_, str> {
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        g.write_string(self)
+    }
+}
+
+impl<'input> Deserialize<'input> for &'input str {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::String(s) => Ok(s),
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedString,
+            )),
+        }
+    }
+}
+
+impl<'input> Deserialize<'input> for String {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        <&str>::from_tape(de).map(ToString::to_string)
+    }
+}
+
+impl<'input> Deserialize<'input> for Cow<'input, str> {
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        <&str>::from_tape(de).map(Cow::Borrowed)
+    }
+}
+
+impl<T> Serialize for Option<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        match self {
+            Some(v) => v.json_write(g),
+            None => g.write(b"null"),
+        }
+    }
+}
+
+impl<'input, T> Deserialize<'input> for Option<T>
+where
+    T: Deserialize<'input>,
+{
+    #[inline]
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.peek_node()) {
+            Node::Static(StaticNode::Null) => {
+                stry!(de.next_node());
+                Ok(None)
+            }
+            _ => T::from_tape(de).map(Some),
+        }
+    }
+
+    #[inline]
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T> Serialize for [T]
+where
+    T: Serialize,
+{
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        let mut iter = self.iter();
+        if let Some(first) = iter.next() {
+            stry!(g.write(b"["));
+            g.indent();
+            stry!(g.new_line());
+            stry!(first.json_write(g));
+            for v in iter {
+                stry!(g.write(b","));
+                stry!(g.new_line());
+                stry!(v.json_write(g));
+            }
+            g.dedent();
+            stry!(g.new_line());
+            g.write(b"]")
+        } else {
+            g.write(b"[]")
+        }
+    }
+}
+
+impl<T> Serialize for Vec<T>
+where
+    T: Serialize,
+{
+    #[inline]
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        self.as_slice().json_write(g)
+    }
+}
+
+impl<'input, T> Deserialize<'input> for Vec<T>
+where
+    T: Deserialize<'input>,
+{
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::Array(len, _) => {
+                let mut v = Vec::with_capacity(len);
+                for _ in 0..len {
+                    v.push(stry!(T::from_tape(de)));
+                }
+                Ok(v)
+            }
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedArray,
+            )),
+        }
+    }
+}
+
+impl<K, V, S> Serialize for HashMap<K, V, S>
+where
+    K: AsRef<str>,
+    V: Serialize,
+{
+    fn json_write<G>(&self, g: &mut G) -> io::Result<()>
+    where
+        G: BaseGenerator,
+    {
+        let mut iter = self.iter();
+        if let Some((k, v)) = iter.next() {
+            stry!(g.write(b"{"));
+            g.indent();
+            stry!(g.new_line());
+            stry!(g.write_string(k.as_ref()));
+            stry!(g.write_min(b": ", b':'));
+            stry!(v.json_write(g));
+            for (k, v) in iter {
+                stry!(g.write(b","));
+                stry!(g.new_line());
+                stry!(g.write_string(k.as_ref()));
+                stry!(g.write_min(b": ", b':'));
+                stry!(v.json_write(g));
+            }
+            g.dedent();
+            stry!(g.new_line());
+            g.write(b"}")
+        } else {
+            g.write(b"{}")
+        }
+    }
+}
+
+impl<'input, K, V, S> Deserialize<'input> for HashMap<K, V, S>
+where
+    K: From<&'input str> + Eq + Hash,
+    V: Deserialize<'input>,
+    S: BuildHasher + Default,
+{
+    fn from_tape(de: &mut Deserializer<'input>) -> Result<Self> {
+        match stry!(de.next_node()) {
+            Node::Object(len, _) => {
+                let mut m = HashMap::with_capacity_and_hasher(len, S::default());
+                for _ in 0..len {
+                    let k = stry!(<&str>::from_tape(de));
+                    m.insert(K::from(k), stry!(V::from_tape(de)));
+                }
+                Ok(m)
+            }
+            _ => Err(Deserializer::node_error(
+                de.node_index(),
+                ErrorType::ExpectedMap,
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::{Deserialize, Deserializer, ErrorType, Serialize};
+    use std::collections::HashMap;
+
+    #[test]
+    fn primitives() {
+        type T<'a> = (
+            u8,
+            i64,
+            f32,
+            bool,
+            Option<u8>,
+            &'a str,
+            Vec<u8>,
+            HashMap<String, Vec<Option<u8>>>,
+        );
+        let mut d = br#"[1, -2, 3.5, true, null, "snot", [], {"a": [null, 1]}]"#.to_vec();
+        let mut de = crate::Deserializer::from_slice(&mut d).unwrap();
+        assert_eq!(de.next_node().unwrap(), crate::Node::Array(8, 14));
+        let t: T = (
+            u8::from_tape(&mut de).unwrap(),
+            i64::from_tape(&mut de).unwrap(),
+            f32::from_tape(&mut de).unwrap(),
+            bool::from_tape(&mut de).unwrap(),
+            Option::from_tape(&mut de).unwrap(),
+            <&str>::from_tape(&mut de).unwrap(),
+            Vec::from_tape(&mut de).unwrap(),
+            HashMap::from_tape(&mut de).unwrap(),
+        );
+        assert_eq!(t.0, 1);
+        assert_eq!(t.1, -2);
+        assert!((t.2 - 3.5).abs() < f32::EPSILON);
+        assert!(t.3);
+        assert_eq!(t.4, None);
+        assert_eq!(t.5, "snot");
+        assert!(t.6.is_empty());
+        assert_eq!(t.7["a"], vec![None, Some(1)]);
+        assert!(de.next_node().is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let mut d = b"-1".to_vec();
+        assert!(u8::from_slice(&mut d).is_err());
+        let mut d = b"256".to_vec();
+        assert!(u8::from_slice(&mut d).is_err());
+        let mut d = br#""1""#.to_vec();
+        assert!(u8::from_slice(&mut d).is_err());
+        let mut d = b"{}".to_vec();
+        assert!(Vec::<u8>::from_slice(&mut d).is_err());
+        let mut d = br#"[1, "2"]"#.to_vec();
+        assert_eq!(
+            Vec::<u8>::from_slice(&mut d).unwrap_err(),
+            Deserializer::node_error(3, ErrorType::ExpectedUnsigned)
+        );
+    }
+
+    #[test]
+    fn serialize() {
+        let mut m: HashMap<&str, Vec<Option<f64>>> = HashMap::new();
+        m.insert("a", vec![Some(1.5), None]);
+        assert_eq!(vec![1_u8, 2].json_string().unwrap(), "[1,2]");
+        assert_eq!("s\"t".json_string().unwrap(), r#""s\"t""#);
+        assert_eq!(false.json_string().unwrap(), "false");
+        assert_eq!(m.json_string().unwrap(), r#"{"a":[1.5,null]}"#);
+        assert_eq!(
+            m.json_string_pp().unwrap(),
+            "{\n  \"a\": [\n    1.5,\n    null\n  ]\n}"
+        );
+        assert_eq!(Vec::<u8>::new().json_string_pp().unwrap(), "[]");
+    }
+}
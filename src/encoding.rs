@@ -0,0 +1,430 @@
+//! Detection and transcoding of the encodings JSON text may come in.
+//!
+//! RFC 8259 requires JSON exchanged between systems to be UTF-8, but
+//! RFC 4627 allowed UTF-16 and UTF-32 as well and some producers still
+//! send them - often with a byte order mark in front. We detect these
+//! encodings by looking at the first four bytes and transcode them to
+//! UTF-8 before handing the data to stage 1.
+
+use crate::charutils::codepoint_to_utf8;
+use crate::{Deserializer, Error, ErrorType, Result};
+
+#[cfg(any(target_feature = "sse4.2", target_feature = "avx2"))]
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m128i, _mm_and_si128, _mm_cmpeq_epi16, _mm_cmpeq_epi32, _mm_cvtsi128_si32, _mm_loadu_si128,
+    _mm_movemask_epi8, _mm_packs_epi32, _mm_packus_epi16, _mm_set1_epi16, _mm_set1_epi32,
+    _mm_setzero_si128, _mm_srli_epi16, _mm_srli_epi32, _mm_storel_epi64,
+};
+#[cfg(any(target_feature = "sse4.2", target_feature = "avx2"))]
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m128i, _mm_and_si128, _mm_cmpeq_epi16, _mm_cmpeq_epi32, _mm_cvtsi128_si32, _mm_loadu_si128,
+    _mm_movemask_epi8, _mm_packs_epi32, _mm_packus_epi16, _mm_set1_epi16, _mm_set1_epi32,
+    _mm_setzero_si128, _mm_srli_epi16, _mm_srli_epi32, _mm_storel_epi64,
+};
+
+/// Encoding of a JSON text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, the only encoding RFC 8259 allows
+    Utf8,
+    /// UTF-16 little endian
+    Utf16Le,
+    /// UTF-16 big endian
+    Utf16Be,
+    /// UTF-32 little endian
+    Utf32Le,
+    /// UTF-32 big endian
+    Utf32Be,
+}
+
+impl Encoding {
+    /// Detects the encoding of `input` and the length of its byte order
+    /// mark, if there is one.
+    ///
+    /// A byte order mark takes precedence, without one we use the
+    /// heuristic from RFC 4627: the first two characters of a JSON text
+    /// are always ASCII so the pattern of zero bytes in the first four
+    /// bytes gives away the encoding.
+    ///
+    /// ```rust
+    /// use simd_json::Encoding;
+    /// assert_eq!(Encoding::detect(b"\xEF\xBB\xBF{}"), (Encoding::Utf8, 3));
+    /// assert_eq!(Encoding::detect(b"[\x001\x00]\x00"), (Encoding::Utf16Le, 0));
+    /// assert_eq!(Encoding::detect(b"{}"), (Encoding::Utf8, 0));
+    /// ```
+    #[must_use]
+    pub fn detect(input: &[u8]) -> (Self, usize) {
+        match input {
+            [0xEF, 0xBB, 0xBF, ..] => (Self::Utf8, 3),
+            [0x00, 0x00, 0xFE, 0xFF, ..] => (Self::Utf32Be, 4),
+            [0xFF, 0xFE, 0x00, 0x00, ..] => (Self::Utf32Le, 4),
+            [0xFE, 0xFF, ..] => (Self::Utf16Be, 2),
+            [0xFF, 0xFE, ..] => (Self::Utf16Le, 2),
+            [0x00, 0x00, 0x00, b, ..] if *b != 0 => (Self::Utf32Be, 0),
+            [a, 0x00, 0x00, 0x00, ..] if *a != 0 => (Self::Utf32Le, 0),
+            [0x00, a, 0x00, b, ..] if *a != 0 && *b != 0 => (Self::Utf16Be, 0),
+            [a, 0x00, b, 0x00, ..] if *a != 0 && *b != 0 => (Self::Utf16Le, 0),
+            // A single character document can't show the four byte
+            // pattern so we fall back to two bytes
+            [0x00, a] if *a != 0 => (Self::Utf16Be, 0),
+            [a, 0x00] if *a != 0 => (Self::Utf16Le, 0),
+            _ => (Self::Utf8, 0),
+        }
+    }
+
+    /// Width of a code unit in bytes
+    fn unit_len(self) -> usize {
+        match self {
+            Self::Utf8 => 1,
+            Self::Utf16Le | Self::Utf16Be => 2,
+            Self::Utf32Le | Self::Utf32Be => 4,
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn read_unit(self, src: &[u8]) -> u32 {
+        match self {
+            Self::Utf8 => u32::from(src[0]),
+            Self::Utf16Le => u32::from(u16::from_le_bytes([src[0], src[1]])),
+            Self::Utf16Be => u32::from(u16::from_be_bytes([src[0], src[1]])),
+            Self::Utf32Le => u32::from_le_bytes([src[0], src[1], src[2], src[3]]),
+            Self::Utf32Be => u32::from_be_bytes([src[0], src[1], src[2], src[3]]),
+        }
+    }
+
+    /// Decodes the code point at the start of `src`, returns the code point
+    /// and the number of bytes it occupied.
+    fn decode(self, src: &[u8]) -> std::result::Result<(u32, usize), ErrorType> {
+        let unit_len = self.unit_len();
+        if src.len() < unit_len {
+            return Err(ErrorType::InvalidUtf8);
+        }
+        let unit = self.read_unit(src);
+        match self {
+            Self::Utf16Le | Self::Utf16Be => match unit {
+                0xD800..=0xDBFF => {
+                    if src.len() < 4 {
+                        return Err(ErrorType::InvalidUtf8);
+                    }
+                    let low = self.read_unit(&src[2..]);
+                    if (0xDC00..=0xDFFF).contains(&low) {
+                        Ok(((((unit - 0xD800) << 10) | (low - 0xDC00)) + 0x10000, 4))
+                    } else {
+                        Err(ErrorType::InvalidUtf8)
+                    }
+                }
+                0xDC00..=0xDFFF => Err(ErrorType::InvalidUtf8),
+                _ => Ok((unit, 2)),
+            },
+            Self::Utf32Le | Self::Utf32Be => {
+                if unit > 0x0010_FFFF || (0xD800..=0xDFFF).contains(&unit) {
+                    Err(ErrorType::InvalidUtf8)
+                } else {
+                    Ok((unit, 4))
+                }
+            }
+            Self::Utf8 => Ok((unit, 1)),
+        }
+    }
+}
+
+/// Transcodes `input`, encoded as `encoding` and stripped of its byte
+/// order mark, to UTF-8. Errors carry the offset into `input`.
+pub(crate) fn transcode_to_utf8(input: &[u8], encoding: Encoding) -> Result<Vec<u8>> {
+    // Most JSON is ASCII so one byte per code unit is the best guess
+    let mut res = Vec::with_capacity(input.len() / encoding.unit_len() + 64);
+    let mut buf = [0_u8; 4];
+    let mut idx = 0;
+    while idx < input.len() {
+        idx += ascii_prefix(&input[idx..], encoding, &mut res);
+        if idx >= input.len() {
+            break;
+        }
+        let (cp, len) = match encoding.decode(&input[idx..]) {
+            Ok(r) => r,
+            Err(e) => return Err(Error::new(idx, '?', e)),
+        };
+        let written = codepoint_to_utf8(cp, &mut buf);
+        res.extend_from_slice(&buf[..written]);
+        idx += len;
+    }
+    Ok(res)
+}
+
+/// Maps an offset into the UTF-8 transcoding of `input` back to an
+/// offset into `input` itself.
+pub(crate) fn original_offset(input: &[u8], encoding: Encoding, utf8_offset: usize) -> usize {
+    let mut buf = [0_u8; 4];
+    let mut utf8_idx = 0;
+    let mut idx = 0;
+    while utf8_idx < utf8_offset && idx < input.len() {
+        if let Ok((cp, len)) = encoding.decode(&input[idx..]) {
+            utf8_idx += codepoint_to_utf8(cp, &mut buf);
+            idx += len;
+        } else {
+            break;
+        }
+    }
+    idx
+}
+
+/// Copies the leading run of ASCII code units from `src` to `dst` a
+/// block at a time, returns the number of bytes consumed from `src`.
+#[cfg(any(target_feature = "sse4.2", target_feature = "avx2"))]
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::cast_ptr_alignment
+)]
+fn ascii_prefix(src: &[u8], encoding: Encoding, dst: &mut Vec<u8>) -> usize {
+    let mut idx = 0;
+    unsafe {
+        match encoding {
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                // a code unit is ASCII if everything but the low 7 bit is zero,
+                // for big endian the lanes are byte swapped.
+                let mask = if encoding == Encoding::Utf16Le {
+                    _mm_set1_epi16(0xFF80_u16 as i16)
+                } else {
+                    _mm_set1_epi16(0x80FF_u16 as i16)
+                };
+                while idx + 16 <= src.len() {
+                    let v: __m128i = _mm_loadu_si128(src.as_ptr().add(idx).cast::<__m128i>());
+                    let non_ascii = _mm_cmpeq_epi16(_mm_and_si128(v, mask), _mm_setzero_si128());
+                    if _mm_movemask_epi8(non_ascii) != 0xFFFF {
+                        break;
+                    }
+                    let v = if encoding == Encoding::Utf16Le {
+                        v
+                    } else {
+                        _mm_srli_epi16(v, 8)
+                    };
+                    let packed = _mm_packus_epi16(v, v);
+                    let l = dst.len();
+                    dst.reserve(8);
+                    _mm_storel_epi64(dst.as_mut_ptr().add(l).cast::<__m128i>(), packed);
+                    dst.set_len(l + 8);
+                    idx += 16;
+                }
+            }
+            Encoding::Utf32Le | Encoding::Utf32Be => {
+                let mask = if encoding == Encoding::Utf32Le {
+                    _mm_set1_epi32(0xFFFF_FF80_u32 as i32)
+                } else {
+                    _mm_set1_epi32(0x80FF_FFFF_u32 as i32)
+                };
+                while idx + 16 <= src.len() {
+                    let v: __m128i = _mm_loadu_si128(src.as_ptr().add(idx).cast::<__m128i>());
+                    let non_ascii = _mm_cmpeq_epi32(_mm_and_si128(v, mask), _mm_setzero_si128());
+                    if _mm_movemask_epi8(non_ascii) != 0xFFFF {
+                        break;
+                    }
+                    let v = if encoding == Encoding::Utf32Le {
+                        v
+                    } else {
+                        _mm_srli_epi32(v, 24)
+                    };
+                    let v = _mm_packs_epi32(v, v);
+                    let packed = _mm_packus_epi16(v, v);
+                    dst.extend_from_slice(&_mm_cvtsi128_si32(packed).to_le_bytes());
+                    idx += 16;
+                }
+            }
+            Encoding::Utf8 => (),
+        }
+    }
+    idx
+}
+
+/// Copies the leading run of ASCII code units from `src` to `dst` a
+/// word at a time, returns the number of bytes consumed from `src`.
+#[cfg(not(any(target_feature = "sse4.2", target_feature = "avx2")))]
+#[allow(clippy::cast_possible_truncation)]
+fn ascii_prefix(src: &[u8], encoding: Encoding, dst: &mut Vec<u8>) -> usize {
+    // a code unit is ASCII if everything but the low 7 bit is zero,
+    // for big endian the low byte is the last byte of the code unit.
+    let (mask, shifts): (u64, &[u64]) = match encoding {
+        Encoding::Utf16Le => (0xFF80_FF80_FF80_FF80, &[0, 16, 32, 48]),
+        Encoding::Utf16Be => (0x80FF_80FF_80FF_80FF, &[8, 24, 40, 56]),
+        Encoding::Utf32Le => (0xFFFF_FF80_FFFF_FF80, &[0, 32]),
+        Encoding::Utf32Be => (0x80FF_FFFF_80FF_FFFF, &[24, 56]),
+        Encoding::Utf8 => return 0,
+    };
+    let mut idx = 0;
+    let mut word = [0_u8; 8];
+    while idx + 8 <= src.len() {
+        word.copy_from_slice(&src[idx..idx + 8]);
+        let w = u64::from_le_bytes(word);
+        if w & mask != 0 {
+            break;
+        }
+        dst.extend(shifts.iter().map(|s| (w >> s) as u8));
+        idx += 8;
+    }
+    idx
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer from a buffer in any of the encodings
+    /// JSON can come in. A UTF-8 byte order mark is skipped, UTF-16
+    /// and UTF-32 input (with or without a byte order mark) is transcoded
+    /// to UTF-8 and replaces the content of `input`.
+    ///
+    /// Error offsets refer to the original encoding of `input`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` is invalid JSON or isn't valid in
+    /// its detected encoding.
+    pub fn from_vec_any_encoding(input: &'de mut Vec<u8>) -> Result<Self> {
+        let (encoding, bom_len) = Encoding::detect(input);
+        if encoding == Encoding::Utf8 {
+            return Self::from_slice(&mut input[bom_len..]).map_err(|e| {
+                let idx = e.index() + bom_len;
+                e.with_index(idx)
+            });
+        }
+        let original = input.split_off(bom_len);
+        let utf8 = match transcode_to_utf8(&original, encoding) {
+            Ok(utf8) => utf8,
+            Err(e) => {
+                let idx = e.index() + bom_len;
+                return Err(e.with_index(idx));
+            }
+        };
+        *input = utf8;
+        Self::from_slice(input).map_err(|e| {
+            let idx = original_offset(&original, encoding, e.index()) + bom_len;
+            e.with_index(idx)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{to_owned_value, to_owned_value_any_encoding, OwnedValue};
+    use value_trait::Builder;
+
+    fn encode_utf16(s: &str, le: bool) -> Vec<u8> {
+        s.encode_utf16()
+            .flat_map(|u| if le { u.to_le_bytes() } else { u.to_be_bytes() })
+            .collect()
+    }
+
+    fn encode_utf32(s: &str, le: bool) -> Vec<u8> {
+        s.chars()
+            .map(u32::from)
+            .flat_map(|c| if le { c.to_le_bytes() } else { c.to_be_bytes() })
+            .collect()
+    }
+
+    const DOC: &str = r#"{"key": ["a long enough ascii string", "ünïcödé", "😀 emoji", 42]}"#;
+
+    fn expected() -> OwnedValue {
+        let mut d = DOC.as_bytes().to_vec();
+        to_owned_value(&mut d).expect("valid")
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Encoding::detect(b"{}"), (Encoding::Utf8, 0));
+        assert_eq!(Encoding::detect(b"1"), (Encoding::Utf8, 0));
+        assert_eq!(Encoding::detect(b"\xEF\xBB\xBF1"), (Encoding::Utf8, 3));
+        assert_eq!(Encoding::detect(b"\xFF\xFE1\x00"), (Encoding::Utf16Le, 2));
+        assert_eq!(Encoding::detect(b"\xFE\xFF\x001"), (Encoding::Utf16Be, 2));
+        assert_eq!(
+            Encoding::detect(b"\xFF\xFE\x00\x001\x00\x00\x00"),
+            (Encoding::Utf32Le, 4)
+        );
+        assert_eq!(
+            Encoding::detect(b"\x00\x00\xFE\xFF\x00\x00\x001"),
+            (Encoding::Utf32Be, 4)
+        );
+        assert_eq!(Encoding::detect(b"1\x00"), (Encoding::Utf16Le, 0));
+        assert_eq!(Encoding::detect(b"\x001"), (Encoding::Utf16Be, 0));
+        assert_eq!(
+            Encoding::detect(&encode_utf16("[1]", true)),
+            (Encoding::Utf16Le, 0)
+        );
+        assert_eq!(
+            Encoding::detect(&encode_utf16("[1]", false)),
+            (Encoding::Utf16Be, 0)
+        );
+        assert_eq!(
+            Encoding::detect(&encode_utf32("[1]", true)),
+            (Encoding::Utf32Le, 0)
+        );
+        assert_eq!(
+            Encoding::detect(&encode_utf32("[1]", false)),
+            (Encoding::Utf32Be, 0)
+        );
+    }
+
+    #[test]
+    fn utf8_bom() {
+        let mut d = b"\xEF\xBB\xBF".to_vec();
+        d.extend_from_slice(DOC.as_bytes());
+        assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+        let mut d = DOC.as_bytes().to_vec();
+        assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+    }
+
+    #[test]
+    fn utf16() {
+        for le in &[true, false] {
+            let mut d = encode_utf16(DOC, *le);
+            assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+            let mut d = encode_utf16("\u{FEFF}", *le);
+            d.append(&mut encode_utf16(DOC, *le));
+            assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+        }
+    }
+
+    #[test]
+    fn utf32() {
+        for le in &[true, false] {
+            let mut d = encode_utf32(DOC, *le);
+            assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+            let mut d = encode_utf32("\u{FEFF}", *le);
+            d.append(&mut encode_utf32(DOC, *le));
+            assert_eq!(to_owned_value_any_encoding(&mut d), Ok(expected()));
+        }
+    }
+
+    #[test]
+    fn single_char() {
+        let mut d = encode_utf16("1", true);
+        assert_eq!(to_owned_value_any_encoding(&mut d), Ok(OwnedValue::from(1)));
+        let mut d = encode_utf16("[]", false);
+        assert_eq!(to_owned_value_any_encoding(&mut d), Ok(OwnedValue::array()));
+    }
+
+    #[test]
+    fn lone_surrogate() {
+        let mut d = encode_utf16(r#"["ab"]"#, true);
+        // replace `b` with an unpaired high surrogate
+        d[6] = 0x00;
+        d[7] = 0xD8;
+        let e = to_owned_value_any_encoding(&mut d).expect_err("lone surrogate");
+        assert_eq!(e.index(), 6);
+    }
+
+    #[test]
+    fn error_offset() {
+        // the error is on the `x` after the emoji, which is 14 bytes into
+        // the UTF-16 input but only 9 in UTF-8.
+        let mut d = encode_utf16("[\"😀\", x]", true);
+        let e = to_owned_value_any_encoding(&mut d).expect_err("invalid");
+        assert_eq!(e.index(), 14);
+        let mut d = encode_utf16("\u{FEFF}", false);
+        d.append(&mut encode_utf16("[\"😀\", x]", false));
+        let e = to_owned_value_any_encoding(&mut d).expect_err("invalid");
+        assert_eq!(e.index(), 16);
+        let mut d = b"\xEF\xBB\xBF[1, x]".to_vec();
+        let e = to_owned_value_any_encoding(&mut d).expect_err("invalid");
+        assert_eq!(e.index(), 7);
+    }
+}
@@ -3,8 +3,10 @@ use std::fmt;
 /// Error types encountered while parsing
 #[derive(Debug)]
 pub enum ErrorType {
-    /// Simd-json only supports inputs of up to
-    /// 4GB in size.
+    /// Simd-json used to only support inputs of up to 4GB in size, larger
+    /// inputs are parsed with 64 bit structural indexes now so this error
+    /// is never returned.
+    #[deprecated(note = "inputs of any size are parsed, this error is never returned")]
     InputTooLarge,
     /// The key of a map isn't a string
     BadKeyType,
@@ -84,6 +86,15 @@ pub enum ErrorType {
     Overflow,
     /// IO error
     Io(std::io::Error),
+    /// A handler stopped parsing early
+    Stopped,
+    /// A required field is missing
+    MissingField(&'static str),
+    /// A field that isn't known
+    UnknownField(String),
+    /// A JSON pointer that is neither empty nor starts with `/`, or has a
+    /// `~` that isn't followed by `0` or `1`
+    InvalidPointer(String),
 }
 
 impl From<std::io::Error> for Error {
@@ -134,8 +145,12 @@ impl PartialEq for ErrorType {
             | (Self::ExpectedArrayContent, Self::ExpectedArrayContent)
             | (Self::ExpectedObjectContent, Self::ExpectedObjectContent)
             | (Self::ExpectedObjectKey, Self::ExpectedObjectKey)
-            | (Self::Overflow, Self::Overflow) => true,
+            | (Self::Overflow, Self::Overflow)
+            | (Self::Stopped, Self::Stopped) => true,
             (Self::Serde(s1), Self::Serde(s2)) => s1 == s2,
+            (Self::MissingField(f1), Self::MissingField(f2)) => f1 == f2,
+            (Self::UnknownField(f1), Self::UnknownField(f2)) => f1 == f2,
+            (Self::InvalidPointer(p1), Self::InvalidPointer(p2)) => p1 == p2,
             _ => false,
         }
     }
@@ -143,12 +158,15 @@ impl PartialEq for ErrorType {
 /// Parser error
 #[derive(Debug, PartialEq)]
 pub struct Error {
-    /// Byte index it was encountered at
+    /// Byte index it was encountered at, or the index of the node on the
+    /// tape for errors of the `derive` traits
     index: usize,
     /// Current character
     character: char,
     /// Tyep of error
     error: ErrorType,
+    /// Path of the value the error was encountered at
+    path: String,
 }
 
 impl Error {
@@ -157,6 +175,7 @@ impl Error {
             index,
             character,
             error,
+            path: String::new(),
         }
     }
     /// Create a generic error
@@ -166,19 +185,82 @@ impl Error {
             index: 0,
             character: '💩', //this is the poop emoji
             error: t,
+            path: String::new(),
         }
     }
+
+    /// Byte index the error was encountered at. Errors of the
+    /// `derive` traits carry the index of the node on the tape instead,
+    /// see [`Deserializer::node_error`](crate::Deserializer::node_error).
+    #[must_use]
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub(crate) fn with_index(mut self, index: usize) -> Self {
+        self.index = index;
+        self
+    }
+
+    /// Path of the value the error was encountered at when deserializing
+    /// with serde, like `.servers[2].host`. It is empty for the root
+    /// value and for errors outside of serde.
+    #[must_use]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Prepends the key of an object the error was encountered in to the
+    /// path, errors are built up from the inside out.
+    #[cfg(feature = "serde_impl")]
+    #[cold]
+    pub(crate) fn in_key(mut self, key: &str) -> Self {
+        self.path.insert_str(0, &key_segment(key));
+        self
+    }
+
+    /// Prepends the index of an array the error was encountered in to the
+    /// path.
+    #[cfg(feature = "serde_impl")]
+    #[cold]
+    pub(crate) fn in_element(mut self, index: usize) -> Self {
+        self.path.insert_str(0, &format!("[{}]", index));
+        self
+    }
 }
 impl std::error::Error for Error {}
 
+/// Formats a key as part of a path, keys that aren't plain names are
+/// quoted.
+#[cfg(feature = "serde_impl")]
+pub(crate) fn key_segment(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+    {
+        format!(".{}", key)
+    } else {
+        format!("[{:?}]", key)
+    }
+}
+
 #[cfg(not(tarpaulin_include))]
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{:?} at character {} ('{}')",
-            self.error, self.index, self.character
-        )
+        if self.path.is_empty() {
+            write!(
+                f,
+                "{:?} at character {} ('{}')",
+                self.error, self.index, self.character
+            )
+        } else {
+            write!(
+                f,
+                "{:?} at {}, character {} ('{}')",
+                self.error, self.path, self.index, self.character
+            )
+        }
     }
 }
 
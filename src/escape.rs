@@ -0,0 +1,247 @@
+//! Escaping of strings in serialized JSON.
+
+use crate::{escape_bits, ESCAPE_BLOCK};
+use std::io::{self, Write};
+
+/// Which characters are escaped in strings beyond what JSON requires.
+///
+/// By default only `"`, `\` and control characters are escaped, the
+/// options can be combined:
+///
+/// ```
+/// use simd_json::{json, Escape};
+///
+/// let v = json!("</script> & café");
+/// let e = Escape::new().html_safe(true).ascii_only(true).slash(true);
+/// assert_eq!(
+///     v.encode_escaped(e),
+///     r#""\u003c\/script\u003e \u0026 caf\u00e9""#
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Escape {
+    ascii_only: bool,
+    html_safe: bool,
+    slash: bool,
+}
+
+impl Escape {
+    /// Escapes only what JSON requires
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            ascii_only: false,
+            html_safe: false,
+            slash: false,
+        }
+    }
+
+    /// Escapes every non ASCII character as `\uXXXX`, characters outside
+    /// the basic multilingual plane as a surrogate pair
+    #[must_use]
+    pub const fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Escapes `<`, `>`, `&`, U+2028 and U+2029 so the output can be
+    /// embedded in HTML `<script>` tags
+    #[must_use]
+    pub const fn html_safe(mut self, html_safe: bool) -> Self {
+        self.html_safe = html_safe;
+        self
+    }
+
+    /// Escapes `/` as `\/`
+    #[must_use]
+    pub const fn slash(mut self, slash: bool) -> Self {
+        self.slash = slash;
+        self
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn is_minimal(self) -> bool {
+        !(self.ascii_only || self.html_safe || self.slash)
+    }
+
+    /// Writes the content of a string, the SIMD path is taken if only the
+    /// required escapes are wanted
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn write_content<W: Write>(self, writer: &mut W, string: &str) -> io::Result<()> {
+        if self.is_minimal() {
+            write_str_simd(writer, string.as_bytes())
+        } else {
+            self.write_complex(writer, string)
+        }
+    }
+
+    #[inline(never)]
+    fn write_complex<W: Write>(self, writer: &mut W, string: &str) -> io::Result<()> {
+        let mut start = 0;
+        for (index, ch) in string.char_indices() {
+            let required = ch == '"' || ch == '\\' || ch < ' ';
+            let wanted = match ch {
+                '/' => self.slash,
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if self.html_safe => true,
+                c => self.ascii_only && !c.is_ascii(),
+            };
+            if !(required || wanted) {
+                continue;
+            }
+            stry!(writer.write_all(&string.as_bytes()[start..index]));
+            if required {
+                stry!(write_escape(writer, string.as_bytes()[index]));
+            } else if ch == '/' {
+                stry!(writer.write_all(b"\\/"));
+            } else {
+                let mut units = [0; 2];
+                for unit in ch.encode_utf16(&mut units) {
+                    stry!(u_encode(writer, *unit));
+                }
+            }
+            start = index + ch.len_utf8();
+        }
+        writer.write_all(&string.as_bytes()[start..])
+    }
+}
+
+/// Writes the content of a string with the escapes JSON requires. Each
+/// block is checked with SIMD and the runs of bytes between escapes are
+/// written in one go, the tail that is shorter than a block is checked
+/// byte by byte.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub(crate) fn write_str_simd<W: Write>(writer: &mut W, string: &[u8]) -> io::Result<()> {
+    let len = string.len();
+    // first byte that isn't written yet
+    let mut start = 0;
+    let mut idx = 0;
+    while len - idx >= ESCAPE_BLOCK {
+        let mut bits = unsafe { escape_bits(string.as_ptr().add(idx)) };
+        while bits != 0 {
+            let pos = idx + bits.trailing_zeros() as usize;
+            stry!(writer.write_all(&string[start..pos]));
+            stry!(write_escape(writer, string[pos]));
+            start = pos + 1;
+            bits &= bits - 1;
+        }
+        idx += ESCAPE_BLOCK;
+    }
+    for (pos, byte) in string.iter().enumerate().skip(idx) {
+        if NEEDS_ESCAPE[*byte as usize] {
+            stry!(writer.write_all(&string[start..pos]));
+            stry!(write_escape(writer, *byte));
+            start = pos + 1;
+        }
+    }
+    writer.write_all(&string[start..])
+}
+
+/// Bytes that have to be escaped in JSON strings
+static NEEDS_ESCAPE: [bool; 256] = {
+    let mut table = [false; 256];
+    let mut byte = 0;
+    while byte < 0x20 {
+        table[byte] = true;
+        byte += 1;
+    }
+    table[b'"' as usize] = true;
+    table[b'\\' as usize] = true;
+    table
+};
+
+/// Writes the escape sequence for a quote, backslash or control character
+#[inline(never)]
+fn write_escape<W: Write>(writer: &mut W, byte: u8) -> io::Result<()> {
+    match byte {
+        b'"' => writer.write_all(b"\\\""),
+        b'\\' => writer.write_all(b"\\\\"),
+        0x08 => writer.write_all(b"\\b"),
+        b'\t' => writer.write_all(b"\\t"),
+        b'\n' => writer.write_all(b"\\n"),
+        0x0c => writer.write_all(b"\\f"),
+        b'\r' => writer.write_all(b"\\r"),
+        _ => u_encode(writer, u16::from(byte)),
+    }
+}
+
+fn u_encode<W: Write>(writer: &mut W, unit: u16) -> io::Result<()> {
+    static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+    writer.write_all(&[
+        b'\\',
+        b'u',
+        HEX_DIGITS[(unit >> 12) as usize],
+        HEX_DIGITS[(unit >> 8 & 0xF) as usize],
+        HEX_DIGITS[(unit >> 4 & 0xF) as usize],
+        HEX_DIGITS[(unit & 0xF) as usize],
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn escaped(e: Escape, s: &str) -> String {
+        let mut w = Vec::new();
+        e.write_content(&mut w, s).expect("writing failed");
+        String::from_utf8(w).expect("invalid utf8")
+    }
+
+    #[test]
+    fn minimal() {
+        let s = "a\"b\\c\n\u{1}/<é😀\u{2028}";
+        assert_eq!(
+            escaped(Escape::new(), s),
+            "a\\\"b\\\\c\\n\\u0001/<é😀\u{2028}"
+        );
+        assert_eq!(
+            escaped(Escape::new().slash(false).html_safe(false), s),
+            escaped(Escape::new(), s)
+        );
+    }
+
+    #[test]
+    fn simd_blocks() {
+        // escapes at every position of strings spanning several blocks
+        // need to match the scalar path
+        for len in 0..(3 * ESCAPE_BLOCK + 3) {
+            for pos in 0..len {
+                for special in &["\"", "\\", "\n", "\u{1f}", "\u{0}", "é"] {
+                    let mut s: String = "ab/c~\u{7f}".chars().cycle().take(len).collect();
+                    s.replace_range(pos..=pos, special);
+                    let mut simd = Vec::new();
+                    write_str_simd(&mut simd, s.as_bytes()).expect("writing failed");
+                    let mut scalar = Vec::new();
+                    Escape::new()
+                        .write_complex(&mut scalar, &s)
+                        .expect("writing failed");
+                    assert_eq!(simd, scalar, "{:?}", s);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_only() {
+        let e = Escape::new().ascii_only(true);
+        assert_eq!(escaped(e, "aé😀\n"), "a\\u00e9\\ud83d\\ude00\\n");
+        assert_eq!(escaped(e, "\u{2028}x"), "\\u2028x");
+    }
+
+    #[test]
+    fn html_safe() {
+        let e = Escape::new().html_safe(true);
+        assert_eq!(
+            escaped(e, "<a href='x'>&\u{2029}é</a>"),
+            "\\u003ca href='x'\\u003e\\u0026\\u2029é\\u003c/a\\u003e"
+        );
+    }
+
+    #[test]
+    fn slash() {
+        let e = Escape::new().slash(true);
+        assert_eq!(escaped(e, "a/b\t/"), "a\\/b\\t\\/");
+        let mut w = Vec::new();
+        e.write_content(&mut w, "</").expect("writing failed");
+        assert_eq!(w, b"<\\/");
+    }
+}
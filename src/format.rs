@@ -0,0 +1,283 @@
+//! Layout of serialized JSON.
+
+use crate::Escape;
+use std::io::{self, Write};
+use value_trait::generator::BaseGenerator;
+
+/// Decides on the whitespace between the tokens of serialized JSON.
+///
+/// The serializers call it between the elements of arrays and objects and
+/// between keys and values, everything else is written by them. The
+/// defaults produce compact JSON.
+pub trait Formatter {
+    /// Writes the separator between an object key and its value
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing fails
+    fn write_key_separator<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+    {
+        writer.write_all(b":")
+    }
+
+    /// Called before every element of a non empty array or object and
+    /// before its closing bracket, `depth` is the nesting level of what
+    /// follows.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing fails
+    fn write_new_line<W>(&mut self, _writer: &mut W, _depth: usize) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+    {
+        Ok(())
+    }
+}
+
+/// Writes JSON without any whitespace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// Writes every element on a line of its own, indented by its depth.
+///
+/// ```
+/// use simd_json::{json, PrettyFormatter};
+///
+/// let v = json!({"a": [1]});
+/// let tabs = PrettyFormatter::new().indent(b"\t").space_after_colon(false);
+/// assert_eq!(v.encode_formatted(tabs), "{\n\t\"a\":[\n\t\t1\n\t]\n}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrettyFormatter {
+    indent: Vec<u8>,
+    line_ending: Vec<u8>,
+    space_after_colon: bool,
+    /// The line ending followed by the indentation of the deepest line
+    /// written so far, so a new line takes a single write
+    line: Vec<u8>,
+}
+
+impl PartialEq for PrettyFormatter {
+    fn eq(&self, other: &Self) -> bool {
+        self.indent == other.indent
+            && self.line_ending == other.line_ending
+            && self.space_after_colon == other.space_after_colon
+    }
+}
+
+impl Eq for PrettyFormatter {}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PrettyFormatter {
+    /// Indents by two spaces, ends lines with `\n` and puts a space after
+    /// colons
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            indent: b"  ".to_vec(),
+            line_ending: b"\n".to_vec(),
+            space_after_colon: true,
+            line: b"\n".to_vec(),
+        }
+    }
+
+    /// Sets what is written once per nesting level at the start of a line
+    #[must_use]
+    pub fn indent(mut self, indent: &[u8]) -> Self {
+        self.indent = indent.to_vec();
+        self.line = self.line_ending.clone();
+        self
+    }
+
+    /// Sets what is written at the end of a line, for example `\r\n`
+    #[must_use]
+    pub fn line_ending(mut self, line_ending: &[u8]) -> Self {
+        self.line_ending = line_ending.to_vec();
+        self.line = self.line_ending.clone();
+        self
+    }
+
+    /// Sets if a space is written between the colon and the value of an
+    /// object member
+    #[must_use]
+    pub fn space_after_colon(mut self, space_after_colon: bool) -> Self {
+        self.space_after_colon = space_after_colon;
+        self
+    }
+}
+
+impl Formatter for PrettyFormatter {
+    #[inline]
+    fn write_key_separator<W>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+    {
+        if self.space_after_colon {
+            writer.write_all(b": ")
+        } else {
+            writer.write_all(b":")
+        }
+    }
+
+    #[inline]
+    fn write_new_line<W>(&mut self, writer: &mut W, depth: usize) -> io::Result<()>
+    where
+        W: Write + ?Sized,
+    {
+        let len = self.line_ending.len() + depth * self.indent.len();
+        while self.line.len() < len {
+            self.line.extend_from_slice(&self.indent);
+        }
+        writer.write_all(&self.line[..len])
+    }
+}
+
+/// Generator for the value types that writes compact JSON
+pub(crate) struct CompactGenerator<'writer, W> {
+    writer: &'writer mut W,
+    escape: Escape,
+}
+
+impl<'writer, W> CompactGenerator<'writer, W>
+where
+    W: Write,
+{
+    pub(crate) fn new(writer: &'writer mut W, escape: Escape) -> Self {
+        Self { writer, escape }
+    }
+}
+
+impl<W> BaseGenerator for CompactGenerator<'_, W>
+where
+    W: Write,
+{
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut Self::T {
+        self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, _slice: &[u8], min: u8) -> io::Result<()> {
+        self.writer.write_all(&[min])
+    }
+
+    #[inline]
+    fn write_string_content(&mut self, string: &str) -> io::Result<()> {
+        self.escape.write_content(self.writer, string)
+    }
+
+    #[inline]
+    fn write_simple_str_content(&mut self, string: &str) -> io::Result<()> {
+        self.escape.write_content(self.writer, string)
+    }
+}
+
+/// Generator for the value types that leaves the layout to a `Formatter`
+pub(crate) struct FormattedGenerator<'writer, W, F> {
+    writer: &'writer mut W,
+    formatter: F,
+    escape: Escape,
+    depth: usize,
+}
+
+impl<'writer, W, F> FormattedGenerator<'writer, W, F>
+where
+    W: Write,
+    F: Formatter,
+{
+    pub(crate) fn new(writer: &'writer mut W, formatter: F, escape: Escape) -> Self {
+        Self {
+            writer,
+            formatter,
+            escape,
+            depth: 0,
+        }
+    }
+}
+
+impl<W, F> BaseGenerator for FormattedGenerator<'_, W, F>
+where
+    W: Write,
+    F: Formatter,
+{
+    type T = W;
+
+    #[inline]
+    fn get_writer(&mut self) -> &mut Self::T {
+        self.writer
+    }
+
+    #[inline]
+    fn write_min(&mut self, _slice: &[u8], _min: u8) -> io::Result<()> {
+        self.formatter.write_key_separator(self.writer)
+    }
+
+    #[inline]
+    fn new_line(&mut self) -> io::Result<()> {
+        self.formatter.write_new_line(self.writer, self.depth)
+    }
+
+    #[inline]
+    fn indent(&mut self) {
+        self.depth += 1;
+    }
+
+    #[inline]
+    fn dedent(&mut self) {
+        self.depth -= 1;
+    }
+
+    #[inline]
+    fn write_string_content(&mut self, string: &str) -> io::Result<()> {
+        self.escape.write_content(self.writer, string)
+    }
+
+    #[inline]
+    fn write_simple_str_content(&mut self, string: &str) -> io::Result<()> {
+        self.escape.write_content(self.writer, string)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::BorrowedValue;
+    use value_trait::Writable;
+
+    #[test]
+    fn matches_encode() {
+        let v = json!({"a": [1, {"b": null}, []], "c": {}});
+        assert_eq!(v.encode_formatted(CompactFormatter), v.encode());
+        assert_eq!(v.encode_formatted(PrettyFormatter::new()), v.encode_pp());
+        let v = BorrowedValue::from(v);
+        assert_eq!(v.encode_formatted(CompactFormatter), v.encode());
+        assert_eq!(v.encode_formatted(PrettyFormatter::new()), v.encode_pp());
+    }
+
+    #[test]
+    fn custom() {
+        let v = json!([1, {"b": "c"}]);
+        let f = PrettyFormatter::new().indent(b"    ").line_ending(b"\r\n");
+        assert_eq!(
+            v.encode_formatted(f),
+            "[\r\n    1,\r\n    {\r\n        \"b\": \"c\"\r\n    }\r\n]"
+        );
+        let mut w = Vec::new();
+        BorrowedValue::from(v)
+            .write_formatted(&mut w, PrettyFormatter::new().space_after_colon(false))
+            .expect("writing failed");
+        assert_eq!(w, b"[\n  1,\n  {\n    \"b\":\"c\"\n  }\n]");
+    }
+}
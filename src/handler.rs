@@ -0,0 +1,329 @@
+//! Event based (SAX style) parsing.
+//!
+//! Instead of building a tape or a DOM the parser reports every element
+//! to a [`Handler`] as soon as it is encountered.
+
+use crate::{AlignedBuf, Deserializer, Error, Result, SIMDJSON_PADDING};
+
+/// Receives the events of [`parse_with_handler`] in document order.
+///
+/// All callbacks default to doing nothing, so implementations only need
+/// to override the events they are interested in. Returning an error from
+/// any callback stops parsing right away and the error is returned from
+/// [`parse_with_handler`], [`ErrorType::Stopped`] exists for this purpose.
+///
+/// Strings and keys are unescaped in place and borrow from the input.
+///
+/// ```
+/// use simd_json::{Handler, Result};
+///
+/// #[derive(Default)]
+/// struct Sum(i64);
+///
+/// impl<'de> Handler<'de> for Sum {
+///     fn i64(&mut self, n: i64) -> Result<()> {
+///         self.0 += n;
+///         Ok(())
+///     }
+///     fn u64(&mut self, n: u64) -> Result<()> {
+///         self.0 += n as i64;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut d = br#"[1, -2, {"three": 3}]"#.to_vec();
+/// let mut sum = Sum::default();
+/// simd_json::parse_with_handler(&mut d, &mut sum).unwrap();
+/// assert_eq!(sum.0, 2);
+/// ```
+pub trait Handler<'de> {
+    /// Called at the start of an object, before its first key
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn start_object(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called for every key in an object, followed by the events of the
+    /// value it belongs to
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn key(&mut self, _key: &'de str) -> Result<()> {
+        Ok(())
+    }
+    /// Called at the end of an object
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn end_object(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called at the start of an array, before its first element
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn start_array(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called at the end of an array
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn end_array(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called for a string value
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn string(&mut self, _s: &'de str) -> Result<()> {
+        Ok(())
+    }
+    /// Called for `null`
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn null(&mut self) -> Result<()> {
+        Ok(())
+    }
+    /// Called for `true` and `false`
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn bool(&mut self, _b: bool) -> Result<()> {
+        Ok(())
+    }
+    /// Called for negative integers
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn i64(&mut self, _n: i64) -> Result<()> {
+        Ok(())
+    }
+    /// Called for positive integers
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn u64(&mut self, _n: u64) -> Result<()> {
+        Ok(())
+    }
+    /// Called for floating point numbers
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    fn f64(&mut self, _n: f64) -> Result<()> {
+        Ok(())
+    }
+    /// Called for negative integers that don't fit into an `i64`
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    #[cfg(feature = "128bit")]
+    fn i128(&mut self, _n: i128) -> Result<()> {
+        Ok(())
+    }
+    /// Called for positive integers that don't fit into an `u64`
+    ///
+    /// # Errors
+    ///
+    /// An error stops parsing
+    #[cfg(feature = "128bit")]
+    fn u128(&mut self, _n: u128) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Parses a slice of bytes and reports its content to `handler` without
+/// building a tape. This function will rewrite the slice to de-escape
+/// strings.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON or if the handler returned
+/// an error.
+pub fn parse_with_handler<'de, H>(s: &'de mut [u8], handler: &mut H) -> Result<()>
+where
+    H: Handler<'de>,
+{
+    let len = s.len();
+
+    let mut input_buffer = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
+    input_buffer.load(s);
+
+    let mut string_buffer: Vec<u8> = vec![0; len + SIMDJSON_PADDING];
+
+    if len > u32::MAX as usize {
+        drive::<u64, H>(s, &input_buffer, &mut string_buffer, handler)
+    } else {
+        drive::<u32, H>(s, &input_buffer, &mut string_buffer, handler)
+    }
+}
+
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+fn drive<'de, I, H>(
+    input: &'de mut [u8],
+    input_buffer: &[u8],
+    string_buffer: &mut [u8],
+    handler: &mut H,
+) -> Result<()>
+where
+    I: crate::StructuralIndex,
+    H: Handler<'de>,
+{
+    let structural_indexes: Vec<I> =
+        match unsafe { Deserializer::find_structural_bits(input_buffer) } {
+            Ok(i) => i,
+            Err(t) => return Err(Error::generic(t)),
+        };
+    stry!(Deserializer::drive_handler(
+        input,
+        input_buffer,
+        string_buffer,
+        &structural_indexes,
+        handler,
+    ));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::ErrorType;
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl<'de> Handler<'de> for Events {
+        fn start_object(&mut self) -> Result<()> {
+            self.0.push("{".into());
+            Ok(())
+        }
+        fn key(&mut self, key: &'de str) -> Result<()> {
+            self.0.push(format!("key:{}", key));
+            Ok(())
+        }
+        fn end_object(&mut self) -> Result<()> {
+            self.0.push("}".into());
+            Ok(())
+        }
+        fn start_array(&mut self) -> Result<()> {
+            self.0.push("[".into());
+            Ok(())
+        }
+        fn end_array(&mut self) -> Result<()> {
+            self.0.push("]".into());
+            Ok(())
+        }
+        fn string(&mut self, s: &'de str) -> Result<()> {
+            self.0.push(format!("str:{}", s));
+            Ok(())
+        }
+        fn null(&mut self) -> Result<()> {
+            self.0.push("null".into());
+            Ok(())
+        }
+        fn bool(&mut self, b: bool) -> Result<()> {
+            self.0.push(format!("bool:{}", b));
+            Ok(())
+        }
+        fn i64(&mut self, n: i64) -> Result<()> {
+            self.0.push(format!("i64:{}", n));
+            Ok(())
+        }
+        fn u64(&mut self, n: u64) -> Result<()> {
+            self.0.push(format!("u64:{}", n));
+            Ok(())
+        }
+        fn f64(&mut self, n: f64) -> Result<()> {
+            self.0.push(format!("f64:{}", n));
+            Ok(())
+        }
+    }
+
+    fn events(s: &str) -> Result<Vec<String>> {
+        let mut d = s.as_bytes().to_vec();
+        let mut h = Events::default();
+        parse_with_handler(&mut d, &mut h)?;
+        Ok(h.0)
+    }
+
+    #[test]
+    fn scalars() {
+        assert_eq!(events("null").unwrap(), vec!["null"]);
+        assert_eq!(events("true").unwrap(), vec!["bool:true"]);
+        assert_eq!(events("-1").unwrap(), vec!["i64:-1"]);
+        assert_eq!(events("1").unwrap(), vec!["u64:1"]);
+        assert_eq!(events("1.5").unwrap(), vec!["f64:1.5"]);
+        assert_eq!(events(r#""s\"""#).unwrap(), vec!["str:s\""]);
+    }
+
+    #[test]
+    fn nested() {
+        assert_eq!(
+            events(r#"{"a": [1, {"b": false}, []], "c": {}, "d": "e"}"#).unwrap(),
+            vec![
+                "{",
+                "key:a",
+                "[",
+                "u64:1",
+                "{",
+                "key:b",
+                "bool:false",
+                "}",
+                "[",
+                "]",
+                "]",
+                "key:c",
+                "{",
+                "}",
+                "key:d",
+                "str:e",
+                "}"
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(events("[1, 2").is_err());
+        assert!(events(r#"{"a" 1}"#).is_err());
+        assert!(events("[1] 2").is_err());
+        assert!(events("[tru]").is_err());
+        assert!(events("]").is_err());
+        assert!(events("").is_err());
+    }
+
+    struct FirstString<'de>(Option<&'de str>);
+
+    impl<'de> Handler<'de> for FirstString<'de> {
+        fn string(&mut self, s: &'de str) -> Result<()> {
+            self.0 = Some(s);
+            Err(Error::generic(ErrorType::Stopped))
+        }
+    }
+
+    #[test]
+    fn stop_early() {
+        // the input is invalid after the first string, so we only
+        // succeed if we stop before looking at it
+        let mut d = br#"["first", "second", }"#.to_vec();
+        let mut h = FirstString(None);
+        let e = parse_with_handler(&mut d, &mut h).unwrap_err();
+        assert_eq!(e, Error::generic(ErrorType::Stopped));
+        assert_eq!(h.0, Some("first"));
+    }
+}
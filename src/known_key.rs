@@ -1,16 +1,49 @@
 use crate::cow::Cow;
 use crate::prelude::*;
-use crate::BorrowedValue as Value;
-use halfbrown::RawEntryMut;
+use crate::value::borrowed::{HashedObject, HashedValue};
+use crate::value::map::RawEntryMut;
+use crate::value::owned::{HashedObject as OwnedHashedObject, HashedValue as OwnedHashedValue};
+use halfbrown::DefaultHashBuilder;
 use std::fmt;
 use std::hash::{BuildHasher, Hash, Hasher};
+use std::marker::PhantomData;
 
 /// Well known key that can be looked up in a `Value` faster.
 /// It achives this by memorizing the hash.
-#[derive(Debug, Clone, PartialEq)]
-pub struct KnownKey<'key> {
+///
+/// The hash is computed with a default `S`, the hasher of the objects the
+/// key is looked up in. This only works for hashers that hash a key the
+/// same way every time they are created, like the default hasher or
+/// `BuildHasherDefault`, but not `std::collections::hash_map::RandomState`.
+pub struct KnownKey<'key, S = DefaultHashBuilder> {
     key: Cow<'key, str>,
     hash: u64,
+    _hasher: PhantomData<fn() -> S>,
+}
+
+impl<S> fmt::Debug for KnownKey<'_, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KnownKey")
+            .field("key", &self.key)
+            .field("hash", &self.hash)
+            .finish()
+    }
+}
+
+impl<S> Clone for KnownKey<'_, S> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            hash: self.hash,
+            _hasher: PhantomData,
+        }
+    }
+}
+
+impl<S> PartialEq for KnownKey<'_, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.key == other.key
+    }
 }
 
 /// Error for known keys
@@ -18,6 +51,43 @@ pub struct KnownKey<'key> {
 pub enum Error {
     /// The target passed wasn't an object
     NotAnObject(ValueType),
+    /// The key of a `KnownPath` segment wasn't present
+    MissingKey {
+        /// Position of the failing segment in the path
+        segment: usize,
+        /// The key that wasn't present
+        key: String,
+    },
+    /// The index of a `KnownPath` segment was past the end of the array
+    MissingIndex {
+        /// Position of the failing segment in the path
+        segment: usize,
+        /// The index that was out of bounds
+        index: usize,
+    },
+    /// A `KnownPath` segment was applied to a value of the wrong type
+    WrongType {
+        /// Position of the failing segment in the path
+        segment: usize,
+        /// The type the segment needs
+        expected: ValueType,
+        /// The type that was found
+        found: ValueType,
+    },
+}
+
+impl Error {
+    /// Position of the `KnownPath` segment that failed, if the error
+    /// came from resolving a path
+    #[must_use]
+    pub fn segment(&self) -> Option<usize> {
+        match self {
+            Self::NotAnObject(_) => None,
+            Self::MissingKey { segment, .. }
+            | Self::MissingIndex { segment, .. }
+            | Self::WrongType { segment, .. } => Some(*segment),
+        }
+    }
 }
 
 #[cfg(not(tarpaulin_include))]
@@ -25,28 +95,47 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::NotAnObject(t) => write!(f, "Expected object but got {:?}", t),
+            Self::MissingKey { segment, key } => {
+                write!(f, "Key {:?} of segment {} not found", key, segment)
+            }
+            Self::MissingIndex { segment, index } => {
+                write!(f, "Index {} of segment {} out of bounds", index, segment)
+            }
+            Self::WrongType {
+                segment,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Expected {:?} at segment {} but got {:?}",
+                expected, segment, found
+            ),
         }
     }
 }
 impl std::error::Error for Error {}
 
-impl<'key, S> From<S> for KnownKey<'key>
+impl<'key, T, S> From<T> for KnownKey<'key, S>
 where
-    Cow<'key, str>: From<S>,
+    Cow<'key, str>: From<T>,
+    S: BuildHasher + Default,
 {
-    fn from(key: S) -> Self {
+    fn from(key: T) -> Self {
         let key = Cow::from(key);
-        let hash_builder = halfbrown::DefaultHashBuilder::default();
-        let mut hasher = hash_builder.build_hasher();
+        let mut hasher = S::default().build_hasher();
         key.hash(&mut hasher);
         Self {
             hash: hasher.finish(),
             key,
+            _hasher: PhantomData,
         }
     }
 }
 
-impl<'key> KnownKey<'key> {
+impl<'key, S> KnownKey<'key, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     /// The known key
     #[inline]
     #[must_use]
@@ -73,8 +162,8 @@ impl<'key> KnownKey<'key> {
     #[must_use]
     pub fn lookup<'target, 'value>(
         &self,
-        target: &'target Value<'value>,
-    ) -> Option<&'target Value<'value>>
+        target: &'target HashedValue<'value, S>,
+    ) -> Option<&'target HashedValue<'value, S>>
     where
         'key: 'value,
         'value: 'target,
@@ -100,8 +189,8 @@ impl<'key> KnownKey<'key> {
     #[must_use]
     pub fn map_lookup<'target, 'value>(
         &self,
-        map: &'target halfbrown::HashMap<Cow<'value, str>, Value<'value>>,
-    ) -> Option<&'target Value<'value>>
+        map: &'target HashedObject<'value, S>,
+    ) -> Option<&'target HashedValue<'value, S>>
     where
         'key: 'value,
         'value: 'target,
@@ -133,8 +222,8 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn lookup_mut<'target, 'value>(
         &self,
-        target: &'target mut Value<'value>,
-    ) -> Option<&'target mut Value<'value>>
+        target: &'target mut HashedValue<'value, S>,
+    ) -> Option<&'target mut HashedValue<'value, S>>
     where
         'key: 'value,
         'value: 'target,
@@ -166,8 +255,8 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn map_lookup_mut<'target, 'value>(
         &self,
-        map: &'target mut halfbrown::HashMap<Cow<'value, str>, Value<'value>>,
-    ) -> Option<&'target mut Value<'value>>
+        map: &'target mut HashedObject<'value, S>,
+    ) -> Option<&'target mut HashedValue<'value, S>>
     where
         'key: 'value,
         'value: 'target,
@@ -214,16 +303,16 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn lookup_or_insert_mut<'target, 'value, F>(
         &self,
-        target: &'target mut Value<'value>,
+        target: &'target mut HashedValue<'value, S>,
         with: F,
-    ) -> Result<&'target mut Value<'value>, Error>
+    ) -> Result<&'target mut HashedValue<'value, S>, Error>
     where
         'key: 'value,
         'value: 'target,
-        F: FnOnce() -> Value<'value>,
+        F: FnOnce() -> HashedValue<'value, S>,
     {
         match target {
-            Value::Object(inner) => Ok(self.map_lookup_or_insert_mut(inner, with)),
+            HashedValue::Object(inner) => Ok(self.map_lookup_or_insert_mut(inner, with)),
             other => Err(Error::NotAnObject(other.value_type())),
         }
     }
@@ -261,13 +350,13 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn map_lookup_or_insert_mut<'target, 'value, F>(
         &self,
-        map: &'target mut halfbrown::HashMap<Cow<'value, str>, Value<'value>>,
+        map: &'target mut HashedObject<'value, S>,
         with: F,
-    ) -> &'target mut Value<'value>
+    ) -> &'target mut HashedValue<'value, S>
     where
         'key: 'value,
         'value: 'target,
-        F: FnOnce() -> Value<'value>,
+        F: FnOnce() -> HashedValue<'value, S>,
     {
         map.raw_entry_mut()
             .from_key_hashed_nocheck(self.hash, &self.key)
@@ -304,9 +393,9 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn insert<'target, 'value>(
         &self,
-        target: &'target mut Value<'value>,
-        value: Value<'value>,
-    ) -> Result<Option<Value<'value>>, Error>
+        target: &'target mut HashedValue<'value, S>,
+        value: HashedValue<'value, S>,
+    ) -> Result<Option<HashedValue<'value, S>>, Error>
     where
         'key: 'value,
         'value: 'target,
@@ -348,9 +437,9 @@ impl<'key> KnownKey<'key> {
     #[inline]
     pub fn map_insert<'target, 'value>(
         &self,
-        map: &'target mut halfbrown::HashMap<Cow<'value, str>, Value<'value>>,
-        value: Value<'value>,
-    ) -> Option<Value<'value>>
+        map: &'target mut HashedObject<'value, S>,
+        value: HashedValue<'value, S>,
+    ) -> Option<HashedValue<'value, S>>
     where
         'key: 'value,
         'value: 'target,
@@ -366,12 +455,386 @@ impl<'key> KnownKey<'key> {
             }
         }
     }
+
+    /// Looks up this key in an `OwnedValue`, returns None if the
+    /// key wasn't present or `target` isn't an object
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let object = json!({
+    ///   "answer": 42,
+    ///   "key": 7
+    /// });
+    /// let known_key = KnownKey::from("answer");
+    /// assert_eq!(known_key.lookup_owned(&object).unwrap(), &42);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn lookup_owned<'target>(
+        &self,
+        target: &'target OwnedHashedValue<S>,
+    ) -> Option<&'target OwnedHashedValue<S>> {
+        target.as_object().and_then(|m| self.map_lookup_owned(m))
+    }
+
+    /// Looks up this key in the object map of an `OwnedValue`, returns
+    /// None if the key wasn't present.
+    #[inline]
+    #[must_use]
+    pub fn map_lookup_owned<'target>(
+        &self,
+        map: &'target OwnedHashedObject<S>,
+    ) -> Option<&'target OwnedHashedValue<S>> {
+        map.raw_entry()
+            .from_key_hashed_nocheck(self.hash, self.key.as_ref())
+            .map(|kv| kv.1)
+    }
+
+    /// Looks up this key in an `OwnedValue`, returns None if the
+    /// key wasn't present or `target` isn't an object
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let mut object = json!({
+    ///   "answer": 23,
+    ///   "key": 7
+    /// });
+    /// let known_key = KnownKey::from("answer");
+    ///
+    /// if let Some(answer) = known_key.lookup_owned_mut(&mut object) {
+    ///   *answer = OwnedValue::from(42);
+    /// }
+    ///
+    /// assert_eq!(object["answer"], 42);
+    /// ```
+    #[inline]
+    pub fn lookup_owned_mut<'target>(
+        &self,
+        target: &'target mut OwnedHashedValue<S>,
+    ) -> Option<&'target mut OwnedHashedValue<S>> {
+        target
+            .as_object_mut()
+            .and_then(|m| self.map_lookup_owned_mut(m))
+    }
+
+    /// Looks up this key in the object map of an `OwnedValue`, returns
+    /// None if the key wasn't present.
+    #[inline]
+    pub fn map_lookup_owned_mut<'target>(
+        &self,
+        map: &'target mut OwnedHashedObject<S>,
+    ) -> Option<&'target mut OwnedHashedValue<S>> {
+        match map
+            .raw_entry_mut()
+            .from_key_hashed_nocheck(self.hash, self.key.as_ref())
+        {
+            RawEntryMut::Occupied(e) => Some(e.into_mut()),
+            RawEntryMut::Vacant(_e) => None,
+        }
+    }
+
+    /// Looks up this key in an `OwnedValue`, inserts `with` when the key
+    /// wasn't present.
+    /// # Errors
+    /// * if target is not a record
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let mut object = json!({
+    ///   "answer": 23,
+    ///   "key": 7
+    /// });
+    /// let known_key = KnownKey::from("also the answer");
+    ///
+    /// if let Ok(answer) = known_key.lookup_or_insert_owned_mut(&mut object, || 8.into()) {
+    ///   assert_eq!(*answer, 8);
+    ///   *answer = OwnedValue::from(42);
+    /// }
+    ///
+    /// assert_eq!(object["also the answer"], 42);
+    /// ```
+    #[inline]
+    pub fn lookup_or_insert_owned_mut<'target, F>(
+        &self,
+        target: &'target mut OwnedHashedValue<S>,
+        with: F,
+    ) -> Result<&'target mut OwnedHashedValue<S>, Error>
+    where
+        F: FnOnce() -> OwnedHashedValue<S>,
+    {
+        match target {
+            OwnedHashedValue::Object(inner) => Ok(self.map_lookup_or_insert_owned_mut(inner, with)),
+            other => Err(Error::NotAnObject(other.value_type())),
+        }
+    }
+
+    /// Looks up this key in the object map of an `OwnedValue`, inserts
+    /// `with` when the key wasn't present.
+    #[inline]
+    pub fn map_lookup_or_insert_owned_mut<'target, F>(
+        &self,
+        map: &'target mut OwnedHashedObject<S>,
+        with: F,
+    ) -> &'target mut OwnedHashedValue<S>
+    where
+        F: FnOnce() -> OwnedHashedValue<S>,
+    {
+        map.raw_entry_mut()
+            .from_key_hashed_nocheck(self.hash, self.key.as_ref())
+            .or_insert_with(|| (self.key.to_string(), with()))
+            .1
+    }
+
+    /// Inserts a value key into an `OwnedValue`, returns None if the
+    /// key wasn't present otherwise Some(`old value`).
+    /// # Errors
+    ///   * if `target` isn't an object
+    ///
+    /// ```rust
+    /// use simd_json::*;
+    /// let mut object = json!({
+    ///   "answer": 23,
+    ///   "key": 7
+    /// });
+    /// let known_key = KnownKey::from("answer");
+    ///
+    /// assert_eq!(
+    ///   known_key.insert_owned(&mut object, OwnedValue::from(42)),
+    ///   Ok(Some(OwnedValue::from(23)))
+    /// );
+    /// assert_eq!(object["answer"], 42);
+    /// ```
+    #[inline]
+    pub fn insert_owned(
+        &self,
+        target: &mut OwnedHashedValue<S>,
+        value: OwnedHashedValue<S>,
+    ) -> Result<Option<OwnedHashedValue<S>>, Error> {
+        match target {
+            OwnedHashedValue::Object(inner) => Ok(self.map_insert_owned(inner, value)),
+            other => Err(Error::NotAnObject(other.value_type())),
+        }
+    }
+
+    /// Inserts a value key into the object map of an `OwnedValue`,
+    /// returns None if the key wasn't present otherwise Some(`old value`).
+    #[inline]
+    pub fn map_insert_owned(
+        &self,
+        map: &mut OwnedHashedObject<S>,
+        value: OwnedHashedValue<S>,
+    ) -> Option<OwnedHashedValue<S>> {
+        match map
+            .raw_entry_mut()
+            .from_key_hashed_nocheck(self.hash, self.key.as_ref())
+        {
+            RawEntryMut::Occupied(mut e) => Some(e.insert(value)),
+            RawEntryMut::Vacant(e) => {
+                e.insert_hashed_nocheck(self.hash, self.key.to_string(), value);
+                None
+            }
+        }
+    }
+}
+
+/// Segment of a `KnownPath`
+#[derive(Debug, Clone, PartialEq)]
+pub enum KnownSegment<'key, S = DefaultHashBuilder> {
+    /// Looks up a key in an object
+    Key(KnownKey<'key, S>),
+    /// Looks up an index in an array
+    Index(usize),
+}
+
+/// Path of well known keys and array indices that is resolved in a
+/// single call, for example `user.profile.id`. The hashes of the keys are
+/// memorized just like for a `KnownKey`.
+///
+/// ```rust
+/// use simd_json::*;
+/// let object = json!({
+///   "user": {"profile": {"id": 42}, "roles": ["admin"]}
+/// });
+/// let id = KnownPath::new().key("user").key("profile").key("id");
+/// assert_eq!(id.lookup_owned(&object).unwrap(), &42);
+///
+/// let role = KnownPath::new().key("user").key("roles").index(1);
+/// assert_eq!(role.lookup_owned(&object).unwrap_err().segment(), Some(2));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KnownPath<'key, S = DefaultHashBuilder> {
+    segments: Vec<KnownSegment<'key, S>>,
+}
+
+impl<S> Default for KnownPath<'_, S> {
+    fn default() -> Self {
+        Self {
+            segments: Vec::new(),
+        }
+    }
+}
+
+impl<'key, T, S> From<Vec<T>> for KnownPath<'key, S>
+where
+    Cow<'key, str>: From<T>,
+    S: BuildHasher + Default,
+{
+    fn from(keys: Vec<T>) -> Self {
+        Self {
+            segments: keys
+                .into_iter()
+                .map(|k| KnownSegment::Key(KnownKey::from(k)))
+                .collect(),
+        }
+    }
+}
+
+macro_rules! resolve {
+    ($self:ident, $target:ident, $value:ident, $map_lookup:ident, $get_idx:ident) => {{
+        let mut current = $target;
+        for (segment, s) in $self.segments.iter().enumerate() {
+            current = match (s, current) {
+                (KnownSegment::Key(k), $value::Object(m)) => {
+                    stry!(k.$map_lookup(m).ok_or_else(|| Error::MissingKey {
+                        segment,
+                        key: k.key().to_string(),
+                    }))
+                }
+                (KnownSegment::Index(index), $value::Array(a)) => {
+                    stry!(a.$get_idx(*index).ok_or(Error::MissingIndex {
+                        segment,
+                        index: *index,
+                    }))
+                }
+                (s, other) => {
+                    return Err(Error::WrongType {
+                        segment,
+                        expected: if let KnownSegment::Key(_) = s {
+                            ValueType::Object
+                        } else {
+                            ValueType::Array
+                        },
+                        found: other.value_type(),
+                    })
+                }
+            }
+        }
+        Ok(current)
+    }};
+}
+
+impl<'key, S> KnownPath<'key, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    /// Creates an empty path that resolves to the target itself
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a key segment to the path
+    #[must_use]
+    pub fn key<T>(mut self, key: T) -> Self
+    where
+        Cow<'key, str>: From<T>,
+    {
+        self.segments.push(KnownSegment::Key(KnownKey::from(key)));
+        self
+    }
+
+    /// Appends an array index segment to the path
+    #[must_use]
+    pub fn index(mut self, index: usize) -> Self {
+        self.segments.push(KnownSegment::Index(index));
+        self
+    }
+
+    /// The segments of the path
+    #[inline]
+    #[must_use]
+    pub fn segments(&self) -> &[KnownSegment<'key, S>] {
+        &self.segments
+    }
+
+    /// Resolves the path in a `Value`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` naming the segment that couldn't be resolved, if
+    /// a key or index isn't present or a value has the wrong type
+    pub fn lookup<'target, 'value>(
+        &self,
+        target: &'target HashedValue<'value, S>,
+    ) -> Result<&'target HashedValue<'value, S>, Error>
+    where
+        'key: 'value,
+        'value: 'target,
+    {
+        resolve!(self, target, HashedValue, map_lookup, get)
+    }
+
+    /// Resolves the path in a `Value` and returns a mutable reference
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` naming the segment that couldn't be resolved, if
+    /// a key or index isn't present or a value has the wrong type
+    pub fn lookup_mut<'target, 'value>(
+        &self,
+        target: &'target mut HashedValue<'value, S>,
+    ) -> Result<&'target mut HashedValue<'value, S>, Error>
+    where
+        'key: 'value,
+        'value: 'target,
+    {
+        resolve!(self, target, HashedValue, map_lookup_mut, get_mut)
+    }
+
+    /// Resolves the path in an `OwnedValue`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` naming the segment that couldn't be resolved, if
+    /// a key or index isn't present or a value has the wrong type
+    pub fn lookup_owned<'target>(
+        &self,
+        target: &'target OwnedHashedValue<S>,
+    ) -> Result<&'target OwnedHashedValue<S>, Error> {
+        resolve!(self, target, OwnedHashedValue, map_lookup_owned, get)
+    }
+
+    /// Resolves the path in an `OwnedValue` and returns a mutable
+    /// reference
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` naming the segment that couldn't be resolved, if
+    /// a key or index isn't present or a value has the wrong type
+    pub fn lookup_owned_mut<'target>(
+        &self,
+        target: &'target mut OwnedHashedValue<S>,
+    ) -> Result<&'target mut OwnedHashedValue<S>, Error> {
+        resolve!(
+            self,
+            target,
+            OwnedHashedValue,
+            map_lookup_owned_mut,
+            get_mut
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    #![allow(clippy::unnecessary_operation, clippy::non_ascii_literal)]
+    #![allow(
+        clippy::unnecessary_operation,
+        clippy::non_ascii_literal,
+        clippy::unwrap_used
+    )]
     use super::*;
+    use crate::BorrowedValue as Value;
+    use crate::OwnedValue;
 
     #[test]
     fn known_key() {
@@ -459,10 +922,134 @@ mod tests {
         assert_eq!(v["cake"], 3);
     }
 
+    #[test]
+    fn known_key_owned() {
+        let mut v = OwnedValue::object();
+        v.insert("key", 1).unwrap();
+        let key1 = KnownKey::from("key");
+        let key2 = KnownKey::from("cake");
+
+        let mut v1 = OwnedValue::null();
+        assert!(key1.lookup_owned(&v1).is_none());
+        assert!(key1.lookup_owned_mut(&mut v1).is_none());
+        assert!(key1.insert_owned(&mut v1, 2.into()).is_err());
+        assert!(key1
+            .lookup_or_insert_owned_mut(&mut v1, || 2.into())
+            .is_err());
+
+        assert_eq!(key1.lookup_owned(&v), Some(&OwnedValue::from(1)));
+        assert!(key2.lookup_owned(&v).is_none());
+        *key1.lookup_owned_mut(&mut v).unwrap() = 2.into();
+        assert_eq!(key1.insert_owned(&mut v, 3.into()).unwrap(), Some(2.into()));
+        assert_eq!(
+            *key2
+                .lookup_or_insert_owned_mut(&mut v, || 4.into())
+                .unwrap(),
+            4
+        );
+        assert_eq!(key2.insert_owned(&mut v, 5.into()).unwrap(), Some(4.into()));
+        assert_eq!(v["key"], 3);
+        assert_eq!(v["cake"], 5);
+    }
+
+    #[test]
+    fn known_path() {
+        let mut v: Value = crate::json!({
+            "user": {"profile": {"id": 1}, "roles": ["admin", "dev"]}
+        })
+        .into();
+        let id = KnownPath::from(vec!["user", "profile", "id"]);
+        let role = KnownPath::new().key("user").key("roles").index(1);
+
+        assert_eq!(KnownPath::new().lookup(&v).unwrap(), &v);
+        assert_eq!(id.lookup(&v).unwrap(), &1);
+        assert_eq!(role.lookup(&v).unwrap(), "dev");
+        *id.lookup_mut(&mut v).unwrap() = 2.into();
+        assert_eq!(v["user"]["profile"]["id"], 2);
+        assert_eq!(id.segments().len(), 3);
+        assert_eq!(role.segments()[2], KnownSegment::Index(1));
+
+        assert_eq!(
+            KnownPath::from(vec!["user", "name"]).lookup(&v),
+            Err(Error::MissingKey {
+                segment: 1,
+                key: "name".to_string()
+            })
+        );
+        assert_eq!(
+            KnownPath::new()
+                .key("user")
+                .key("roles")
+                .index(2)
+                .lookup(&v),
+            Err(Error::MissingIndex {
+                segment: 2,
+                index: 2
+            })
+        );
+        let e = KnownPath::new()
+            .key("user")
+            .index(0)
+            .lookup(&v)
+            .unwrap_err();
+        assert_eq!(
+            e,
+            Error::WrongType {
+                segment: 1,
+                expected: ValueType::Array,
+                found: ValueType::Object
+            }
+        );
+        assert_eq!(e.segment(), Some(1));
+        assert_eq!(Error::NotAnObject(ValueType::Null).segment(), None);
+    }
+
+    #[test]
+    fn known_path_owned() {
+        let mut v = crate::json!({"a": [{"b": 1}]});
+        let path = KnownPath::new().key("a").index(0).key("b");
+        assert_eq!(path.lookup_owned(&v).unwrap(), &1);
+        *path.lookup_owned_mut(&mut v).unwrap() = 2.into();
+        assert_eq!(v["a"][0]["b"], 2);
+        assert_eq!(
+            path.lookup_owned(&crate::json!({"a": [1]})).unwrap_err(),
+            Error::WrongType {
+                segment: 2,
+                expected: ValueType::Object,
+                found: ValueType::I64
+            }
+        );
+    }
+
     #[test]
     fn known_key_get_key() {
-        let key1 = KnownKey::from("snot");
+        let key1: KnownKey = KnownKey::from("snot");
 
         assert_eq!(key1.key(), "snot");
     }
+
+    #[test]
+    fn known_key_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+        type Fixed = BuildHasherDefault<DefaultHasher>;
+
+        let mut d = br#"{"user": {"id": 42}}"#.to_vec();
+        let mut v: crate::value::borrowed::HashedValue<Fixed> =
+            crate::to_borrowed_value_with_hasher(&mut d).unwrap();
+        let key: KnownKey<Fixed> = KnownKey::from("user");
+        assert!(key.lookup(&v).is_some());
+        let path: KnownPath<Fixed> = KnownPath::new().key("user").key("id");
+        assert_eq!(path.lookup(&v).unwrap(), &42);
+        *path.lookup_mut(&mut v).unwrap() = 23.into();
+        assert_eq!(v.encode(), r#"{"user":{"id":23}}"#);
+
+        let mut d = br#"{"user": {"id": 42}}"#.to_vec();
+        let mut v: crate::value::owned::HashedValue<Fixed> =
+            crate::to_owned_value_with_hasher(&mut d).unwrap();
+        assert!(key.lookup_owned(&v).is_some());
+        let old = key.insert_owned(&mut v, 1.into()).unwrap().unwrap();
+        assert_eq!(old.encode(), r#"{"id":42}"#);
+        assert_eq!(path.lookup_owned(&v).unwrap_err().segment(), Some(1));
+    }
 }
@@ -78,13 +78,55 @@
 //! to fxhash, ahash is faster at hashing and provides protection
 //! against DOS attacks by forcing multiple keys into a single hashing
 //! bucket. fxhash on the other hand allows for repeatable hashing
-//! results, that allows memorizing hashes for well know keys and saving
-//! time on lookups. In workloads that are heavy at accessing some well
-//! known keys this can be a performance advantage.
+//! results, which allows memorizing hashes for well known keys and
+//! saving time on lookups. In workloads that are heavy at accessing some
+//! well known keys this can be a performance advantage.
+//!
+//! It enables `KnownKey` for borrowed and owned values and `KnownPath`
+//! to resolve nested keys and indices in a single call. A `KnownKey<S>`
+//! memorizes the hash of its key for the hasher `S` of the objects it
+//! looks up. `S` defaults to the hasher of `BorrowedValue` and
+//! `OwnedValue`, and works for `value::borrowed::HashedValue<S>` and
+//! `value::owned::HashedValue<S>` parsed with
+//! [`to_borrowed_value_with_hasher`] and [`to_owned_value_with_hasher`]
+//! as long as every `S::default()` hashes a key the same way, like
+//! `BuildHasherDefault` does. Untrusted input can still be parsed with a
+//! DOS resistant hasher that way.
+//!
+//! ### `preserve_order`
+//!
+//! Objects keep their keys in the order they were inserted, or read
+//! from the input, instead of the order of a hash map. Parsing a
+//! document and encoding it again keeps the keys where they were, which
+//! matters for human edited files or comparing against golden files.
+//! The [`ObjectMap`] of objects then wraps an `indexmap::IndexMap` that
+//! works with `KnownKey`, the `value_trait` accessors and serde just like
+//! the default `halfbrown::HashMap`. Removing a key moves the keys after
+//! it up so it takes time linear in the size of the object. The feature
+//! needs Rust 1.63.
+//!
+//! ### `mmap`
+//!
+//! Adds [`from_file`] and [`MappedFile`] to parse files that are memory
+//! mapped privately (copy-on-write). Strings are unescaped in the mapping
+//! without the changes ever reaching the file, and borrowed values
+//! reference the mapping instead of a copy of the file.
+//!
+//! ### `arena`
+//!
+//! Adds [`value::arena`] with a value whose arrays and objects are
+//! allocated in an [`Arena`], so a document is built with a pointer bump
+//! per container and freed all at once.
+//!
+//! ### `derive`
+//!
+//! Adds the [`derive`] module with `Serialize` and `Deserialize` traits and
+//! derives for them that read from the tape and write to a generator
+//! directly, skipping serde's visitors.
 //!
 //! ## Usage
 //!
-//! simd-json offers two main entry points for usage:
+//! simd-json offers three main entry points for usage:
 //!
 //! ### Values API
 //!
@@ -117,6 +159,58 @@
 //! let mut d = br#"{"some": ["key", "value", 2]}"#.to_vec();
 //! let v: Value = simd_json::serde::from_slice(&mut d).unwrap();
 //! ```
+//!
+//! ### Handler API
+//!
+//! For cases where neither a DOM nor a tape is needed, `parse_with_handler`
+//! reports each element to a [`Handler`] as it is parsed, see its
+//! documentation for an example.
+//!
+//! ### Writer API
+//!
+//! To produce JSON incrementally without building a value first,
+//! [`JsonWriter`] writes elements straight to an `io::Write`.
+//!
+//! ### Rewriter API
+//!
+//! To remove or mask values in raw JSON without re-encoding it, the
+//! [`Rewriter`] copies everything it doesn't touch verbatim.
+//!
+//! ### Projection API
+//!
+//! When only a few fields of a large document are needed, a [`Projection`]
+//! parses just the selected values and skips over the rest.
+//!
+//! ### Spans
+//!
+//! To map tape nodes back to the input, for example for error messages,
+//! [`to_tape_with_spans`] and [`Deserializer::spans`] return the [`Span`]
+//! of every node next to the tape.
+//!
+//! ### Compact tape
+//!
+//! A [`CompactTape`] stores each node in a single `u64` word, with strings
+//! in a length prefixed buffer, instead of a 24 byte [`Node`]. It reads its
+//! nodes back as `Node`s and is a better fit for the cache when a large
+//! document is kept around as a tape.
+//!
+//! ### Formatting
+//!
+//! The whitespace of serialized JSON is decided by a [`Formatter`], values
+//! take one in `encode_formatted` and serde types through a
+//! [`serde::PrettySerializer`]. [`PrettyFormatter`] can be configured with
+//! the indentation, line ending and the space after colons.
+//!
+//! Strings are escaped as little as JSON allows unless an [`Escape`] policy
+//! is given, to the serializers through their `escape` method and to values
+//! through `encode_escaped` and its siblings. It can escape all non ASCII
+//! characters, characters that are unsafe in HTML and `/`.
+//!
+//! ### Canonical JSON
+//!
+//! For signing and hashing, `encode_canonical` on the value types and
+//! [`to_canonical_vec`] for serde types write the RFC 8785 canonical form:
+//! sorted keys, no whitespace and numbers formatted like ECMAScript does.
 
 #[cfg(feature = "serde_impl")]
 extern crate serde as serde_ext;
@@ -125,10 +219,13 @@ extern crate serde as serde_ext;
 /// serde related helper functions
 pub mod serde;
 
+#[cfg(feature = "derive")]
+pub mod derive;
+
 #[cfg(feature = "serde_impl")]
 pub use crate::serde::{
-    from_reader, from_slice, from_str, to_string, to_string_pretty, to_vec, to_vec_pretty,
-    to_writer, to_writer_pretty,
+    from_reader, from_slice, from_slice_in_place, from_str, to_canonical_string, to_canonical_vec,
+    to_string, to_string_pretty, to_vec, to_vec_pretty, to_writer, to_writer_pretty,
 };
 
 /// Default trait imports;
@@ -137,9 +234,21 @@ pub mod prelude;
 mod charutils;
 #[macro_use]
 mod macros;
+mod canonical;
+mod encoding;
 mod error;
+mod escape;
+mod format;
+mod handler;
+#[cfg(feature = "mmap")]
+mod mmap;
 mod numberparse;
+mod path;
+mod projection;
+mod rewriter;
+mod span;
 mod stringparse;
+mod writer;
 
 /// Reexport of Cow
 pub mod cow;
@@ -149,6 +258,8 @@ mod avx2;
 #[cfg(target_feature = "avx2")]
 pub use crate::avx2::deser::*;
 #[cfg(target_feature = "avx2")]
+use crate::avx2::ser::{escape_bits, ESCAPE_BLOCK};
+#[cfg(target_feature = "avx2")]
 use crate::avx2::stage1::{SimdInput, SIMDINPUT_LENGTH, SIMDJSON_PADDING};
 #[cfg(target_feature = "avx2")]
 use simdutf8::basic::imp::x86::avx2::ChunkedUtf8ValidatorImp;
@@ -158,6 +269,8 @@ mod sse42;
 #[cfg(all(target_feature = "sse4.2", not(target_feature = "avx2")))]
 pub use crate::sse42::deser::*;
 #[cfg(all(target_feature = "sse4.2", not(target_feature = "avx2")))]
+use crate::sse42::ser::{escape_bits, ESCAPE_BLOCK};
+#[cfg(all(target_feature = "sse4.2", not(target_feature = "avx2")))]
 use crate::sse42::stage1::{SimdInput, SIMDINPUT_LENGTH, SIMDJSON_PADDING};
 #[cfg(all(target_feature = "sse4.2", not(target_feature = "avx2")))]
 use simdutf8::basic::imp::x86::sse42::ChunkedUtf8ValidatorImp;
@@ -167,6 +280,8 @@ mod neon;
 #[cfg(target_feature = "neon")]
 pub use crate::neon::deser::*;
 #[cfg(target_feature = "neon")]
+use crate::neon::ser::{escape_bits, ESCAPE_BLOCK};
+#[cfg(target_feature = "neon")]
 use crate::neon::stage1::{SimdInput, SIMDINPUT_LENGTH, SIMDJSON_PADDING};
 #[cfg(target_feature = "neon")]
 use simdutf8::basic::imp::aarch64::neon::ChunkedUtf8ValidatorImp;
@@ -194,6 +309,12 @@ pub use crate::sse42::deser::*;
     target_feature = "avx2",
     target_feature = "neon"
 ))))]
+use crate::sse42::ser::{escape_bits, ESCAPE_BLOCK};
+#[cfg(all(not(any(
+    target_feature = "sse4.2",
+    target_feature = "avx2",
+    target_feature = "neon"
+))))]
 use crate::sse42::stage1::{SimdInput, SIMDINPUT_LENGTH, SIMDJSON_PADDING};
 #[cfg(all(not(any(
     target_feature = "sse4.2",
@@ -219,8 +340,18 @@ pub mod value;
 use std::mem;
 pub use value_trait::StaticNode;
 
+pub use crate::encoding::Encoding;
 pub use crate::error::{Error, ErrorType};
+pub use crate::escape::Escape;
+pub use crate::format::{CompactFormatter, Formatter, PrettyFormatter};
+pub use crate::handler::{parse_with_handler, Handler};
+#[cfg(feature = "mmap")]
+pub use crate::mmap::{from_file, MappedFile};
+pub use crate::projection::Projection;
+pub use crate::rewriter::Rewriter;
+pub use crate::span::{to_tape_with_spans, Span};
 pub use crate::value::*;
+pub use crate::writer::JsonWriter;
 pub use value_trait::ValueType;
 
 /// simd-json Result type
@@ -229,9 +360,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[cfg(feature = "known-key")]
 mod known_key;
 #[cfg(feature = "known-key")]
-pub use known_key::{Error as KnownKeyError, KnownKey};
+pub use known_key::{Error as KnownKeyError, KnownKey, KnownPath, KnownSegment};
 
-pub use crate::tape::{Node, Tape};
+pub use crate::tape::{CompactTape, Node, Tape};
 use std::alloc::{alloc, handle_alloc_error, Layout};
 use std::ops::{Deref, DerefMut};
 use std::ptr::NonNull;
@@ -246,6 +377,18 @@ pub fn to_tape(s: &mut [u8]) -> Result<Vec<Node>> {
     Deserializer::from_slice(s).map(Deserializer::into_tape)
 }
 
+/// Creates a [`CompactTape`] from the input, its nodes take 8 bytes
+/// instead of the 24 of a node on the tape [`to_tape`] returns, and it
+/// doesn't borrow the input.
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_compact_tape(s: &mut [u8]) -> Result<CompactTape> {
+    let mut builder = crate::tape::CompactTapeBuilder::new();
+    stry!(parse_with_handler(s, &mut builder));
+    Ok(builder.into_tape())
+}
+
 pub(crate) trait Stage1Parse<T> {
     fn compute_quote_mask(quote_bits: u64) -> u64;
 
@@ -385,12 +528,71 @@ pub(crate) trait Stage1Parse<T> {
     fn zero() -> T;
 }
 
+/// Offset of a structural character in the input. We use `u32` as long as
+/// the input is smaller then 4GB since it allows flattening the structural
+/// bits with SIMD, `u64` for everything larger.
+pub(crate) trait StructuralIndex: Copy + Default {
+    /// flattens `bits` into `base` see `Stage1Parse::flatten_bits`
+    fn flatten_bits(base: &mut Vec<Self>, idx: usize, bits: u64);
+    fn as_usize(self) -> usize;
+}
+
+impl StructuralIndex for u32 {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    #[allow(clippy::cast_possible_truncation)]
+    fn flatten_bits(base: &mut Vec<Self>, idx: usize, bits: u64) {
+        SimdInput::flatten_bits(base, idx as u32, bits);
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn as_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl StructuralIndex for u64 {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn flatten_bits(base: &mut Vec<Self>, idx: usize, mut bits: u64) {
+        let idx_minus_64 = (idx as u64).wrapping_sub(64);
+        base.reserve(bits.count_ones() as usize);
+        while bits != 0 {
+            base.push(idx_minus_64.wrapping_add(u64::from(bits.trailing_zeros())));
+            bits &= bits.wrapping_sub(1);
+        }
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    #[allow(clippy::cast_possible_truncation)]
+    fn as_usize(self) -> usize {
+        self as usize
+    }
+}
+
+/// Structural indexes of a document in the width they were found with
+enum Indexes {
+    U32(Vec<u32>),
+    U64(Vec<u64>),
+}
+
+/// The input of a document and what we need to find the text of its
+/// values in it, see [`Deserializer::from_slice_with_source`]
+struct Source<'de> {
+    // the input after parsing, strings are unescaped in place
+    input: &'de [u8],
+    indexes: Indexes,
+    // the input as it was before parsing
+    #[cfg_attr(not(feature = "serde_impl"), allow(dead_code))]
+    original: AlignedBuf,
+}
+
 /// Deserializer struct to deserialize a JSON
 pub struct Deserializer<'de> {
     // Note: we use the 2nd part as both index and length since only one is ever
     // used (array / object use len) everything else uses idx
     pub(crate) tape: Vec<Node<'de>>,
     idx: usize,
+    source: Option<Source<'de>>,
+    // last tape node we looked up the structural index of
+    #[cfg_attr(not(feature = "serde_impl"), allow(dead_code))]
+    cursor: (usize, usize),
 }
 
 impl<'de> Deserializer<'de> {
@@ -456,27 +658,90 @@ impl<'de> Deserializer<'de> {
         input_buffer: &mut AlignedBuf,
         string_buffer: &mut [u8],
     ) -> Result<Self> {
+        let (tape, _, _) = stry!(Self::parse(input, input_buffer, string_buffer));
+        Ok(Self::new(tape, None))
+    }
+
+    /// Creates a deserializer like [`from_slice`](Self::from_slice) that
+    /// holds on to the input, its structural indexes and a copy of the
+    /// input from before strings were unescaped.
+    ///
+    /// [`spans`](Self::spans) and `RawValue` need them to find the text of
+    /// values in the input, other deserializers drop them after parsing.
+    /// `simd_json::serde::from_slice_with_raw` deserializes with one.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON.
+    pub fn from_slice_with_source(input: &'de mut [u8]) -> Result<Self> {
         let len = input.len();
 
-        if len > std::u32::MAX as usize {
-            return Err(Self::error(ErrorType::InputTooLarge));
-        }
+        let mut string_buffer: Vec<u8> = Vec::with_capacity(len + SIMDJSON_PADDING);
+        unsafe {
+            string_buffer.set_len(len + SIMDJSON_PADDING);
+        };
+        let mut original = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
 
-        if input_buffer.capacity() < len + SIMDJSON_PADDING * 2 {
-            *input_buffer = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
+        let (tape, input, indexes) = stry!(Self::parse(input, &mut original, &mut string_buffer));
+        Ok(Self::new(
+            tape,
+            Some(Source {
+                input,
+                indexes,
+                original,
+            }),
+        ))
+    }
+
+    fn new(tape: Vec<Node<'de>>, source: Option<Source<'de>>) -> Self {
+        Self {
+            tape,
+            idx: 0,
+            source,
+            cursor: (0, 0),
         }
+    }
 
-        unsafe {
-            input_buffer
-                .as_mut_slice()
-                .get_unchecked_mut(..len)
-                .clone_from_slice(input);
-            *(input_buffer.get_unchecked_mut(len)) = 0;
-            input_buffer.set_len(len);
-        };
+    /// Parses `input` into a tape, returns it along with the input, now
+    /// only shared since the tape borrows from it, and the structural
+    /// indexes.
+    fn parse(
+        input: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+    ) -> Result<(Vec<Node<'de>>, &'de [u8], Indexes)> {
+        let len = input.len();
 
-        let s1_result: std::result::Result<Vec<u32>, ErrorType> =
-            unsafe { Self::find_structural_bits(&input_buffer) };
+        input_buffer.load(input);
+
+        // Documents that don't fit into 32 bit offsets need 64 bit
+        // structural indexes, this costs us the SIMD flattening of the
+        // structural bits so we only do it when we have to.
+        if len > u32::MAX as usize {
+            let (tape, input, indexes) = stry!(Self::parse_with_indexes::<u64>(
+                input,
+                input_buffer,
+                string_buffer
+            ));
+            Ok((tape, input, Indexes::U64(indexes)))
+        } else {
+            let (tape, input, indexes) = stry!(Self::parse_with_indexes::<u32>(
+                input,
+                input_buffer,
+                string_buffer
+            ));
+            Ok((tape, input, Indexes::U32(indexes)))
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn parse_with_indexes<I: StructuralIndex>(
+        input: &'de mut [u8],
+        input_buffer: &[u8],
+        string_buffer: &mut [u8],
+    ) -> Result<(Vec<Node<'de>>, &'de [u8], Vec<I>)> {
+        let s1_result: std::result::Result<Vec<I>, ErrorType> =
+            unsafe { Self::find_structural_bits(input_buffer) };
 
         let structural_indexes = match s1_result {
             Ok(i) => i,
@@ -485,10 +750,13 @@ impl<'de> Deserializer<'de> {
             }
         };
 
-        let tape: Vec<Node> =
-            Self::build_tape(input, &input_buffer, string_buffer, &structural_indexes)?;
-
-        Ok(Self { tape, idx: 0 })
+        let (tape, input) = stry!(Self::build_tape(
+            input,
+            input_buffer,
+            string_buffer,
+            &structural_indexes
+        ));
+        Ok((tape, input, structural_indexes))
     }
 
     #[cfg(feature = "serde_impl")]
@@ -497,6 +765,67 @@ impl<'de> Deserializer<'de> {
         self.idx += 1;
     }
 
+    /// Takes the next node from the tape and moves the cursor past it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tape is exhausted.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub fn next_node(&mut self) -> Result<Node<'de>> {
+        self.tape
+            .get(self.idx + 1)
+            .copied()
+            .map(|n| {
+                self.idx += 1;
+                n
+            })
+            .ok_or_else(|| Self::error(ErrorType::UnexpectedEnd))
+    }
+
+    /// Looks at the next node on the tape without moving the cursor.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tape is exhausted.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub fn peek_node(&self) -> Result<Node<'de>> {
+        self.tape
+            .get(self.idx + 1)
+            .copied()
+            .ok_or_else(|| Self::error(ErrorType::UnexpectedEnd))
+    }
+
+    /// Moves the cursor past the next value, including all of its
+    /// children.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tape is exhausted.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub fn skip_value(&mut self) -> Result<()> {
+        match stry!(self.next_node()) {
+            Node::Array(_, end) | Node::Object(_, end) => self.idx = end - 1,
+            Node::String(_) | Node::Static(_) => (),
+        }
+        Ok(())
+    }
+
+    /// Index on the tape of the node last taken with
+    /// [`Deserializer::next_node`], `0` before the first one.
+    #[must_use]
+    pub fn node_index(&self) -> usize {
+        self.idx
+    }
+
+    /// Creates an error for the node at `node` on the tape, the index of
+    /// the error is the index of the node. [`Deserializer::spans`] maps it
+    /// to a position in the input.
+    #[cold]
+    #[must_use]
+    pub fn node_error(node: usize, error: ErrorType) -> Error {
+        Self::raw_error(node, '?', error)
+    }
+
     /// Same as next() but we pull out the check so we don't need to
     /// stry every time. Use this only if you know the next element exists!
     ///
@@ -512,14 +841,14 @@ impl<'de> Deserializer<'de> {
 
     //#[inline(never)]
     #[allow(clippy::cast_possible_truncation)]
-    pub(crate) unsafe fn find_structural_bits(
+    pub(crate) unsafe fn find_structural_bits<I: StructuralIndex>(
         input: &[u8],
-    ) -> std::result::Result<Vec<u32>, ErrorType> {
+    ) -> std::result::Result<Vec<I>, ErrorType> {
         let len = input.len();
         // 6 is a heuristic number to estimate it turns out a rate of 1/6 structural characters
         // leads almost never to relocations.
         let mut structural_indexes = Vec::with_capacity(len / 6);
-        structural_indexes.push(0); // push extra root element
+        structural_indexes.push(I::default()); // push extra root element
 
         let mut utf8_validator = ChunkedUtf8ValidatorImp::new();
 
@@ -577,8 +906,7 @@ impl<'de> Deserializer<'de> {
 
             // take the previous iterations structural bits, not our current iteration,
             // and flatten
-            #[allow(clippy::cast_possible_truncation)]
-            SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
+            I::flatten_bits(&mut structural_indexes, idx, structurals);
 
             let mut whitespace: u64 = 0;
             input.find_whitespace_and_structurals(&mut whitespace, &mut structurals);
@@ -623,7 +951,7 @@ impl<'de> Deserializer<'de> {
 
             // take the previous iterations structural bits, not our current iteration,
             // and flatten
-            SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
+            I::flatten_bits(&mut structural_indexes, idx, structurals);
 
             let mut whitespace: u64 = 0;
             input.find_whitespace_and_structurals(&mut whitespace, &mut structurals);
@@ -643,7 +971,7 @@ impl<'de> Deserializer<'de> {
             return Err(ErrorType::Syntax);
         }
         // finally, flatten out the remaining structurals from the last iteration
-        SimdInput::flatten_bits(&mut structural_indexes, idx as u32, structurals);
+        I::flatten_bits(&mut structural_indexes, idx, structurals);
 
         // a valid JSON file cannot have zero structural indexes - we should have
         // found something (note that we compare to 1 as we always add the root!)
@@ -651,7 +979,7 @@ impl<'de> Deserializer<'de> {
             return Err(ErrorType::Eof);
         }
 
-        if structural_indexes.last() > Some(&(len as u32)) {
+        if structural_indexes.last().map(|i| i.as_usize()) > Some(len) {
             return Err(ErrorType::InternalError);
         }
 
@@ -692,6 +1020,24 @@ impl AlignedBuf {
         }
     }
 
+    /// Copies `input` into the buffer, growing it if it can't hold the
+    /// input along with the padding required for SIMD reads.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn load(&mut self, input: &[u8]) {
+        let len = input.len();
+
+        if self.capacity() < len + SIMDJSON_PADDING * 2 {
+            *self = Self::with_capacity(len + SIMDJSON_PADDING * 2);
+        }
+
+        unsafe {
+            let dst = self.as_mut_ptr();
+            std::ptr::copy_nonoverlapping(input.as_ptr(), dst, len);
+            *dst.add(len) = 0;
+            self.set_len(len);
+        };
+    }
+
     fn capacity_overflow() -> ! {
         panic!("capacity overflow");
     }
@@ -714,10 +1060,13 @@ impl DerefMut for AlignedBuf {
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unnecessary_operation, clippy::non_ascii_literal)]
-    use super::{owned::Value, to_borrowed_value, to_owned_value, Deserializer};
+    use super::{
+        owned::Value, to_borrowed_value, to_owned_value, AlignedBuf, Deserializer, Indexes, Result,
+        Source, SIMDJSON_PADDING,
+    };
     use crate::tape::*;
     use proptest::prelude::*;
-    use value_trait::{StaticNode, Writable};
+    use value_trait::{StaticNode, ValueAccess, Writable};
 
     #[test]
     fn count1() {
@@ -761,6 +1110,76 @@ mod tests {
         assert_eq!(simd.tape[2], Node::Array(0, 3));
     }
 
+    /// Parses `d` with 64 bit structural indexes, like we do for documents
+    /// larger than 4GB
+    fn from_slice_u64(d: &mut [u8]) -> Result<Deserializer<'_>> {
+        let mut original = AlignedBuf::with_capacity(d.len() + SIMDJSON_PADDING * 2);
+        original.load(d);
+        let mut string_buffer = vec![0; d.len() + SIMDJSON_PADDING];
+        let (tape, input, indexes) =
+            Deserializer::parse_with_indexes::<u64>(d, &original, &mut string_buffer)?;
+        Ok(Deserializer::new(
+            tape,
+            Some(Source {
+                input,
+                indexes: Indexes::U64(indexes),
+                original,
+            }),
+        ))
+    }
+
+    #[test]
+    fn large_structural_indexes() {
+        let d = br#"{"key": [1, "two", {"three": null}], "a somewhat longer key with an \"escape\" in it": [true, false, -1.5, "\u00e9"]}"#;
+        let small = unsafe { Deserializer::find_structural_bits::<u32>(d) }.expect("");
+        let large = unsafe { Deserializer::find_structural_bits::<u64>(d) }.expect("");
+        assert_eq!(small.into_iter().map(u64::from).collect::<Vec<_>>(), large);
+
+        let mut d1 = d.to_vec();
+        let mut d2 = d.to_vec();
+        let small = Deserializer::from_slice_with_source(&mut d1).expect("");
+        let large = from_slice_u64(&mut d2).expect("");
+        assert!(matches!(
+            large.source,
+            Some(Source {
+                indexes: Indexes::U64(_),
+                ..
+            })
+        ));
+        assert_eq!(small.tape, large.tape);
+        assert_eq!(small.spans(), large.spans());
+        #[cfg(feature = "serde_impl")]
+        for node in 1..small.tape.len() {
+            assert_eq!(
+                small.raw_value(node).map(|r| r.get().to_string()),
+                large.raw_value(node).map(|r| r.get().to_string())
+            );
+        }
+
+        let invalid: &[&[u8]] = &[b"[1, 2", br#"{"a" 1}"#, b"[1] 2", b"[tru]", b"]"];
+        for d in invalid {
+            let small = Deserializer::from_slice(&mut d.to_vec()).err();
+            let large = from_slice_u64(&mut d.to_vec()).err();
+            assert!(small.is_some());
+            assert_eq!(small, large);
+        }
+    }
+
+    #[test]
+    #[ignore]
+    fn larger_then_4gb() {
+        // `[0,0,...,0]` is just over 4GB
+        let len = (u32::MAX as usize) / 2 + 2;
+        let mut d = Vec::with_capacity(len * 2 + 1);
+        d.push(b'[');
+        for _ in 0..len {
+            d.extend_from_slice(b"0,");
+        }
+        *d.last_mut().expect("") = b']';
+        let v = to_borrowed_value(&mut d).expect("");
+        assert_eq!(v.as_array().map(Vec::len), Some(len));
+    }
+
     #[cfg(feature = "128bit")]
     #[test]
     fn odd_nuber() {
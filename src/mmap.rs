@@ -0,0 +1,189 @@
+//! Parsing of memory mapped files.
+//!
+//! Files are mapped privately (copy-on-write) so the in-place unescaping
+//! done while parsing strings never reaches the file on disk, the pages
+//! touched are copied by the OS on first write.
+//!
+//! The mapping itself is never padded. Stage 1 and stage 2 read from the
+//! padded `AlignedBuf` copy of the input that every `Deserializer` creates,
+//! so SIMD loads past the end of the file only ever touch that buffer.
+//! Writes into the mapping are limited to the bounds of strings found in
+//! the file.
+
+use crate::{BorrowedValue, Deserializer, OwnedValue, Result};
+use memmap2::{MmapMut, MmapOptions};
+use std::fs::File;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+
+/// A file mapped privately (copy-on-write) into memory.
+///
+/// Values parsed from the mapping borrow from it, so they can not outlive
+/// the `MappedFile` they were created from.
+///
+/// ```no_run
+/// use simd_json::prelude::*;
+/// let mut file = unsafe { simd_json::from_file("data.json") }.unwrap();
+/// let v = file.to_borrowed_value().unwrap();
+/// println!("{}", v.encode());
+/// ```
+pub struct MappedFile {
+    map: MmapMut,
+}
+
+impl MappedFile {
+    /// Memory maps the file at `path` privately.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file can not be opened or mapped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated, by this or any other
+    /// process, while it is mapped. Doing so is undefined behaviour and
+    /// can crash the process with a `SIGBUS` on unix systems.
+    pub unsafe fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file = File::open(path)?;
+        let map = MmapOptions::new().map_copy(&file)?;
+        Ok(Self { map })
+    }
+
+    /// Creates a `Deserializer` for the mapped file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file content is invalid JSON.
+    pub fn deserializer(&mut self) -> Result<Deserializer<'_>> {
+        Deserializer::from_slice(&mut self.map)
+    }
+
+    /// Parses the mapped file into a borrowed value, strings reference
+    /// the mapping where possible.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file content is invalid JSON.
+    pub fn to_borrowed_value(&mut self) -> Result<BorrowedValue<'_>> {
+        crate::to_borrowed_value(&mut self.map)
+    }
+
+    /// Parses the mapped file into an owned value.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file content is invalid JSON.
+    pub fn to_owned_value(&mut self) -> Result<OwnedValue> {
+        crate::to_owned_value(&mut self.map)
+    }
+}
+
+impl Deref for MappedFile {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.map
+    }
+}
+
+impl DerefMut for MappedFile {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        &mut self.map
+    }
+}
+
+/// Memory maps the file at `path` privately so it can be parsed in place,
+/// see [`MappedFile`].
+///
+/// # Errors
+///
+/// Will return `Err` if the file can not be opened or mapped.
+///
+/// # Safety
+///
+/// The file must not be modified or truncated while it is mapped, see
+/// [`MappedFile::open`].
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub unsafe fn from_file<P: AsRef<Path>>(path: P) -> Result<MappedFile> {
+    MappedFile::open(path)
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::prelude::*;
+    use std::io::Write;
+    use std::path::PathBuf;
+
+    fn tmp_file(name: &str, content: &[u8]) -> PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("simd-json-{}-{}.json", std::process::id(), name));
+        let mut f = File::create(&path).unwrap();
+        f.write_all(content).unwrap();
+        path
+    }
+
+    #[test]
+    fn borrowed() {
+        let path = tmp_file("borrowed", br#"{"key": ["v\"alue", 1, null]}"#);
+        {
+            let mut file = unsafe { from_file(&path) }.unwrap();
+            let v = file.to_borrowed_value().unwrap();
+            assert_eq!(v["key"][0], "v\"alue");
+            assert_eq!(v["key"][1], 1);
+        }
+        // the mapping is private so the unescaping never reaches the disk
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            br#"{"key": ["v\"alue", 1, null]}"#
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn owned() {
+        let path = tmp_file("owned", b"[1, 2, \"three\"]");
+        let mut file = unsafe { MappedFile::open(&path) }.unwrap();
+        let v = file.to_owned_value().unwrap();
+        assert_eq!(v.encode(), r#"[1,2,"three"]"#);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn page_sized() {
+        // a string ending right at the end of the mapping
+        let mut content = vec![b'"'];
+        content.resize(4095, b'a');
+        content.push(b'"');
+        let path = tmp_file("page_sized", &content);
+        let mut file = unsafe { from_file(&path) }.unwrap();
+        let v = file.to_borrowed_value().unwrap();
+        assert_eq!(v.as_str().map(str::len), Some(4094));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn empty() {
+        let path = tmp_file("empty", b"");
+        let mut file = unsafe { from_file(&path) }.unwrap();
+        assert!(file.is_empty());
+        assert!(file.deserializer().is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing() {
+        let r = unsafe { from_file("/this/file/does/not/exist.json") };
+        assert!(r.is_err());
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn serde() {
+        let path = tmp_file("serde", br#"{"a": [1, 2]}"#);
+        let mut file = unsafe { from_file(&path) }.unwrap();
+        let v: std::collections::HashMap<&str, Vec<u8>> = crate::from_slice(&mut file).unwrap();
+        assert_eq!(v["a"], vec![1, 2]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,28 @@
+use crate::neon::stage1::bit_mask;
+use std::arch::aarch64::{
+    vandq_u8, vceqq_u8, vcleq_u8, vgetq_lane_u16, vld1q_u8, vmovq_n_u8, vorrq_u8, vpaddq_u8,
+    vreinterpretq_u16_u8,
+};
+
+/// Number of bytes `escape_bits` looks at
+pub(crate) const ESCAPE_BLOCK: usize = 16;
+
+/// Returns a bit for every byte in the `ESCAPE_BLOCK` bytes at `ptr` that
+/// has to be escaped in a JSON string, those are quotes, backslashes and
+/// control characters.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub(crate) unsafe fn escape_bits(ptr: *const u8) -> u32 {
+    let data = vld1q_u8(ptr);
+    let bs_or_quote = vorrq_u8(
+        vceqq_u8(data, vmovq_n_u8(b'\\')),
+        vceqq_u8(data, vmovq_n_u8(b'"')),
+    );
+    let control = vcleq_u8(data, vmovq_n_u8(0x1f));
+    let bits = vandq_u8(vorrq_u8(bs_or_quote, control), bit_mask());
+    // three pairwise additions fold the lower and upper eight lanes into
+    // the first two bytes
+    let sum = vpaddq_u8(bits, bits);
+    let sum = vpaddq_u8(sum, sum);
+    let sum = vpaddq_u8(sum, sum);
+    u32::from(vgetq_lane_u16(vreinterpretq_u16_u8(sum), 0))
+}
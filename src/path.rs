@@ -0,0 +1,202 @@
+//! Helpers to walk the structural indexes of a document without building
+//! a tape, and to match the paths of the values found on the way.
+
+use crate::{Deserializer, Error, ErrorType, Node, Result, StructuralIndex};
+use std::borrow::Cow;
+
+/// Selects values in a document
+#[derive(Debug, Clone)]
+pub(crate) enum Target {
+    /// Matches object members with this key at any depth
+    Key(String),
+    /// Matches the value at exactly this path
+    Pointer(Vec<String>),
+}
+
+impl Target {
+    /// Parses a [JSON pointer](https://tools.ietf.org/html/rfc6901) into
+    /// its unescaped segments, a pointer has to be empty or start with `/`
+    pub(crate) fn pointer(pointer: &str) -> Result<Self> {
+        let invalid = || Error::generic(ErrorType::InvalidPointer(pointer.to_string()));
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            return Err(invalid());
+        }
+        let mut segments = Vec::new();
+        for s in pointer.split('/').skip(1) {
+            let mut escapes = s.split('~').skip(1);
+            if escapes.any(|e| !(e.starts_with('0') || e.starts_with('1'))) {
+                return Err(invalid());
+            }
+            segments.push(s.replace("~1", "/").replace("~0", "~"));
+        }
+        Ok(Target::Pointer(segments))
+    }
+
+    /// Checks if the value at `path` is selected
+    pub(crate) fn matches<'s, P>(&self, path: P) -> bool
+    where
+        P: DoubleEndedIterator<Item = &'s Segment<'s>> + ExactSizeIterator,
+    {
+        match self {
+            Target::Key(key) => matches!(path.last(), Some(Segment::Key(k)) if k == key),
+            Target::Pointer(segments) => {
+                segments.len() == path.len() && path.zip(segments.iter()).all(|(p, s)| p.matches(s))
+            }
+        }
+    }
+
+    /// Checks if values below `path` can be selected
+    pub(crate) fn selects_below<'s, P>(&self, path: P) -> bool
+    where
+        P: Iterator<Item = &'s Segment<'s>> + ExactSizeIterator,
+    {
+        match self {
+            Target::Key(_) => true,
+            Target::Pointer(segments) => {
+                segments.len() > path.len() && path.zip(segments.iter()).all(|(p, s)| p.matches(s))
+            }
+        }
+    }
+}
+
+/// A segment of the path to a value
+pub(crate) enum Segment<'input> {
+    Key(Cow<'input, str>),
+    Index(usize),
+}
+
+impl Segment<'_> {
+    fn matches(&self, pointer_segment: &str) -> bool {
+        match self {
+            Segment::Key(k) => k == pointer_segment,
+            Segment::Index(i) => array_index(pointer_segment) == Some(*i),
+        }
+    }
+}
+
+/// Parses an array index of a JSON pointer, RFC 6901 only allows `0` or
+/// digits without a leading zero
+fn array_index(pointer_segment: &str) -> Option<usize> {
+    let digits = pointer_segment.as_bytes();
+    if digits.is_empty()
+        || (digits[0] == b'0' && digits.len() > 1)
+        || !digits.iter().all(u8::is_ascii_digit)
+    {
+        return None;
+    }
+    pointer_segment.parse().ok()
+}
+
+/// The structural indexes of `input` as found by stage 1
+pub(crate) struct Structurals<'input, I> {
+    pub(crate) input: &'input [u8],
+    pub(crate) idx: &'input [I],
+}
+
+impl<'input, I: StructuralIndex> Structurals<'input, I> {
+    /// Number of structural indexes, including the root
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn len(&self) -> usize {
+        self.idx.len()
+    }
+
+    /// Byte offset of the structural at `p`
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn at(&self, p: usize) -> usize {
+        self.idx[p].as_usize()
+    }
+
+    /// Structural character at `p`, fails if the input ends before
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn char_at(&self, p: usize) -> Result<u8> {
+        if p < self.idx.len() {
+            Ok(self.input[self.at(p)])
+        } else {
+            Err(self.error(p, ErrorType::EarlyEnd))
+        }
+    }
+
+    pub(crate) fn error(&self, p: usize, error: ErrorType) -> Error {
+        if p < self.idx.len() {
+            let i = self.at(p);
+            Error::new(i, self.input[i] as char, error)
+        } else {
+            Error::new(self.input.len(), '?', error)
+        }
+    }
+
+    /// Decodes the key whose opening quote is at `p`
+    pub(crate) fn key(&self, p: usize) -> Result<Cow<'input, str>> {
+        let input = self.input;
+        let start = self.at(p) + 1;
+        // the key ends at the colon, we need to strip the whitespace
+        // and the closing quote
+        let mut end = if p + 1 < self.idx.len() {
+            self.at(p + 1)
+        } else {
+            input.len()
+        };
+        while end > start && input[end - 1].is_ascii_whitespace() {
+            end -= 1;
+        }
+        if end <= start || input[end - 1] != b'"' {
+            return Err(self.error(p, ErrorType::ExpectedString));
+        }
+        let raw = &input[start..end - 1];
+        if raw.contains(&b'\\') {
+            let mut quoted = Vec::with_capacity(raw.len() + 2);
+            quoted.push(b'"');
+            quoted.extend_from_slice(raw);
+            quoted.push(b'"');
+            match Deserializer::from_slice(&mut quoted)?.into_tape().get(1) {
+                Some(Node::String(s)) => Ok(Cow::Owned((*s).to_string())),
+                _ => Err(self.error(p, ErrorType::ExpectedString)),
+            }
+        } else {
+            match std::str::from_utf8(raw) {
+                Ok(s) => Ok(Cow::Borrowed(s)),
+                Err(_) => Err(self.error(p, ErrorType::InvalidUtf8)),
+            }
+        }
+    }
+
+    /// Skips the value starting at `p`, returns the index of the first
+    /// structural after it
+    pub(crate) fn skip_value(&self, p: usize) -> Result<usize> {
+        let mut closers = Vec::new();
+        let mut q = p;
+        loop {
+            match self.char_at(q)? {
+                b'{' => closers.push(b'}'),
+                b'[' => closers.push(b']'),
+                c @ b'}' | c @ b']' if closers.pop() != Some(c) => {
+                    return Err(self.error(q, ErrorType::Syntax));
+                }
+                _ => (),
+            }
+            q += 1;
+            if closers.is_empty() {
+                return Ok(q);
+            }
+        }
+    }
+
+    /// The end of the value starting at `p` with the next structural
+    /// after it at `next`, trailing whitespace is not part of the value
+    pub(crate) fn value_end(&self, p: usize, next: usize) -> usize {
+        match self.input[self.at(p)] {
+            b'{' | b'[' => self.at(next - 1) + 1,
+            _ => {
+                let mut end = if next < self.idx.len() {
+                    self.at(next)
+                } else {
+                    self.input.len()
+                };
+                while self.input[end - 1].is_ascii_whitespace() {
+                    end -= 1;
+                }
+                end
+            }
+        }
+    }
+}
@@ -0,0 +1,475 @@
+//! Parsing of selected parts of a document.
+//!
+//! Only the selected subtrees are parsed into a value, everything else is
+//! skipped using the structural indexes found by stage 1 without decoding
+//! strings or numbers.
+
+use crate::path::{Segment, Structurals, Target};
+use crate::value::ObjectMap;
+use crate::{
+    AlignedBuf, BorrowedValue, Deserializer, Error, ErrorType, OwnedValue, Result, StaticNode,
+    StructuralIndex, SIMDJSON_PADDING,
+};
+
+/// A compiled set of keys and [JSON pointers](https://tools.ietf.org/html/rfc6901)
+/// to parse out of a document.
+///
+/// Keys select object members with that key at any depth, pointers select
+/// the value at exactly that path. The resulting value contains the
+/// selected subtrees at the same paths as the input along with the objects
+/// and arrays leading to them. Arrays on the way only contain the elements
+/// that contain selected values, so their indexes can differ from the
+/// input.
+///
+/// Selected subtrees are fully validated, for the rest of the document
+/// only the structure is.
+///
+/// ```
+/// use simd_json::{json, Projection};
+///
+/// let projection = Projection::new().key("id").pointer("/user/name").unwrap();
+/// let mut d = br#"{"id": 1, "user": {"name": "snot", "bio": "..."}, "items": [{"id": 2}, {"x": 3}]}"#.to_vec();
+/// let v = projection.to_owned_value(&mut d).unwrap();
+/// assert_eq!(v, json!({"id": 1, "user": {"name": "snot"}, "items": [{"id": 2}]}));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Projection {
+    targets: Vec<Target>,
+}
+
+impl Projection {
+    /// Creates a projection that selects nothing
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Selects all object members with the key `key`
+    #[must_use]
+    pub fn key<K: Into<String>>(mut self, key: K) -> Self {
+        self.targets.push(Target::Key(key.into()));
+        self
+    }
+
+    /// Selects the value at `pointer`, the empty pointer selects the
+    /// whole document
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `pointer` is not a valid JSON pointer, like
+    /// `user/name` without the leading `/`.
+    pub fn pointer(mut self, pointer: &str) -> Result<Self> {
+        self.targets.push(Target::pointer(pointer)?);
+        Ok(self)
+    }
+
+    /// Parses the selected parts of `s` into a borrowed value, strings
+    /// in it are unescaped in place and reference `s`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON.
+    pub fn to_borrowed_value<'de>(&self, s: &'de mut [u8]) -> Result<BorrowedValue<'de>> {
+        self.project(s)
+    }
+
+    /// Parses the selected parts of `s` into an owned value.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON.
+    pub fn to_owned_value(&self, s: &mut [u8]) -> Result<OwnedValue> {
+        self.project(s)
+    }
+
+    fn project<'de, V: Projected<'de>>(&self, s: &'de mut [u8]) -> Result<V> {
+        let len = s.len();
+        let mut input_buffer = AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2);
+        let mut string_buffer = vec![0_u8; len + SIMDJSON_PADDING];
+
+        if self
+            .targets
+            .iter()
+            .any(|t| t.matches(std::iter::empty::<&Segment>()))
+        {
+            return V::parse(s, &mut input_buffer, &mut string_buffer);
+        }
+
+        input_buffer.load(s);
+        if len > u32::MAX as usize {
+            self.project_with_indexes::<u64, V>(s, &input_buffer, &mut string_buffer)
+        } else {
+            self.project_with_indexes::<u32, V>(s, &input_buffer, &mut string_buffer)
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn project_with_indexes<'de, I, V>(
+        &self,
+        input: &'de mut [u8],
+        input_buffer: &[u8],
+        string_buffer: &mut [u8],
+    ) -> Result<V>
+    where
+        I: StructuralIndex,
+        V: Projected<'de>,
+    {
+        let structural_indexes: Vec<I> =
+            match unsafe { Deserializer::find_structural_bits(input_buffer) } {
+                Ok(i) => i,
+                Err(t) => return Err(Error::generic(t)),
+            };
+        // We walk the copy of the input in `input_buffer` since parsing
+        // the selected values rewrites `input`, they get their own buffer.
+        let s = Structurals {
+            input: input_buffer,
+            idx: &structural_indexes,
+        };
+        let mut value_buffer = AlignedBuf::with_capacity(SIMDJSON_PADDING * 2);
+
+        // the part of the input after the last selected value
+        let mut rest = input;
+        let mut rest_start = 0;
+        let mut take = |start: usize, end: usize| -> &'de mut [u8] {
+            let (_, tail) = std::mem::take(&mut rest).split_at_mut(start - rest_start);
+            let (selected, tail) = tail.split_at_mut(end - start);
+            rest = tail;
+            rest_start = end;
+            selected
+        };
+
+        let mut stack: Vec<Frame<V>> = Vec::new();
+        let mut result = None;
+        // skip the root index
+        let mut p = 1;
+        let mut state = State::Value;
+        loop {
+            match state {
+                State::Value => match s.char_at(p)? {
+                    c @ b'{' | c @ b'[' => {
+                        let object = c == b'{';
+                        stack.push(Frame {
+                            object,
+                            segment: Segment::Index(0),
+                            children: Vec::new(),
+                        });
+                        p += 1;
+                        match s.char_at(p)? {
+                            b'}' if object => state = State::End,
+                            b']' if !object => state = State::End,
+                            _ if object => state = State::Member,
+                            _ => state = State::Element,
+                        }
+                    }
+                    b'"' | b't' | b'f' | b'n' | b'-' | b'0'..=b'9' => {
+                        p += 1;
+                        state = State::AfterValue;
+                    }
+                    _ => return Err(s.error(p, ErrorType::UnexpectedCharacter)),
+                },
+                State::Member | State::Element => {
+                    if let State::Member = state {
+                        if s.char_at(p)? != b'"' {
+                            return Err(s.error(p, ErrorType::ExpectedObjectKey));
+                        }
+                        let key = stry!(s.key(p));
+                        if s.char_at(p + 1)? != b':' {
+                            return Err(s.error(p + 1, ErrorType::ExpectedObjectColon));
+                        }
+                        p += 2;
+                        if let Some(frame) = stack.last_mut() {
+                            frame.segment = Segment::Key(key);
+                        }
+                    }
+                    let path = || stack.iter().map(|f| &f.segment);
+                    if self.targets.iter().any(|t| t.matches(path())) {
+                        let next = stry!(s.skip_value(p));
+                        let start = s.at(p);
+                        let selected = take(start, s.value_end(p, next));
+                        // errors of the selected value are relative to it
+                        let v = stry!(V::parse(selected, &mut value_buffer, string_buffer)
+                            .map_err(|e| {
+                                let index = e.index() + start;
+                                e.with_index(index)
+                            }));
+                        if let Some(frame) = stack.last_mut() {
+                            let key = frame.key();
+                            frame.children.push((key, v));
+                        }
+                        p = next;
+                        state = State::AfterValue;
+                    } else if self.targets.iter().any(|t| t.selects_below(path())) {
+                        state = State::Value;
+                    } else {
+                        p = stry!(s.skip_value(p));
+                        state = State::AfterValue;
+                    }
+                }
+                State::AfterValue => match stack.last_mut() {
+                    None => {
+                        if p != s.len() {
+                            return Err(s.error(p, ErrorType::TrailingCharacters));
+                        }
+                        return Ok(result.unwrap_or_else(V::nothing));
+                    }
+                    Some(frame) => match s.char_at(p)? {
+                        b',' => {
+                            p += 1;
+                            if frame.object {
+                                state = State::Member;
+                            } else {
+                                if let Segment::Index(i) = &mut frame.segment {
+                                    *i += 1;
+                                }
+                                state = State::Element;
+                            }
+                        }
+                        b'}' if frame.object => state = State::End,
+                        b']' if !frame.object => state = State::End,
+                        _ if frame.object => {
+                            return Err(s.error(p, ErrorType::ExpectedObjectContent))
+                        }
+                        _ => return Err(s.error(p, ErrorType::ExpectedArrayContent)),
+                    },
+                },
+                State::End => {
+                    // `p` is at the end of the object or array on the top
+                    // of the stack
+                    p += 1;
+                    if let Some(frame) = stack.pop() {
+                        let v = if frame.object {
+                            V::from_members(frame.children)
+                        } else {
+                            V::from_elements(frame.children.into_iter().map(|(_, v)| v).collect())
+                        };
+                        match stack.last_mut() {
+                            Some(parent) => {
+                                if !v.is_empty_container() {
+                                    let key = parent.key();
+                                    parent.children.push((key, v));
+                                }
+                            }
+                            None => result = Some(v),
+                        }
+                    }
+                    state = State::AfterValue;
+                }
+            }
+        }
+    }
+}
+
+enum State {
+    Value,
+    Member,
+    Element,
+    AfterValue,
+    /// The end of an object or array
+    End,
+}
+
+struct Frame<'input, V> {
+    object: bool,
+    /// The key or index of the current member or element
+    segment: Segment<'input>,
+    /// The selected members or elements
+    children: Vec<(String, V)>,
+}
+
+impl<V> Frame<'_, V> {
+    fn key(&self) -> String {
+        match &self.segment {
+            Segment::Key(k) => k.to_string(),
+            Segment::Index(_) => String::new(),
+        }
+    }
+}
+
+/// Values a projection can be parsed into
+trait Projected<'de>: Sized {
+    fn parse(
+        s: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+    ) -> Result<Self>;
+    fn from_elements(elements: Vec<Self>) -> Self;
+    fn from_members(members: Vec<(String, Self)>) -> Self;
+    fn nothing() -> Self;
+    fn is_empty_container(&self) -> bool;
+}
+
+impl<'de> Projected<'de> for BorrowedValue<'de> {
+    fn parse(
+        s: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+    ) -> Result<Self> {
+        crate::to_borrowed_value_with_buffers(s, input_buffer, string_buffer)
+    }
+    fn from_elements(elements: Vec<Self>) -> Self {
+        Self::Array(elements)
+    }
+    fn from_members(members: Vec<(String, Self)>) -> Self {
+        let mut o = ObjectMap::with_capacity(members.len());
+        for (k, v) in members {
+            o.insert(k.into(), v);
+        }
+        Self::Object(Box::new(o))
+    }
+    fn nothing() -> Self {
+        Self::Static(StaticNode::Null)
+    }
+    fn is_empty_container(&self) -> bool {
+        match self {
+            Self::Array(a) => a.is_empty(),
+            Self::Object(o) => o.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+impl<'de> Projected<'de> for OwnedValue {
+    fn parse(
+        s: &'de mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+    ) -> Result<Self> {
+        crate::to_owned_value_with_buffers(s, input_buffer, string_buffer)
+    }
+    fn from_elements(elements: Vec<Self>) -> Self {
+        Self::Array(elements)
+    }
+    fn from_members(members: Vec<(String, Self)>) -> Self {
+        let mut o = ObjectMap::with_capacity(members.len());
+        for (k, v) in members {
+            o.insert(k, v);
+        }
+        Self::Object(Box::new(o))
+    }
+    fn nothing() -> Self {
+        Self::Static(StaticNode::Null)
+    }
+    fn is_empty_container(&self) -> bool {
+        match self {
+            Self::Array(a) => a.is_empty(),
+            Self::Object(o) => o.is_empty(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    const DOC: &str = r#"{
+        "id": 1,
+        "user": {"name": "sn\"ot", "email": "snot@badger.com", "tags": ["a", "b"]},
+        "items": [{"id": 2, "v": [1, 2, 3]}, {"x": 3}, {"id": 4}],
+        "big": [1.5, "not parsed", {"deep": [null]}]
+    }"#;
+
+    fn owned(p: &Projection, input: &str) -> Result<OwnedValue> {
+        let mut d = input.as_bytes().to_vec();
+        p.to_owned_value(&mut d)
+    }
+
+    #[test]
+    fn keys() {
+        let p = Projection::new().key("id");
+        assert_eq!(
+            owned(&p, DOC).unwrap(),
+            json!({"id": 1, "items": [{"id": 2}, {"id": 4}]})
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let p = Projection::new()
+            .pointer("/user/name")
+            .and_then(|p| p.pointer("/user/tags/1"))
+            .and_then(|p| p.pointer("/items/1"))
+            .unwrap();
+        assert_eq!(
+            owned(&p, DOC).unwrap(),
+            json!({"user": {"name": "sn\"ot", "tags": ["b"]}, "items": [{"x": 3}]})
+        );
+    }
+
+    #[test]
+    fn pointer_indexes() {
+        let p = Projection::new()
+            .pointer("/items/01")
+            .and_then(|p| p.pointer("/items/+1"))
+            .unwrap();
+        assert_eq!(owned(&p, DOC).unwrap(), json!({}));
+        let p = Projection::new()
+            .pointer("/items/0/v/0")
+            .and_then(|p| p.pointer("/items/10"))
+            .unwrap();
+        assert_eq!(owned(&p, DOC).unwrap(), json!({"items": [{"v": [1]}]}));
+    }
+
+    #[test]
+    fn whole_document() {
+        let p = Projection::new().pointer("").unwrap();
+        let mut d = DOC.as_bytes().to_vec();
+        let mut d2 = DOC.as_bytes().to_vec();
+        assert_eq!(
+            p.to_owned_value(&mut d).unwrap(),
+            crate::to_owned_value(&mut d2).unwrap()
+        );
+    }
+
+    #[test]
+    fn nothing() {
+        let p = Projection::new();
+        assert_eq!(owned(&p, DOC).unwrap(), json!({}));
+        assert_eq!(owned(&p, "[1, 2]").unwrap(), json!([]));
+        assert_eq!(owned(&p, "1").unwrap(), json!(null));
+        let p = Projection::new().key("nope");
+        assert_eq!(owned(&p, DOC).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn borrowed() {
+        let p = Projection::new().pointer("/user/name").unwrap().key("v");
+        let mut d = DOC.as_bytes().to_vec();
+        let v = p.to_borrowed_value(&mut d).unwrap();
+        assert_eq!(v["user"]["name"], "sn\"ot");
+        assert_eq!(v["items"][0]["v"][2], 3);
+    }
+
+    #[test]
+    fn invalid() {
+        let p = Projection::new().key("id");
+        // invalid inside the selection
+        assert!(owned(&p, r#"{"id": [1, }"#).is_err());
+        assert!(owned(&p, r#"{"id": tru}"#).is_err());
+        // invalid structure outside of it
+        assert!(owned(&p, r#"{"x": [1}, "id": 1}"#).is_err());
+        assert!(owned(&p, r#"{"x" 1}"#).is_err());
+        assert!(owned(&p, "[1] 2").is_err());
+    }
+
+    #[test]
+    fn error_offsets() {
+        let p = Projection::new().key("id");
+        let mut sub = b"[1, }".to_vec();
+        let sub_index = crate::to_owned_value(&mut sub).unwrap_err().index();
+        let e = owned(&p, r#"{"x": 1, "id": [1, }"#).unwrap_err();
+        assert_eq!(e.index(), 15 + sub_index);
+    }
+
+    #[test]
+    fn invalid_pointers() {
+        for pointer in &["user/name", "id", "/a~b"] {
+            let e = Projection::new().pointer(pointer).unwrap_err();
+            assert_eq!(
+                e,
+                Error::generic(ErrorType::InvalidPointer(pointer.to_string()))
+            );
+        }
+    }
+}
@@ -0,0 +1,481 @@
+//! Rewriting of raw JSON documents.
+//!
+//! The rewriter walks the structural indexes found by stage 1 and copies
+//! everything it isn't asked to touch verbatim, so keys keep their order
+//! and numbers, whitespace and escapes are not reformatted.
+
+use crate::path::{Segment, Structurals, Target};
+use crate::prelude::*;
+use crate::SIMDJSON_PADDING;
+use crate::{AlignedBuf, Deserializer, Error, ErrorType, OwnedValue, Result, StructuralIndex};
+use std::io::Write;
+
+macro_rules! iomap {
+    ($e:expr) => {
+        ($e).map_err(|err| Error::generic(ErrorType::Io(err)))
+    };
+}
+
+#[derive(Debug, Clone)]
+enum Action {
+    Remove,
+    /// Replaces the value with this (encoded) JSON
+    Replace(Vec<u8>),
+}
+
+struct Frame<'input> {
+    object: bool,
+    /// Set once one of the members or elements was written to the output
+    kept: bool,
+    /// The key or index of the current member or element
+    segment: Segment<'input>,
+}
+
+enum State {
+    Value,
+    Member,
+    Element,
+    AfterValue,
+}
+
+/// Removes or replaces values in raw JSON while copying everything else
+/// verbatim.
+///
+/// Values are selected either by key, matching object members with that
+/// key at any depth, or by [JSON pointer](https://tools.ietf.org/html/rfc6901)
+/// matching the value at exactly that path. The root of the document
+/// can't be rewritten. Removing an element from an array shifts the
+/// indexes of the following elements for the output, pointers always
+/// refer to the indexes in the input.
+///
+/// The structure of the input is validated while it is rewritten, but
+/// values that are copied verbatim or removed are not parsed.
+///
+/// ```
+/// use simd_json::Rewriter;
+///
+/// let rewriter = Rewriter::new()
+///     .remove_key("password")
+///     .replace_pointer("/user/email", "<redacted>")
+///     .unwrap();
+/// let input = br#"{"user": {"name": "snot", "email": "snot@badger.com", "password": "1234"}, "n": 1.0e3}"#;
+/// let out = rewriter.rewrite_to_vec(input).unwrap();
+/// assert_eq!(
+///     out,
+///     br#"{"user": {"name": "snot", "email": "<redacted>"}, "n": 1.0e3}"#.to_vec()
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Rewriter {
+    rules: Vec<(Target, Action)>,
+}
+
+impl Rewriter {
+    /// Creates a rewriter that leaves the input untouched
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes all object members with the key `key`
+    #[must_use]
+    pub fn remove_key<K: Into<String>>(mut self, key: K) -> Self {
+        self.rules.push((Target::Key(key.into()), Action::Remove));
+        self
+    }
+
+    /// Replaces the values of all object members with the key `key`
+    #[must_use]
+    pub fn replace_key<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<OwnedValue>,
+    {
+        let value = value.into().encode().into_bytes();
+        self.rules
+            .push((Target::Key(key.into()), Action::Replace(value)));
+        self
+    }
+
+    /// Removes the value at `pointer`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `pointer` is not a valid JSON pointer, like
+    /// `user/email` without the leading `/`.
+    pub fn remove_pointer(mut self, pointer: &str) -> Result<Self> {
+        self.rules.push((Target::pointer(pointer)?, Action::Remove));
+        Ok(self)
+    }
+
+    /// Replaces the value at `pointer`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `pointer` is not a valid JSON pointer.
+    pub fn replace_pointer<V>(mut self, pointer: &str, value: V) -> Result<Self>
+    where
+        V: Into<OwnedValue>,
+    {
+        let target = Target::pointer(pointer)?;
+        let value = value.into().encode().into_bytes();
+        self.rules.push((target, Action::Replace(value)));
+        Ok(self)
+    }
+
+    /// Rewrites `input` into a new vector
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` is not well formed JSON.
+    pub fn rewrite_to_vec(&self, input: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(input.len());
+        self.rewrite(input, &mut out)?;
+        Ok(out)
+    }
+
+    /// Rewrites `input` into `writer`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `input` is not well formed JSON or if
+    /// writing fails. Parts of the output might have been written
+    /// when an error is returned.
+    pub fn rewrite<W: Write>(&self, input: &[u8], writer: &mut W) -> Result<()> {
+        let mut input_buffer = AlignedBuf::with_capacity(input.len() + SIMDJSON_PADDING * 2);
+        input_buffer.load(input);
+        if input.len() > u32::MAX as usize {
+            self.rewrite_with_indexes::<u64, W>(input, &input_buffer, writer)
+        } else {
+            self.rewrite_with_indexes::<u32, W>(input, &input_buffer, writer)
+        }
+    }
+
+    fn rewrite_with_indexes<I: StructuralIndex, W: Write>(
+        &self,
+        input: &[u8],
+        input_buffer: &[u8],
+        writer: &mut W,
+    ) -> Result<()> {
+        let structural_indexes: Vec<I> =
+            match unsafe { Deserializer::find_structural_bits(input_buffer) } {
+                Ok(i) => i,
+                Err(t) => return Err(Error::generic(t)),
+            };
+        Walk {
+            s: Structurals {
+                input,
+                idx: &structural_indexes,
+            },
+            rules: &self.rules,
+            writer,
+            written: 0,
+        }
+        .run()
+    }
+}
+
+struct Walk<'input, 'rules, 'w, I, W> {
+    s: Structurals<'input, I>,
+    rules: &'rules [(Target, Action)],
+    writer: &'w mut W,
+    /// Bytes of the input that were already written or skipped
+    written: usize,
+}
+
+impl<'rules, I, W> Walk<'_, 'rules, '_, I, W>
+where
+    I: StructuralIndex,
+    W: Write,
+{
+    /// Writes the input up to `start`, followed by `replacement` and
+    /// skips the input up to `end`
+    fn edit(&mut self, start: usize, end: usize, replacement: Option<&[u8]>) -> Result<()> {
+        stry!(iomap!(self
+            .writer
+            .write_all(&self.s.input[self.written..start])));
+        if let Some(r) = replacement {
+            stry!(iomap!(self.writer.write_all(r)));
+        }
+        self.written = end;
+        Ok(())
+    }
+
+    /// Finds the action for the current path, `stack` is the path to the
+    /// value, the last segment of it is the key or index in its parent
+    fn action(&self, stack: &[Frame]) -> Option<&'rules Action> {
+        self.rules
+            .iter()
+            .find(|(target, _)| target.matches(stack.iter().map(|f| &f.segment)))
+            .map(|(_, action)| action)
+    }
+
+    /// Applies `action` to the member or element whose value starts at
+    /// `p`, `first` is the index of its key (or of the value in arrays).
+    /// Returns the index after the value.
+    fn apply(&mut self, action: &Action, kept: bool, first: usize, p: usize) -> Result<usize> {
+        let next = self.s.skip_value(p)?;
+        match action {
+            Action::Replace(value) => {
+                let end = self.s.value_end(p, next);
+                stry!(self.edit(self.s.at(p), end, Some(value)));
+            }
+            // A previous sibling was kept so we remove the comma in front
+            // of the member
+            Action::Remove if kept => {
+                stry!(self.edit(self.s.at(first - 1), self.s.at(next), None));
+            }
+            // Otherwise the one after it, if there is one, along with the
+            // whitespace up to the next sibling
+            Action::Remove => {
+                let end = if self.s.char_at(next)? == b',' {
+                    self.s.at(next + 1)
+                } else {
+                    self.s.at(next)
+                };
+                stry!(self.edit(self.s.at(first), end, None));
+            }
+        }
+        Ok(next)
+    }
+
+    fn run(mut self) -> Result<()> {
+        let mut stack: Vec<Frame> = Vec::new();
+        // skip the root index
+        let mut p = 1;
+        let mut state = State::Value;
+        loop {
+            match state {
+                State::Value => match self.s.char_at(p)? {
+                    b'{' => {
+                        p += 1;
+                        if self.s.char_at(p)? == b'}' {
+                            p += 1;
+                            state = State::AfterValue;
+                        } else {
+                            stack.push(Frame {
+                                object: true,
+                                kept: false,
+                                segment: Segment::Index(0),
+                            });
+                            state = State::Member;
+                        }
+                    }
+                    b'[' => {
+                        p += 1;
+                        if self.s.char_at(p)? == b']' {
+                            p += 1;
+                            state = State::AfterValue;
+                        } else {
+                            stack.push(Frame {
+                                object: false,
+                                kept: false,
+                                segment: Segment::Index(0),
+                            });
+                            state = State::Element;
+                        }
+                    }
+                    b'"' | b't' | b'f' | b'n' | b'-' | b'0'..=b'9' => {
+                        p += 1;
+                        state = State::AfterValue;
+                    }
+                    _ => return Err(self.s.error(p, ErrorType::UnexpectedCharacter)),
+                },
+                State::Member => {
+                    if self.s.char_at(p)? != b'"' {
+                        return Err(self.s.error(p, ErrorType::ExpectedObjectKey));
+                    }
+                    let first = p;
+                    let key = stry!(self.s.key(p));
+                    if self.s.char_at(p + 1)? != b':' {
+                        return Err(self.s.error(p + 1, ErrorType::ExpectedObjectColon));
+                    }
+                    p += 2;
+                    let frame = stack
+                        .last_mut()
+                        .ok_or_else(|| self.s.error(p, ErrorType::InternalError))?;
+                    frame.segment = Segment::Key(key);
+                    state = self.visit(&mut stack, first, &mut p)?;
+                }
+                State::Element => {
+                    state = self.visit(&mut stack, p, &mut p)?;
+                }
+                State::AfterValue => match stack.last_mut() {
+                    None => {
+                        if p != self.s.len() {
+                            return Err(self.s.error(p, ErrorType::TrailingCharacters));
+                        }
+                        let rest = &self.s.input[self.written..];
+                        return iomap!(self.writer.write_all(rest));
+                    }
+                    Some(frame) => match self.s.char_at(p)? {
+                        b',' => {
+                            p += 1;
+                            if frame.object {
+                                state = State::Member;
+                            } else {
+                                if let Segment::Index(i) = &mut frame.segment {
+                                    *i += 1;
+                                }
+                                state = State::Element;
+                            }
+                        }
+                        b'}' if frame.object => {
+                            p += 1;
+                            stack.pop();
+                        }
+                        b']' if !frame.object => {
+                            p += 1;
+                            stack.pop();
+                        }
+                        _ if frame.object => {
+                            return Err(self.s.error(p, ErrorType::ExpectedObjectContent))
+                        }
+                        _ => return Err(self.s.error(p, ErrorType::ExpectedArrayContent)),
+                    },
+                },
+            }
+        }
+    }
+
+    /// Visits the member or element at the top of `stack` whose value
+    /// starts at `p`, `first` is the index of its key in objects
+    fn visit(&mut self, stack: &mut [Frame], first: usize, p: &mut usize) -> Result<State> {
+        let action = self.action(stack);
+        let frame = stack
+            .last_mut()
+            .ok_or_else(|| self.s.error(*p, ErrorType::InternalError))?;
+        if let Some(action) = action {
+            *p = stry!(self.apply(action, frame.kept, first, *p));
+            if let Action::Replace(_) = action {
+                frame.kept = true;
+            }
+            Ok(State::AfterValue)
+        } else {
+            frame.kept = true;
+            Ok(State::Value)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+
+    fn rw(r: &Rewriter, input: &str) -> String {
+        String::from_utf8(r.rewrite_to_vec(input.as_bytes()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn untouched() {
+        let r = Rewriter::new().remove_key("nope");
+        let input = r#" { "a" : [1.0e3, -0, "é"] ,"b":{ } , "c" : [ ] } "#;
+        assert_eq!(rw(&r, input), input);
+        assert_eq!(rw(&r, "1"), "1");
+        assert_eq!(rw(&r, r#""a""#), r#""a""#);
+    }
+
+    #[test]
+    fn remove_keys() {
+        let input = r#"{"a": 1, "b": 2, "c": 3}"#;
+        assert_eq!(
+            rw(&Rewriter::new().remove_key("a"), input),
+            r#"{"b": 2, "c": 3}"#
+        );
+        assert_eq!(
+            rw(&Rewriter::new().remove_key("b"), input),
+            r#"{"a": 1, "c": 3}"#
+        );
+        assert_eq!(
+            rw(&Rewriter::new().remove_key("c"), input),
+            r#"{"a": 1, "b": 2}"#
+        );
+        let r = Rewriter::new().remove_key("a").remove_key("b");
+        assert_eq!(rw(&r, input), r#"{"c": 3}"#);
+        let r = Rewriter::new().remove_key("b").remove_key("c");
+        assert_eq!(rw(&r, input), r#"{"a": 1}"#);
+        let r = Rewriter::new().remove_key("a").remove_key("c");
+        assert_eq!(rw(&r, input), r#"{"b": 2}"#);
+        let r = r.remove_key("b");
+        assert_eq!(rw(&r, input), "{}");
+    }
+
+    #[test]
+    fn remove_nested() {
+        let r = Rewriter::new().remove_key("password");
+        let input = r#"[{"password": {"x": [1, {"password": 2}]}}, {"user": "snot", "password": "badger"}]"#;
+        assert_eq!(rw(&r, input), r#"[{}, {"user": "snot"}]"#);
+    }
+
+    #[test]
+    fn replace() {
+        let r = Rewriter::new().replace_key("password", "***");
+        let input = r#"{"password" : "hunter2" , "other": {"password": [1, 2]}}"#;
+        assert_eq!(
+            rw(&r, input),
+            r#"{"password" : "***" , "other": {"password": "***"}}"#
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let r = Rewriter::new()
+            .remove_pointer("/user/email")
+            .and_then(|r| r.replace_pointer("/list/1", 42))
+            .and_then(|r| r.remove_pointer("/a~1b/c~0d"))
+            .unwrap();
+        let input = r#"{"email": 1, "user": {"email": "x@y.z", "name": "snot"}, "list": [1, 2, 3], "a/b": {"c~d": 1, "e": 2}}"#;
+        assert_eq!(
+            rw(&r, input),
+            r#"{"email": 1, "user": {"name": "snot"}, "list": [1, 42, 3], "a/b": {"e": 2}}"#
+        );
+    }
+
+    #[test]
+    fn invalid_pointers() {
+        // a pointer without the leading slash must not select `/email`
+        for pointer in &["user/email", "password", "/a~2b", "/a~"] {
+            let e = Rewriter::new().remove_pointer(pointer).unwrap_err();
+            assert_eq!(
+                e,
+                Error::generic(ErrorType::InvalidPointer(pointer.to_string()))
+            );
+            assert!(Rewriter::new().replace_pointer(pointer, 1).is_err());
+        }
+        let r = Rewriter::new().remove_pointer("").unwrap();
+        assert_eq!(rw(&r, "[1]"), "[1]");
+    }
+
+    #[test]
+    fn array_elements() {
+        let input = "[1, 2, 3]";
+        let r = Rewriter::new().remove_pointer("/0").unwrap();
+        assert_eq!(rw(&r, input), "[2, 3]");
+        let r = Rewriter::new().remove_pointer("/2").unwrap();
+        assert_eq!(rw(&r, input), "[1, 2]");
+        let r = Rewriter::new()
+            .remove_pointer("/0")
+            .and_then(|r| r.remove_pointer("/1"))
+            .unwrap();
+        assert_eq!(rw(&r, input), "[3]");
+    }
+
+    #[test]
+    fn escaped_keys() {
+        let r = Rewriter::new().remove_key("pass\"word");
+        assert_eq!(rw(&r, r#"{"pass\"word": 1, "a": 2}"#), r#"{"a": 2}"#);
+        let r = Rewriter::new().remove_key("é");
+        assert_eq!(rw(&r, r#"{"é": 1, "a": 2}"#), r#"{"a": 2}"#);
+    }
+
+    #[test]
+    fn invalid() {
+        let r = Rewriter::new().remove_key("a");
+        assert!(r.rewrite_to_vec(b"{\"a\": 1").is_err());
+        assert!(r.rewrite_to_vec(b"{\"a\": [1}, \"b\": 2}").is_err());
+        assert!(r.rewrite_to_vec(b"{\"b\" 1}").is_err());
+        assert!(r.rewrite_to_vec(b"[1] 2").is_err());
+        assert!(r.rewrite_to_vec(b"").is_err());
+    }
+}
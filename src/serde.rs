@@ -7,9 +7,20 @@
 /// directly to structs this is th4 place to go.
 ///
 mod de;
+mod lenient;
+mod raw;
 mod se;
+mod stream;
+mod tagged;
 mod value;
-pub use self::se::*;
+pub use self::lenient::{Coercion, Lenient};
+pub use self::raw::RawValue;
+pub use self::se::{
+    to_canonical_string, to_canonical_vec, to_string, to_string_pretty, to_vec, to_vec_pretty,
+    to_writer, to_writer_pretty, PrettySerializer, Serializer,
+};
+pub use self::stream::StreamDeserializer;
+pub use self::tagged::{tagged, Tagged, TaggedEnum};
 pub use self::value::*;
 use crate::{stry, Deserializer, Error, ErrorType, Result};
 use crate::{BorrowedValue, OwnedValue};
@@ -62,6 +73,51 @@ where
     let mut deserializer = stry!(Deserializer::from_slice(s));
     T::deserialize(&mut deserializer)
 }
+/// parses a byte slice using a serde deserializer that keeps the input
+/// and its structural indexes around while `T` is deserialized, which
+/// [`RawValue`] fields need to find their text. It costs a copy of the
+/// input and the indexes, so [`from_slice`] is the better choice for
+/// types without raw values.
+/// note that the slice will be rewritten in the process.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub fn from_slice_with_raw<'a, T>(s: &'a mut [u8]) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = stry!(Deserializer::from_slice_with_source(s));
+    T::deserialize(&mut deserializer)
+}
+/// parses a byte slice into an existing value using a serde deserializer.
+/// Values that implement `deserialize_in_place`, such as `String`, `Vec`,
+/// `OwnedValue` or structs derived with serde's `deserialize_in_place`
+/// feature, keep their allocations where the shape of the data matches.
+/// note that the slice will be rewritten in the process.
+///
+/// ```
+/// let mut names: Vec<String> = Vec::new();
+/// for i in 0..3 {
+///     let mut d = format!(r#"["a{}", "b{}"]"#, i, i).into_bytes();
+///     simd_json::serde::from_slice_in_place(&mut d, &mut names).unwrap();
+///     assert_eq!(names, vec![format!("a{}", i), format!("b{}", i)]);
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, `place` may be partially
+/// updated in that case.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub fn from_slice_in_place<'a, T>(s: &'a mut [u8], place: &mut T) -> Result<()>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = stry!(Deserializer::from_slice(s));
+    T::deserialize_in_place(&mut deserializer, place)
+}
 /// parses a str  using a serde deserializer.
 /// note that the slice will be rewritten in the process and
 /// might not remain a valid utf8 string in its entirety.
@@ -130,6 +186,17 @@ impl<'de> Deserializer<'de> {
             .ok_or_else(|| Self::error(ErrorType::UnexpectedEnd))
     }
 
+    /// Adds the key at tape index `key` to the path of an error that
+    /// happened in its value.
+    #[cold]
+    fn key_error(&self, key: usize, e: Error) -> Error {
+        if let Some(Node::String(k)) = self.tape.get(key) {
+            e.in_key(k)
+        } else {
+            e
+        }
+    }
+
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     #[allow(clippy::cast_sign_loss)]
     fn parse_u8(&mut self) -> Result<u8> {
@@ -576,4 +643,251 @@ mod test {
         assert_eq!(p.x, 1);
         assert_eq!(p.y, 2);
     }
+
+    #[test]
+    fn skip_unknown_fields() {
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        struct Point {
+            x: u64,
+            y: u64,
+        }
+
+        let mut json =
+            br#"{"skipped":{"a":[1,{"b":[]},"c"],"d":{}},"x":1,"nope":[[],[2]],"y":2,"z":null}"#
+                .to_vec();
+        let p: Point = crate::from_slice(&mut json).unwrap();
+        assert_eq!(p, Point { x: 1, y: 2 });
+
+        let mut json = br#"[{"x":1,"y":2,"extra":[1,2,3]},{"extra":{"x":0},"y":4,"x":3}]"#.to_vec();
+        let ps: Vec<Point> = crate::from_slice(&mut json).unwrap();
+        assert_eq!(ps, vec![Point { x: 1, y: 2 }, Point { x: 3, y: 4 }]);
+    }
+
+    #[test]
+    fn ignored_any() {
+        use serde_ext::de::IgnoredAny;
+        let mut json = br#"[{"a":[1,2,{"b":3}]},[],"snot",4]"#.to_vec();
+        let v: (IgnoredAny, IgnoredAny, String, u8) = crate::from_slice(&mut json).unwrap();
+        assert_eq!(v.2, "snot");
+        assert_eq!(v.3, 4);
+    }
+
+    #[test]
+    fn tagged_enum() {
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        #[serde(deny_unknown_fields)]
+        struct Inner {
+            a: Vec<u8>,
+        }
+
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        #[serde(tag = "type")]
+        enum Message {
+            Request { id: usize, method: String },
+            Response { id: String, result: Inner },
+            Wrapped(Inner),
+            Unit,
+        }
+
+        let mut json = br#"[
+            {"id": 1, "type": "Request", "params": {"type": "Unit"}, "method": "snot"},
+            {"type": "Response", "id": "1", "result": {"a": [1, 2]}},
+            {"a": [3], "type": "Wrapped"},
+            {"type": "Unit", "data": [{}]}
+        ]"#
+        .to_vec();
+        let ms: Vec<Message> = crate::from_slice(&mut json).unwrap();
+        assert_eq!(
+            ms,
+            vec![
+                Message::Request {
+                    id: 1,
+                    method: "snot".to_string()
+                },
+                Message::Response {
+                    id: "1".to_string(),
+                    result: Inner { a: vec![1, 2] }
+                },
+                Message::Wrapped(Inner { a: vec![3] }),
+                Message::Unit,
+            ]
+        );
+
+        let mut json = br#"{"id": 1, "method": "snot"}"#.to_vec();
+        assert!(crate::from_slice::<Message>(&mut json).is_err());
+        let mut json = br#"{"type": "Nope"}"#.to_vec();
+        assert!(crate::from_slice::<Message>(&mut json).is_err());
+        let mut json = br#"{"type": 1}"#.to_vec();
+        assert!(crate::from_slice::<Message>(&mut json).is_err());
+    }
+
+    #[test]
+    fn adjacently_tagged_enum() {
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        #[serde(tag = "t", content = "c")]
+        enum Message {
+            Request { id: usize, method: String },
+            Point(u8, u8),
+            Wrapped(String),
+            Unit,
+        }
+
+        let mut json = br#"[
+            {"c": {"method": "snot", "id": 1}, "t": "Request"},
+            {"t": "Point", "c": [1, 2]},
+            {"t": "Wrapped", "x": [], "c": "badger"},
+            {"t": "Unit"}
+        ]"#
+        .to_vec();
+        let ms: Vec<Message> = crate::from_slice(&mut json).unwrap();
+        assert_eq!(
+            ms,
+            vec![
+                Message::Request {
+                    id: 1,
+                    method: "snot".to_string()
+                },
+                Message::Point(1, 2),
+                Message::Wrapped("badger".to_string()),
+                Message::Unit,
+            ]
+        );
+
+        let mut json = br#"{"t": "Point"}"#.to_vec();
+        assert!(crate::from_slice::<Message>(&mut json).is_err());
+
+        let mut json = br#"{"t": "Point", "c": [1, "2"]}"#.to_vec();
+        let e = crate::from_slice::<Message>(&mut json).unwrap_err();
+        assert_eq!(e.path(), ".c[1]");
+    }
+
+    #[test]
+    fn flattened() {
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        struct Meta {
+            id: u64,
+            tags: Vec<String>,
+        }
+
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        #[serde(tag = "type")]
+        enum Event {
+            Click {
+                #[serde(flatten)]
+                meta: Meta,
+                x: i8,
+            },
+        }
+
+        let mut json = br#"{"x": -1, "tags": ["a"], "type": "Click", "id": 7}"#.to_vec();
+        assert_eq!(
+            crate::from_slice::<Event>(&mut json).unwrap(),
+            Event::Click {
+                meta: Meta {
+                    id: 7,
+                    tags: vec!["a".to_string()]
+                },
+                x: -1
+            }
+        );
+    }
+
+    #[test]
+    fn error_path() {
+        use serde_ext::Deserialize;
+        #[derive(serde_ext::Deserialize, Debug)]
+        struct Server {
+            #[allow(dead_code)]
+            host: String,
+        }
+        #[derive(serde_ext::Deserialize, Debug)]
+        struct Config {
+            #[allow(dead_code)]
+            servers: Vec<Server>,
+        }
+
+        let json = r#"{"servers": [{"host": "a"}, {"host": "b"}, {"host": 3}]}"#;
+        let e = crate::from_slice::<Config>(&mut json.as_bytes().to_vec()).unwrap_err();
+        assert_eq!(e.path(), ".servers[2].host");
+        assert_eq!(
+            e.to_string(),
+            "ExpectedString at .servers[2].host, character 0 ('?')"
+        );
+
+        let v: OwnedValue = crate::from_slice(&mut json.as_bytes().to_vec()).unwrap();
+        let e = Config::deserialize(&v).unwrap_err();
+        assert_eq!(e.path(), ".servers[2].host");
+        let e = super::from_owned_value::<Config>(v).unwrap_err();
+        assert_eq!(e.path(), ".servers[2].host");
+
+        let mut d = json.as_bytes().to_vec();
+        let v: BorrowedValue = crate::to_borrowed_value(&mut d).unwrap();
+        let e = Config::deserialize(&v).unwrap_err();
+        assert_eq!(e.path(), ".servers[2].host");
+        let e = super::from_borrowed_value::<Config>(v).unwrap_err();
+        assert_eq!(e.path(), ".servers[2].host");
+
+        // keys that aren't plain names are quoted, custom errors get a path too
+        let mut json = br#"{"a b": [true, {"c": [1, 300]}]}"#.to_vec();
+        let e = crate::from_slice::<std::collections::HashMap<String, (bool, Config)>>(&mut json)
+            .unwrap_err();
+        assert_eq!(e.path(), r#"["a b"][1]"#);
+        let mut json = br#"{"x": [1, 300]}"#.to_vec();
+        let e =
+            crate::from_slice::<std::collections::HashMap<String, Vec<u8>>>(&mut json).unwrap_err();
+        assert_eq!(e.path(), ".x[1]");
+
+        let mut json = b"[1, 2]".to_vec();
+        let e = crate::from_slice::<String>(&mut json).unwrap_err();
+        assert_eq!(e.path(), "");
+    }
+
+    #[test]
+    fn in_place() {
+        use value_trait::ValueAccess;
+        #[derive(serde_ext::Deserialize, Debug, PartialEq)]
+        struct Message {
+            id: u64,
+            tags: Vec<String>,
+            body: OwnedValue,
+        }
+
+        let mut m = Message {
+            id: 0,
+            tags: Vec::new(),
+            body: OwnedValue::default(),
+        };
+        let mut d =
+            br#"{"id": 1, "tags": ["xyz", "b"], "body": {"k": "vvv", "a": [1, 2]}}"#.to_vec();
+        super::from_slice_in_place(&mut d, &mut m).unwrap();
+        assert_eq!(m.id, 1);
+        assert_eq!(m.tags, vec!["xyz", "b"]);
+        assert_eq!(m.body, json!({"k": "vvv", "a": [1, 2]}));
+        let tags = m.tags.as_ptr();
+        let tag = m.tags[0].as_ptr();
+        let k = m.body["k"].as_str().unwrap().as_ptr();
+        let a = m.body["a"].as_array().unwrap().as_ptr();
+
+        let mut d =
+            br#"{"id": 2, "tags": ["abc"], "body": {"a": [3], "k": "w", "n": null}}"#.to_vec();
+        super::from_slice_in_place(&mut d, &mut m).unwrap();
+        assert_eq!(
+            m,
+            Message {
+                id: 2,
+                tags: vec!["abc".to_string()],
+                body: json!({"k": "w", "a": [3], "n": null}),
+            }
+        );
+        assert_eq!(m.tags.as_ptr(), tags);
+        assert_eq!(m.tags[0].as_ptr(), tag);
+        assert_eq!(m.body["k"].as_str().unwrap().as_ptr(), k);
+        assert_eq!(m.body["a"].as_array().unwrap().as_ptr(), a);
+
+        // a different shape replaces the old value
+        let mut d = br#"{"id": 3, "tags": [], "body": ["k"]}"#.to_vec();
+        super::from_slice_in_place(&mut d, &mut m).unwrap();
+        assert!(m.tags.is_empty());
+        assert_eq!(m.body, json!(["k"]));
+    }
 }
@@ -1,7 +1,10 @@
+use super::raw::TOKEN;
+use super::tagged::TOKEN as TAGGED;
 use crate::serde_ext::de::IntoDeserializer;
 use crate::{serde_ext, stry, Deserializer, Error, ErrorType, Node, Result, StaticNode};
 use serde_ext::de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor};
 use serde_ext::forward_to_deserialize_any;
+use std::borrow::Cow;
 use std::str;
 
 impl<'a, 'de> de::Deserializer<'de> for &'a mut Deserializer<'de>
@@ -73,6 +76,8 @@ where
         }
     }
 
+    // We hand out a `&str` rather than a `String`, so `deserialize_in_place`
+    // can copy it into the capacity of a string it already has.
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value>
     where
@@ -284,10 +289,16 @@ where
     // As is done here, serializers are encouraged to treat newtype structs as
     // insignificant wrappers around the data they contain. That means not
     // parsing anything other than the contained value.
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == TOKEN {
+            return match stry!(self.next_raw()) {
+                Cow::Borrowed(json) => visitor.visit_borrowed_str(json),
+                Cow::Owned(json) => visitor.visit_string(json),
+            };
+        }
         visitor.visit_newtype_struct(self)
     }
 
@@ -308,13 +319,16 @@ where
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn deserialize_struct<V>(
         mut self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == TAGGED {
+            return self.deserialize_tagged(fields, visitor);
+        }
         match self.next() {
             // Give the visitor access to each element of the sequence.
             Ok(Node::Object(len, _)) => {
@@ -348,24 +362,40 @@ where
         }
     }
 
+    // Values that are ignored (like unknown fields of a struct) don't need to
+    // be walked through a visitor. Arrays and objects know where they end on
+    // the tape so we can jump the cursor past the whole subtree at once.
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        stry!(self.skip_value());
+        visitor.visit_unit()
+    }
+
     forward_to_deserialize_any! {
             char
             bytes byte_buf
-            identifier ignored_any
+            identifier
     }
 }
 
 // In order to handle commas correctly when deserializing a JSON array or map,
 // we need to track whether we are on the first element or past the first
 // element.
+//
+// We also remember where we are, the index of the element or the tape index
+// of the key, so errors can tell which value they came from.
 struct CommaSeparated<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
     len: usize,
+    pos: usize,
 }
 impl<'a, 'de> CommaSeparated<'a, 'de> {
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn new(de: &'a mut Deserializer<'de>, len: usize) -> Self {
-        CommaSeparated { de, len }
+        CommaSeparated { de, len, pos: 0 }
     }
 }
 
@@ -383,9 +413,15 @@ impl<'de, 'a> SeqAccess<'de> for CommaSeparated<'a, 'de> {
             Ok(None)
         } else {
             self.len -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+            let index = self.pos;
+            self.pos += 1;
+            seed.deserialize(&mut *self.de)
+                .map(Some)
+                .map_err(|e| e.in_element(index))
         }
     }
+    // The tape knows the exact length, in place deserialization of a `Vec`
+    // uses it to reserve the missing elements at once.
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn size_hint(&self) -> Option<usize> {
         Some(self.len)
@@ -406,6 +442,7 @@ impl<'de, 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
             Ok(None)
         } else {
             self.len -= 1;
+            self.pos = self.de.idx + 1;
             seed.deserialize(&mut *self.de).map(Some)
         }
     }
@@ -416,7 +453,9 @@ impl<'de, 'a> MapAccess<'de> for CommaSeparated<'a, 'de> {
         V: DeserializeSeed<'de>,
     {
         // read the value
+        let key = self.pos;
         seed.deserialize(&mut *self.de)
+            .map_err(|e| self.de.key_error(key, e))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -0,0 +1,1137 @@
+use crate::error::key_segment;
+use crate::serde_ext::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use crate::{stry, BorrowedValue, Deserializer, OwnedValue, Result};
+use serde_ext::de::value::{BorrowedStrDeserializer, MapAccessDeserializer};
+use serde_ext::Deserialize;
+use std::fmt;
+
+/// A type coercion done by a [`Lenient`] deserializer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coercion {
+    /// A number was read from a string, like `"42"`
+    StringToNumber,
+    /// A boolean was read from the string `"true"` or `"false"`
+    StringToBool,
+    /// A boolean was read from the number `0` or `1`
+    NumberToBool,
+    /// A single value was read as an array with one element
+    ValueToArray,
+}
+
+/// Deserializes with serde while coercing values that don't quite have
+/// the expected type.
+///
+/// This is opt-in, `simd_json::serde::from_slice` stays strict. The
+/// coercions are:
+///
+/// * numbers are read from strings that hold a JSON number, like `"42"`
+/// * booleans are read from `"true"`, `"false"`, `0` and `1`
+/// * a single value is read as an array with one element where a sequence
+///   is expected
+///
+/// Every coercion is reported to the diagnostics callback together with
+/// the path of the value, like `.servers[2].port`.
+///
+/// Internally tagged enums and `#[serde(flatten)]` fields are buffered
+/// by serde before they reach their type, values in them are read
+/// strictly.
+///
+/// ```
+/// use serde::Deserialize;
+/// use simd_json::serde::{Coercion, Lenient};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Server {
+///     port: u16,
+///     tls: bool,
+///     tags: Vec<String>,
+/// }
+///
+/// let mut d = br#"{"port": "8080", "tls": 1, "tags": "prod"}"#.to_vec();
+/// let mut seen = Vec::new();
+/// let server: Server = Lenient::new()
+///     .with_diagnostics(|path: &str, c| seen.push((path.to_string(), c)))
+///     .from_slice(&mut d)
+///     .unwrap();
+/// assert_eq!(
+///     server,
+///     Server {
+///         port: 8080,
+///         tls: true,
+///         tags: vec!["prod".to_string()]
+///     }
+/// );
+/// assert_eq!(
+///     seen,
+///     vec![
+///         (".port".to_string(), Coercion::StringToNumber),
+///         (".tls".to_string(), Coercion::NumberToBool),
+///         (".tags".to_string(), Coercion::ValueToArray),
+///     ]
+/// );
+/// ```
+pub struct Lenient<F = fn(&str, Coercion)> {
+    diagnostics: F,
+}
+
+fn ignore(_path: &str, _coercion: Coercion) {}
+
+impl Lenient {
+    /// Creates a lenient deserializer that doesn't report coercions
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            diagnostics: ignore,
+        }
+    }
+}
+
+impl Default for Lenient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Lenient<F>
+where
+    F: FnMut(&str, Coercion),
+{
+    /// Reports every coercion to `diagnostics` with the path of the value
+    /// it was done on.
+    #[must_use]
+    pub fn with_diagnostics<G>(self, diagnostics: G) -> Lenient<G>
+    where
+        G: FnMut(&str, Coercion),
+    {
+        Lenient { diagnostics }
+    }
+
+    /// Parses a byte slice, the slice will be rewritten in the process.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON or doesn't match `T` even
+    /// with coercions.
+    pub fn from_slice<'de, T>(&mut self, s: &'de mut [u8]) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        let mut deserializer = stry!(Deserializer::from_slice(s));
+        self.deserialize(&mut deserializer)
+    }
+
+    /// Converts an `OwnedValue`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` doesn't match `T` even with coercions.
+    pub fn from_owned_value<T>(&mut self, value: OwnedValue) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.deserialize(value)
+    }
+
+    /// Converts a `BorrowedValue`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `value` doesn't match `T` even with coercions.
+    pub fn from_borrowed_value<'de, T>(&mut self, value: BorrowedValue<'de>) -> Result<T>
+    where
+        T: Deserialize<'de>,
+    {
+        self.deserialize(value)
+    }
+
+    /// Deserializes from any serde deserializer, like a reference to a
+    /// value.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the data doesn't match `T` even with coercions.
+    pub fn deserialize<'de, T, D>(&mut self, deserializer: D) -> std::result::Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: de::Deserializer<'de>,
+    {
+        let mut state = State {
+            diagnostics: &mut self.diagnostics,
+            path: String::new(),
+            key: String::new(),
+        };
+        T::deserialize(LenientDeserializer {
+            de: deserializer,
+            state: &mut state,
+            key: false,
+        })
+    }
+}
+
+/// Shared by all the wrappers of one deserialization
+struct State<'f> {
+    diagnostics: &'f mut dyn FnMut(&str, Coercion),
+    /// Path of the current value
+    path: String,
+    /// The last key that was read
+    key: String,
+}
+
+impl<'f> State<'f> {
+    fn report(&mut self, coercion: Coercion) {
+        (self.diagnostics)(&self.path, coercion);
+    }
+}
+
+/// What the `Deserialize` implementation asked for, this decides which
+/// coercions apply.
+#[derive(Clone, Copy, PartialEq)]
+enum Want {
+    Any,
+    Bool,
+    Integer,
+    Float,
+    Seq,
+    /// A map key, it is remembered for the path
+    Key,
+}
+
+/// A scalar read from a string
+#[derive(Clone, Copy)]
+enum Scalar {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
+/// Reads the scalar `want` from `s` if it holds one.
+fn parse(want: Want, s: &str) -> Option<Scalar> {
+    match want {
+        Want::Bool => match s {
+            "true" => Some(Scalar::Bool(true)),
+            "false" => Some(Scalar::Bool(false)),
+            _ => None,
+        },
+        Want::Integer if is_number(s) => s
+            .parse()
+            .map(Scalar::U64)
+            .or_else(|_| s.parse().map(Scalar::I64))
+            .ok(),
+        Want::Float if is_number(s) => s
+            .parse()
+            .ok()
+            .filter(|f: &f64| f.is_finite())
+            .map(Scalar::F64),
+        _ => None,
+    }
+}
+
+/// Checks that `s` follows the JSON number grammar.
+fn is_number(s: &str) -> bool {
+    let b = s.as_bytes();
+    let digits = |i: usize| b[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    let mut i = usize::from(b.first() == Some(&b'-'));
+    let n = digits(i);
+    if n == 0 || (n > 1 && b[i] == b'0') {
+        return false;
+    }
+    i += n;
+    if b.get(i) == Some(&b'.') {
+        let n = digits(i + 1);
+        if n == 0 {
+            return false;
+        }
+        i += n + 1;
+    }
+    if let Some(b'e') | Some(b'E') = b.get(i) {
+        i += 1;
+        if let Some(b'+') | Some(b'-') = b.get(i) {
+            i += 1;
+        }
+        let n = digits(i);
+        if n == 0 {
+            return false;
+        }
+        i += n;
+    }
+    i == b.len()
+}
+
+struct LenientDeserializer<'a, 'f, D> {
+    de: D,
+    state: &'a mut State<'f>,
+    key: bool,
+}
+
+impl<'a, 'f, D> LenientDeserializer<'a, 'f, D> {
+    fn wrap<V>(self, visitor: V, want: Want) -> (D, Wrap<'a, 'f, V>) {
+        let want = if self.key { Want::Key } else { want };
+        (
+            self.de,
+            Wrap {
+                visitor,
+                state: self.state,
+                want,
+            },
+        )
+    }
+}
+
+// Methods with coercions go through `deserialize_any` so the visitor gets
+// to see what is actually there, keys are read as they are.
+macro_rules! coerce {
+    ($($method:ident => $want:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> std::result::Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                if self.key {
+                    let (de, visitor) = self.wrap(visitor, Want::Key);
+                    de.$method(visitor)
+                } else {
+                    let (de, visitor) = self.wrap(visitor, Want::$want);
+                    de.deserialize_any(visitor)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! forward {
+    ($($method:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> std::result::Result<V::Value, D::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let (de, visitor) = self.wrap(visitor, Want::Any);
+                de.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'a, 'f, 'de, D> de::Deserializer<'de> for LenientDeserializer<'a, 'f, D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    coerce! {
+        deserialize_bool => Bool,
+        deserialize_i8 => Integer,
+        deserialize_i16 => Integer,
+        deserialize_i32 => Integer,
+        deserialize_i64 => Integer,
+        deserialize_i128 => Integer,
+        deserialize_u8 => Integer,
+        deserialize_u16 => Integer,
+        deserialize_u32 => Integer,
+        deserialize_u64 => Integer,
+        deserialize_u128 => Integer,
+        deserialize_f32 => Float,
+        deserialize_f64 => Float,
+        deserialize_seq => Seq,
+    }
+
+    forward! {
+        deserialize_any,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any,
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let (de, visitor) = self.wrap(visitor, Want::Any);
+        de.deserialize_enum(name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+/// Wraps the visitor of a `Deserialize` implementation to coerce the
+/// values it is given and to keep nested values lenient.
+struct Wrap<'a, 'f, V> {
+    visitor: V,
+    state: &'a mut State<'f>,
+    want: Want,
+}
+
+impl<'a, 'f, V> Wrap<'a, 'f, V> {
+    fn remember_key(&mut self, key: &str) {
+        if self.want == Want::Key {
+            self.state.key.clear();
+            self.state.key.push_str(key);
+        }
+    }
+}
+
+impl<'a, 'f, 'de, V> Wrap<'a, 'f, V>
+where
+    V: Visitor<'de>,
+{
+    fn scalar<E>(self, scalar: Scalar) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        match scalar {
+            Scalar::Bool(b) => {
+                self.state.report(Coercion::StringToBool);
+                self.visitor.visit_bool(b)
+            }
+            Scalar::I64(n) => {
+                self.state.report(Coercion::StringToNumber);
+                self.visitor.visit_i64(n)
+            }
+            Scalar::U64(n) => {
+                self.state.report(Coercion::StringToNumber);
+                self.visitor.visit_u64(n)
+            }
+            Scalar::F64(n) => {
+                self.state.report(Coercion::StringToNumber);
+                self.visitor.visit_f64(n)
+            }
+        }
+    }
+
+    fn single<D>(self, de: D) -> std::result::Result<V::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.state.report(Coercion::ValueToArray);
+        self.visitor.visit_seq(Single {
+            de: Some(de),
+            state: self.state,
+        })
+    }
+}
+
+impl<'a, 'f, 'de, V> Visitor<'de> for Wrap<'a, 'f, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.visitor.expecting(formatter)
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        if self.want == Want::Seq {
+            self.single(v.into_deserializer())
+        } else {
+            self.visitor.visit_bool(v)
+        }
+    }
+
+    fn visit_i8<E>(self, v: i8) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_i8(v)
+    }
+
+    fn visit_i16<E>(self, v: i16) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_i16(v)
+    }
+
+    fn visit_i32<E>(self, v: i32) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_i32(v)
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.want {
+            Want::Bool if v == 0 || v == 1 => {
+                self.state.report(Coercion::NumberToBool);
+                self.visitor.visit_bool(v == 1)
+            }
+            Want::Seq => self.single(v.into_deserializer()),
+            _ => self.visitor.visit_i64(v),
+        }
+    }
+
+    fn visit_i128<E>(self, v: i128) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_i128(v)
+    }
+
+    fn visit_u8<E>(self, v: u8) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_u8(v)
+    }
+
+    fn visit_u16<E>(self, v: u16) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_u16(v)
+    }
+
+    fn visit_u32<E>(self, v: u32) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_u32(v)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.want {
+            Want::Bool if v <= 1 => {
+                self.state.report(Coercion::NumberToBool);
+                self.visitor.visit_bool(v == 1)
+            }
+            Want::Seq => self.single(v.into_deserializer()),
+            _ => self.visitor.visit_u64(v),
+        }
+    }
+
+    fn visit_u128<E>(self, v: u128) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_u128(v)
+    }
+
+    fn visit_f32<E>(self, v: f32) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_f32(v)
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        if self.want == Want::Seq {
+            self.single(v.into_deserializer())
+        } else {
+            self.visitor.visit_f64(v)
+        }
+    }
+
+    fn visit_char<E>(self, v: char) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_char(v)
+    }
+
+    fn visit_str<E>(mut self, v: &str) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.remember_key(v);
+        if self.want == Want::Seq {
+            return self.single(v.into_deserializer());
+        }
+        match parse(self.want, v) {
+            Some(scalar) => self.scalar(scalar),
+            None => self.visitor.visit_str(v),
+        }
+    }
+
+    fn visit_borrowed_str<E>(mut self, v: &'de str) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.remember_key(v);
+        if self.want == Want::Seq {
+            return self.single(BorrowedStrDeserializer::new(v));
+        }
+        match parse(self.want, v) {
+            Some(scalar) => self.scalar(scalar),
+            None => self.visitor.visit_borrowed_str(v),
+        }
+    }
+
+    fn visit_string<E>(mut self, v: String) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.remember_key(&v);
+        if self.want == Want::Seq {
+            return self.single(v.into_deserializer());
+        }
+        match parse(self.want, &v) {
+            Some(scalar) => self.scalar(scalar),
+            None => self.visitor.visit_string(v),
+        }
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_bytes(v)
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_borrowed_bytes(v)
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_byte_buf(v)
+    }
+
+    fn visit_none<E>(self) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> std::result::Result<V::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.visitor.visit_some(LenientDeserializer {
+            de: deserializer,
+            state: self.state,
+            key: false,
+        })
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<V::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visitor.visit_unit()
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> std::result::Result<V::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.visitor.visit_newtype_struct(LenientDeserializer {
+            de: deserializer,
+            state: self.state,
+            key: false,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> std::result::Result<V::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.visitor.visit_seq(Seq {
+            access: seq,
+            state: self.state,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A>(self, map: A) -> std::result::Result<V::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        if self.want == Want::Seq {
+            self.single(MapAccessDeserializer::new(map))
+        } else {
+            self.visitor.visit_map(Map {
+                access: map,
+                state: self.state,
+            })
+        }
+    }
+
+    fn visit_enum<A>(self, data: A) -> std::result::Result<V::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        self.visitor.visit_enum(Enum {
+            data,
+            state: self.state,
+        })
+    }
+}
+
+/// Deserializes a value leniently
+struct Seed<'a, 'f, T> {
+    inner: T,
+    state: &'a mut State<'f>,
+    key: bool,
+}
+
+impl<'a, 'f, 'de, T> DeserializeSeed<'de> for Seed<'a, 'f, T>
+where
+    T: DeserializeSeed<'de>,
+{
+    type Value = T::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<T::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.inner.deserialize(LenientDeserializer {
+            de: deserializer,
+            state: self.state,
+            key: self.key,
+        })
+    }
+}
+
+/// A single value presented as a sequence
+struct Single<'a, 'f, D> {
+    de: Option<D>,
+    state: &'a mut State<'f>,
+}
+
+impl<'a, 'f, 'de, D> SeqAccess<'de> for Single<'a, 'f, D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> std::result::Result<Option<T::Value>, D::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.de.take() {
+            Some(de) => seed
+                .deserialize(LenientDeserializer {
+                    de,
+                    state: &mut *self.state,
+                    key: false,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(usize::from(self.de.is_some()))
+    }
+}
+
+struct Seq<'a, 'f, A> {
+    access: A,
+    state: &'a mut State<'f>,
+    index: usize,
+}
+
+impl<'a, 'f, 'de, A> SeqAccess<'de> for Seq<'a, 'f, A>
+where
+    A: SeqAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> std::result::Result<Option<T::Value>, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let len = self.state.path.len();
+        self.state.path.push('[');
+        self.state.path.push_str(&self.index.to_string());
+        self.state.path.push(']');
+        self.index += 1;
+        let r = self.access.next_element_seed(Seed {
+            inner: seed,
+            state: &mut *self.state,
+            key: false,
+        });
+        self.state.path.truncate(len);
+        r
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.access.size_hint()
+    }
+}
+
+struct Map<'a, 'f, A> {
+    access: A,
+    state: &'a mut State<'f>,
+}
+
+impl<'a, 'f, 'de, A> MapAccess<'de> for Map<'a, 'f, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> std::result::Result<Option<K::Value>, A::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        self.state.key.clear();
+        self.access.next_key_seed(Seed {
+            inner: seed,
+            state: &mut *self.state,
+            key: true,
+        })
+    }
+
+    fn next_value_seed<T>(&mut self, seed: T) -> std::result::Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let len = self.state.path.len();
+        let segment = key_segment(&self.state.key);
+        self.state.path.push_str(&segment);
+        let r = self.access.next_value_seed(Seed {
+            inner: seed,
+            state: &mut *self.state,
+            key: false,
+        });
+        self.state.path.truncate(len);
+        r
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.access.size_hint()
+    }
+}
+
+struct Enum<'a, 'f, A> {
+    data: A,
+    state: &'a mut State<'f>,
+}
+
+impl<'a, 'f, 'de, A> EnumAccess<'de> for Enum<'a, 'f, A>
+where
+    A: EnumAccess<'de>,
+{
+    type Error = A::Error;
+    type Variant = Variant<'a, 'f, A::Variant>;
+
+    fn variant_seed<V>(self, seed: V) -> std::result::Result<(V::Value, Self::Variant), A::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (value, variant) = stry!(self.data.variant_seed(seed));
+        Ok((
+            value,
+            Variant {
+                variant,
+                state: self.state,
+            },
+        ))
+    }
+}
+
+struct Variant<'a, 'f, A> {
+    variant: A,
+    state: &'a mut State<'f>,
+}
+
+impl<'a, 'f, 'de, A> VariantAccess<'de> for Variant<'a, 'f, A>
+where
+    A: VariantAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn unit_variant(self) -> std::result::Result<(), A::Error> {
+        self.variant.unit_variant()
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> std::result::Result<T::Value, A::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.variant.newtype_variant_seed(Seed {
+            inner: seed,
+            state: self.state,
+            key: false,
+        })
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> std::result::Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.variant.tuple_variant(
+            len,
+            Wrap {
+                visitor,
+                state: self.state,
+                want: Want::Any,
+            },
+        )
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> std::result::Result<V::Value, A::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.variant.struct_variant(
+            fields,
+            Wrap {
+                visitor,
+                state: self.state,
+                want: Want::Any,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::{is_number, Coercion, Lenient};
+    use crate::{json, OwnedValue};
+    use serde_ext::Deserialize;
+    use std::collections::HashMap;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Port {
+        number: u16,
+        open: bool,
+        weight: f32,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Server {
+        host: String,
+        ports: Vec<Port>,
+        ids: Option<Vec<i64>>,
+        extra: HashMap<String, bool>,
+    }
+
+    fn expected() -> Server {
+        Server {
+            host: "a".to_string(),
+            ports: vec![
+                Port {
+                    number: 80,
+                    open: true,
+                    weight: 0.5,
+                },
+                Port {
+                    number: 443,
+                    open: false,
+                    weight: 1.0,
+                },
+            ],
+            ids: Some(vec![-1]),
+            extra: vec![("a b".to_string(), true)].into_iter().collect(),
+        }
+    }
+
+    const SLOPPY: &str = r#"{
+        "host": "a",
+        "ports": [
+            {"number": "80", "open": "true", "weight": "0.5"},
+            {"number": 443, "open": 0, "weight": 1}
+        ],
+        "ids": "-1",
+        "extra": {"a b": 1}
+    }"#;
+
+    fn coercions() -> Vec<(String, Coercion)> {
+        vec![
+            (".ports[0].number".to_string(), Coercion::StringToNumber),
+            (".ports[0].open".to_string(), Coercion::StringToBool),
+            (".ports[0].weight".to_string(), Coercion::StringToNumber),
+            (".ports[1].open".to_string(), Coercion::NumberToBool),
+            (".ids".to_string(), Coercion::ValueToArray),
+            (".ids".to_string(), Coercion::StringToNumber),
+            (r#".extra["a b"]"#.to_string(), Coercion::NumberToBool),
+        ]
+    }
+
+    #[test]
+    fn from_slice() {
+        let mut seen = Vec::new();
+        let mut d = SLOPPY.as_bytes().to_vec();
+        let s: Server = Lenient::new()
+            .with_diagnostics(|p: &str, c| seen.push((p.to_string(), c)))
+            .from_slice(&mut d)
+            .unwrap();
+        assert_eq!(s, expected());
+        assert_eq!(seen, coercions());
+
+        // strict parsing still fails
+        let mut d = SLOPPY.as_bytes().to_vec();
+        assert!(crate::serde::from_slice::<Server>(&mut d).is_err());
+    }
+
+    #[test]
+    fn values() {
+        let mut d = SLOPPY.as_bytes().to_vec();
+        let v: OwnedValue = crate::to_owned_value(&mut d).unwrap();
+
+        let mut seen = Vec::new();
+        let mut lenient =
+            Lenient::new().with_diagnostics(|p: &str, c| seen.push((p.to_string(), c)));
+        assert_eq!(lenient.deserialize::<Server, _>(&v).unwrap(), expected());
+        assert_eq!(lenient.from_owned_value::<Server>(v).unwrap(), expected());
+        let mut d = SLOPPY.as_bytes().to_vec();
+        let v = crate::to_borrowed_value(&mut d).unwrap();
+        assert_eq!(
+            lenient.from_borrowed_value::<Server>(v).unwrap(),
+            expected()
+        );
+        // object iteration order differs for values so we only count
+        assert_eq!(seen.len(), coercions().len() * 3);
+    }
+
+    #[test]
+    fn tagged() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(tag = "type")]
+        enum Message {
+            Ping { ids: Vec<u8> },
+            Pong(Port),
+        }
+
+        let mut seen = Vec::new();
+        let mut lenient =
+            Lenient::new().with_diagnostics(|p: &str, c| seen.push((p.to_string(), c)));
+        let mut d = br#"[
+            {"type": "Ping", "ids": [1]},
+            {"number": 1, "type": "Pong", "open": true, "weight": 2}
+        ]"#
+        .to_vec();
+        let ms: Vec<Message> = lenient.from_slice(&mut d).unwrap();
+        assert_eq!(
+            ms,
+            vec![
+                Message::Ping { ids: vec![1] },
+                Message::Pong(Port {
+                    number: 1,
+                    open: true,
+                    weight: 2.0
+                })
+            ]
+        );
+
+        // serde buffers the enum, so its values aren't coerced
+        let mut d = br#"{"type": "Ping", "ids": "1"}"#.to_vec();
+        assert!(lenient.from_slice::<Message>(&mut d).is_err());
+        assert!(seen.is_empty());
+    }
+
+    #[test]
+    fn still_strict() {
+        let mut lenient = Lenient::new();
+        let v = json!({"number": "080", "open": 2, "weight": "NaN"});
+        assert!(lenient.deserialize::<Port, _>(&v).is_err());
+        let v = json!({"number": "80", "open": "yes", "weight": 1});
+        assert!(lenient.deserialize::<Port, _>(&v).is_err());
+        let v = json!({"number": "70000", "open": true, "weight": 1});
+        assert!(lenient.deserialize::<Port, _>(&v).is_err());
+        let mut d = br#"{"number": 80, "open": true, "weight": " 1"}"#.to_vec();
+        let e = lenient.from_slice::<Port>(&mut d).unwrap_err();
+        assert_eq!(e.path(), ".weight");
+    }
+
+    #[test]
+    fn numbers() {
+        for n in &["0", "-0", "42", "-1.5", "1e5", "1E-5", "2.5e+3"] {
+            assert!(is_number(n), "{}", n);
+        }
+        for n in &["", "-", "01", "1.", ".5", "1e", "+1", "0x10", "1 ", "inf"] {
+            assert!(!is_number(n), "{}", n);
+        }
+    }
+}
@@ -0,0 +1,604 @@
+use crate::path::Structurals;
+use crate::{
+    stry, Deserializer, Error, ErrorType, Indexes, Node, OwnedValue, Result, StructuralIndex,
+};
+use serde::de::{self, Deserialize, Visitor};
+use serde::ser::{self, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Write;
+
+/// The name of the newtype struct `RawValue` serializes and deserializes
+/// as, our serializers and deserializers use it to pass the raw text.
+pub(crate) const TOKEN: &str = "$simd_json::private::RawValue";
+
+/// A JSON value as it appears in the input, without being parsed any
+/// further.
+///
+/// It can be used as a field to pass parts of a document through
+/// untouched, or to delay parsing them. Borrowed as `&RawValue` it
+/// references the input directly, this requires that it contains no
+/// escaped strings since those are rewritten in place during parsing.
+/// `Box<RawValue>` copies the text and works for all values.
+///
+/// Raw values need to know where in the input a value came from, so they
+/// have to be deserialized with [`from_slice_with_raw`](crate::serde::from_slice_with_raw)
+/// or a [`Deserializer::from_slice_with_source`]. The other functions
+/// drop that information after parsing and fail on raw values.
+///
+/// Serializing a `RawValue` with the serializers in this crate writes
+/// the text back verbatim.
+///
+/// ```
+/// use serde::Deserialize;
+/// use simd_json::serde::RawValue;
+///
+/// #[derive(Deserialize)]
+/// struct Envelope<'a> {
+///     kind: &'a str,
+///     #[serde(borrow)]
+///     payload: &'a RawValue,
+/// }
+///
+/// let mut d = br#"{"kind": "point", "payload": {"x": 1,  "y": 2.50}}"#.to_vec();
+/// let envelope: Envelope = simd_json::serde::from_slice_with_raw(&mut d).unwrap();
+/// assert_eq!(envelope.kind, "point");
+/// assert_eq!(envelope.payload.get(), r#"{"x": 1,  "y": 2.50}"#);
+/// ```
+#[repr(transparent)]
+pub struct RawValue {
+    json: str,
+}
+
+impl RawValue {
+    fn from_borrowed(json: &str) -> &Self {
+        unsafe { &*(json as *const str as *const Self) }
+    }
+
+    fn from_owned(json: Box<str>) -> Box<Self> {
+        unsafe { Box::from_raw(Box::into_raw(json) as *mut Self) }
+    }
+
+    /// Turns a string holding JSON into a raw value
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `json` is invalid JSON.
+    pub fn from_string(json: String) -> Result<Box<Self>> {
+        stry!(crate::to_tape(&mut json.as_bytes().to_vec()));
+        Ok(Self::from_owned(json.into_boxed_str()))
+    }
+
+    /// The JSON text of the value
+    #[must_use]
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+
+    /// Parses the JSON text into an `OwnedValue`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the text is invalid JSON, this can only
+    /// happen for values that were not created by parsing.
+    pub fn to_owned_value(&self) -> Result<OwnedValue> {
+        crate::to_owned_value(&mut self.json.as_bytes().to_vec())
+    }
+}
+
+impl Clone for Box<RawValue> {
+    fn clone(&self) -> Self {
+        (**self).to_owned()
+    }
+}
+
+impl ToOwned for RawValue {
+    type Owned = Box<Self>;
+
+    fn to_owned(&self) -> Self::Owned {
+        Self::from_owned(self.json.to_owned().into_boxed_str())
+    }
+}
+
+impl fmt::Debug for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawValue").field(&&self.json).finish()
+    }
+}
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl Serialize for RawValue {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(TOKEN, &self.json)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for &'a RawValue {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct RawVisitor;
+
+        impl<'de> Visitor<'de> for RawVisitor {
+            type Value = &'de RawValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a raw JSON value borrowed from the input")
+            }
+
+            fn visit_borrowed_str<E>(self, json: &'de str) -> std::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_borrowed(json))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Box<RawValue> {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct RawVisitor;
+
+        impl Visitor<'_> for RawVisitor {
+            type Value = Box<RawValue>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a raw JSON value")
+            }
+
+            fn visit_str<E>(self, json: &str) -> std::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(json.into()))
+            }
+
+            fn visit_string<E>(self, json: String) -> std::result::Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(RawValue::from_owned(json.into_boxed_str()))
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(TOKEN, RawVisitor)
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// The JSON text the value at `node` on the tape was parsed from. It
+    /// is borrowed from the input if parsing left it untouched, otherwise
+    /// it is copied from the input as it was before parsing.
+    ///
+    /// Only deserializers created with
+    /// [`Deserializer::from_slice_with_source`] know where their values
+    /// came from, for all others this returns `None`, just like for nodes
+    /// past the end of the tape.
+    ///
+    /// ```
+    /// use simd_json::Deserializer;
+    ///
+    /// let mut d = br#"{"id": 1, "payload": [1.50, "\u00e9"]}"#.to_vec();
+    /// let deserializer = Deserializer::from_slice_with_source(&mut d).unwrap();
+    /// assert_eq!(deserializer.raw_value(3).unwrap().get(), "1");
+    /// assert_eq!(
+    ///     deserializer.raw_value(5).unwrap().get(),
+    ///     r#"[1.50, "\u00e9"]"#
+    /// );
+    /// ```
+    #[must_use]
+    pub fn raw_value(&self, node: usize) -> Option<Cow<'de, RawValue>> {
+        if node == 0 {
+            return None;
+        }
+        let (json, _, _) = self.raw_text(node, (0, 0)).ok()?;
+        Some(match json {
+            Cow::Borrowed(json) => Cow::Borrowed(RawValue::from_borrowed(json)),
+            Cow::Owned(json) => Cow::Owned(RawValue::from_owned(json.into_boxed_str())),
+        })
+    }
+
+    /// Takes the next value off the tape and returns the JSON text it was
+    /// parsed from, see [`Deserializer::raw_value`].
+    pub(crate) fn next_raw(&mut self) -> Result<Cow<'de, str>> {
+        let (json, last, cursor) = stry!(self.raw_text(self.idx + 1, self.cursor));
+        self.cursor = cursor;
+        self.idx = last - 1;
+        Ok(json)
+    }
+
+    /// Finds the text of the value at tape node `first`, starting to count
+    /// structurals at `cursor`. Returns the text, the tape node after the
+    /// value and the new cursor.
+    fn raw_text(
+        &self,
+        first: usize,
+        cursor: (usize, usize),
+    ) -> Result<(Cow<'de, str>, usize, (usize, usize))> {
+        let source = match &self.source {
+            Some(source) => source,
+            None => {
+                return Err(de::Error::custom(
+                    "raw values need `from_slice_with_raw` or a deserializer created with `Deserializer::from_slice_with_source`",
+                ))
+            }
+        };
+        let last = match self.tape.get(first) {
+            Some(Node::Array(_, end)) | Some(Node::Object(_, end)) => *end,
+            Some(_) => first + 1,
+            None => return Err(Self::error(ErrorType::Syntax)),
+        };
+        let input = source.input;
+        let (start, end, verbatim, cursor) = stry!(match &source.indexes {
+            Indexes::U32(idx) => locate(&self.tape, input, idx, cursor, first, last),
+            Indexes::U64(idx) => locate(&self.tape, input, idx, cursor, first, last),
+        });
+
+        let json = if verbatim {
+            Cow::Borrowed(unsafe { std::str::from_utf8_unchecked(&input[start..end]) })
+        } else {
+            let json = unsafe { std::str::from_utf8_unchecked(&source.original[start..end]) };
+            Cow::Owned(json.to_string())
+        };
+        Ok((json, last, cursor))
+    }
+}
+
+/// Values on the tape start at every structural that isn't a separator or
+/// closing bracket
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+fn starts_value(c: u8) -> bool {
+    !matches!(c, b',' | b':' | b']' | b'}')
+}
+
+/// Finds the span of the tape nodes `first..last` in the input, and if
+/// their strings are still as they were before parsing. Starts counting
+/// structurals at `cursor`, the tape node and structural index of the
+/// last lookup, and returns the new cursor.
+fn locate<I: StructuralIndex>(
+    tape: &[Node],
+    input: &[u8],
+    idx: &[I],
+    cursor: (usize, usize),
+    first: usize,
+    last: usize,
+) -> Result<(usize, usize, bool, (usize, usize))> {
+    let s = Structurals { input, idx };
+    let (mut node, mut p) = if cursor.0 <= first { cursor } else { (0, 0) };
+    while node < first {
+        p += 1;
+        if starts_value(stry!(s.char_at(p))) {
+            node += 1;
+        }
+    }
+    let next = stry!(s.skip_value(p));
+
+    // strings that were unescaped in place are shorter than their raw
+    // text, every other value is left alone
+    let mut verbatim = true;
+    for q in p..next {
+        if !starts_value(input[s.at(q)]) {
+            continue;
+        }
+        if let Some(Node::String(string)) = tape.get(node) {
+            verbatim &= s.value_end(q, q + 1) - s.at(q) - 2 == string.len();
+        }
+        node += 1;
+    }
+    debug_assert_eq!(node, last);
+
+    Ok((s.at(p), s.value_end(p, next), verbatim, (first, p)))
+}
+
+/// Writes the text of a `RawValue`, which serializes as a newtype struct
+/// around a string, verbatim.
+pub(crate) struct RawEmitter<'writer, W: Write>(pub(crate) &'writer mut W);
+
+fn expected_raw() -> Error {
+    Error::generic(ErrorType::Serde("expected a raw JSON value".into()))
+}
+
+impl<W> ser::Serializer for RawEmitter<'_, W>
+where
+    W: Write,
+{
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<(), Error>;
+    type SerializeTuple = ser::Impossible<(), Error>;
+    type SerializeTupleStruct = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = ser::Impossible<(), Error>;
+    type SerializeStruct = ser::Impossible<(), Error>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
+
+    fn serialize_str(self, value: &str) -> Result<()> {
+        self.0
+            .write_all(value.as_bytes())
+            .map_err(|err| Error::generic(ErrorType::Io(err)))
+    }
+
+    fn serialize_bool(self, _v: bool) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(expected_raw())
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        Err(expected_raw())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(expected_raw())
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(expected_raw())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(expected_raw())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(expected_raw())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(expected_raw())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(expected_raw())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(expected_raw())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(expected_raw())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(expected_raw())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::serde::{from_slice, from_slice_with_raw, to_string, to_string_pretty};
+    use crate::BorrowedValue;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    struct Borrowed<'a> {
+        id: u64,
+        #[serde(borrow)]
+        payload: &'a RawValue,
+    }
+
+    #[derive(Deserialize, Serialize, Clone)]
+    struct Owned {
+        id: u64,
+        payload: Box<RawValue>,
+        tail: Vec<String>,
+    }
+
+    #[test]
+    fn borrowed() {
+        let mut d = br#"{"payload": [1, {"a" : "b"}, 2.50] , "id": 7}"#.to_vec();
+        let v: Borrowed = from_slice_with_raw(&mut d).unwrap();
+        assert_eq!(v.id, 7);
+        assert_eq!(v.payload.get(), r#"[1, {"a" : "b"}, 2.50]"#);
+        assert_eq!(
+            to_string(&v).unwrap(),
+            r#"{"id":7,"payload":[1, {"a" : "b"}, 2.50]}"#
+        );
+    }
+
+    #[test]
+    fn scalars() {
+        for json in &["1.0e2", "\"text\"", "null", "-0", "true"] {
+            let mut d = format!("[{} , {} ]", json, json).into_bytes();
+            let v: Vec<&RawValue> = from_slice_with_raw(&mut d).unwrap();
+            assert_eq!(v[0].get(), *json);
+            assert_eq!(v[1].get(), *json);
+        }
+    }
+
+    #[test]
+    fn escaped() {
+        let mut d = br#"{"id": 1, "payload": {"a\"b": "\u00e9"}, "tail": ["\n"]}"#.to_vec();
+        let v: Owned = from_slice_with_raw(&mut d).unwrap();
+        assert_eq!(v.payload.get(), r#"{"a\"b": "\u00e9"}"#);
+        assert_eq!(v.tail, vec!["\n"]);
+
+        // borrowing needs the text to be untouched
+        let mut d = br#"{"id": 1, "payload": "a\"b"}"#.to_vec();
+        assert!(from_slice_with_raw::<Borrowed>(&mut d).is_err());
+    }
+
+    #[test]
+    fn needs_source() {
+        // the lean deserializer drops what raw values need
+        let mut d = br#"{"id": 1, "payload": [2]}"#.to_vec();
+        assert!(from_slice::<Borrowed>(&mut d).is_err());
+        let mut d = br#"{"id": 1, "payload": [2], "tail": []}"#.to_vec();
+        assert!(from_slice::<Owned>(&mut d).is_err());
+    }
+
+    #[test]
+    fn raw_value() {
+        let mut d = br#"{"id": 1, "payload": [2, "a\"b"]}"#.to_vec();
+        let deserializer = Deserializer::from_slice_with_source(&mut d).unwrap();
+        assert!(deserializer.raw_value(0).is_none());
+        assert!(
+            matches!(deserializer.raw_value(3), Some(Cow::Borrowed(json)) if json.get() == "1")
+        );
+        assert!(
+            matches!(deserializer.raw_value(5), Some(Cow::Owned(json)) if json.get() == r#"[2, "a\"b"]"#)
+        );
+        assert!(deserializer.raw_value(8).is_none());
+
+        // without the source we can't tell where values came from
+        let mut d = br#"{"id": 1}"#.to_vec();
+        let deserializer = Deserializer::from_slice(&mut d).unwrap();
+        assert!(deserializer.raw_value(3).is_none());
+    }
+
+    #[test]
+    fn serialize() {
+        let v = Owned {
+            id: 1,
+            payload: RawValue::from_string(r#"{ "keep" : [ 1.50 ] }"#.into()).unwrap(),
+            tail: vec![],
+        };
+        assert_eq!(
+            to_string(&v).unwrap(),
+            r#"{"id":1,"payload":{ "keep" : [ 1.50 ] },"tail":[]}"#
+        );
+        assert!(to_string_pretty(&v.clone())
+            .unwrap()
+            .contains(r#""payload": { "keep" : [ 1.50 ] }"#));
+        assert!(RawValue::from_string("{".into()).is_err());
+    }
+
+    #[test]
+    fn values() {
+        let v = Owned {
+            id: 1,
+            payload: RawValue::from_string("[1, 2]".into()).unwrap(),
+            tail: vec![],
+        };
+        let owned = crate::serde::to_owned_value(v.clone()).unwrap();
+        assert_eq!(owned["payload"], OwnedValue::from(vec![1, 2]));
+        let borrowed = crate::serde::to_borrowed_value(v).unwrap();
+        assert_eq!(borrowed["payload"], BorrowedValue::from(vec![1, 2]));
+
+        let back: Owned = crate::serde::from_owned_value(owned).unwrap();
+        assert_eq!(back.payload.get(), "[1,2]");
+        let back: Owned = crate::serde::from_borrowed_value(borrowed.clone()).unwrap();
+        assert_eq!(back.payload.get(), "[1,2]");
+        assert_eq!(
+            back.payload.to_owned_value().unwrap(),
+            OwnedValue::from(vec![1, 2])
+        );
+    }
+}
@@ -1,6 +1,7 @@
 mod pp;
-use crate::{serde_ext, Error, ErrorType};
-pub use pp::*;
+use super::raw::{RawEmitter, TOKEN};
+use crate::{serde_ext, stry, Error, ErrorType, Escape};
+pub use pp::{to_string_pretty, to_vec_pretty, to_writer_pretty, PrettySerializer};
 use serde_ext::ser;
 use std::io::Write;
 use std::result::Result;
@@ -22,8 +23,8 @@ where
     T: ser::Serialize + ?Sized,
 {
     let v = Vec::with_capacity(512);
-    let mut s = Serializer(v);
-    to.serialize(&mut s).map(|_| s.0)
+    let mut s = Serializer::new(v);
+    to.serialize(&mut s).map(|_| s.writer)
 }
 
 /// Write a value to a string
@@ -38,6 +39,34 @@ where
     to_vec(to).map(|v| unsafe { String::from_utf8_unchecked(v) })
 }
 
+/// Write a value to a vector as canonical JSON (RFC 8785), see
+/// `OwnedValue::encode_canonical`
+///
+/// # Errors
+/// when the data can not be converted, for example if it contains NaN
+#[inline]
+pub fn to_canonical_vec<T>(to: &T) -> crate::Result<Vec<u8>>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let value = stry!(super::to_owned_value(to));
+    let mut json = Vec::with_capacity(512);
+    stry!(value.write_canonical(&mut json));
+    Ok(json)
+}
+
+/// Write a value to a string as canonical JSON (RFC 8785)
+///
+/// # Errors
+/// when the data can not be converted, for example if it contains NaN
+#[inline]
+pub fn to_canonical_string<T>(to: &T) -> crate::Result<String>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_canonical_vec(to).map(|v| unsafe { String::from_utf8_unchecked(v) })
+}
+
 /// Write a value to a string
 /// # Errors
 /// when the data can not be written
@@ -47,10 +76,36 @@ where
     T: ser::Serialize + ?Sized,
     W: Write,
 {
-    let mut s = Serializer(writer);
+    let mut s = Serializer::new(writer);
     to.serialize(&mut s)
 }
-struct Serializer<W: Write>(W);
+/// Serializer that writes compact JSON
+pub struct Serializer<W: Write> {
+    writer: W,
+    escape: Escape,
+}
+
+impl<W: Write> Serializer<W> {
+    /// Creates a serializer writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            escape: Escape::new(),
+        }
+    }
+
+    /// Sets which characters are escaped in keys and strings
+    #[must_use]
+    pub fn escape(mut self, escape: Escape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Returns the writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
 
 impl<'writer, W> BaseGenerator for Serializer<W>
 where
@@ -59,14 +114,24 @@ where
     type T = W;
     #[inline]
     fn get_writer(&mut self) -> &mut Self::T {
-        &mut self.0
+        &mut self.writer
     }
     #[inline]
     fn write_min(&mut self, _slice: &[u8], min: u8) -> std::io::Result<()> {
-        self.0.write_all(&[min])
+        self.writer.write_all(&[min])
+    }
+
+    #[inline]
+    fn write_string_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
+    }
+
+    #[inline]
+    fn write_simple_str_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
     }
 }
-struct SerializeSeq<'serializer, W: Write + 'serializer> {
+pub struct SerializeSeq<'serializer, W: Write + 'serializer> {
     s: &'serializer mut Serializer<W>,
     first: bool,
 }
@@ -199,7 +264,7 @@ where
     }
 }
 
-struct SerializeMap<'serializer, W: Write + 'serializer> {
+pub struct SerializeMap<'serializer, W: Write + 'serializer> {
     s: &'serializer mut Serializer<W>,
     first: bool,
     wrote_closing: bool,
@@ -519,7 +584,7 @@ where
     }
 }
 
-struct SerializeStructVariant<'serializer, W: Write + 'serializer> {
+pub struct SerializeStructVariant<'serializer, W: Write + 'serializer> {
     s: &'serializer mut Serializer<W>,
     first: bool,
 }
@@ -698,13 +763,17 @@ where
     #[inline]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde_ext::Serialize,
     {
-        value.serialize(self)
+        if name == TOKEN {
+            value.serialize(RawEmitter(&mut self.writer))
+        } else {
+            value.serialize(self)
+        }
     }
 
     #[inline]
@@ -825,6 +894,55 @@ mod test {
     use crate::{OwnedValue as Value, StaticNode};
     use proptest::prelude::*;
 
+    #[test]
+    fn canonical() {
+        #[derive(serde::Serialize)]
+        struct Doc {
+            z: f64,
+            a: Vec<u32>,
+            m: std::collections::HashMap<&'static str, bool>,
+        }
+        let mut m = std::collections::HashMap::new();
+        m.insert("y", true);
+        m.insert("x", false);
+        m.insert("\u{e9}", true);
+        let d = Doc {
+            z: 1e21,
+            a: vec![1, 2],
+            m,
+        };
+        assert_eq!(
+            crate::to_canonical_string(&d).expect("encoding failed"),
+            "{\"a\":[1,2],\"m\":{\"x\":false,\"y\":true,\"\u{e9}\":true},\"z\":1e+21}"
+        );
+        assert!(crate::to_canonical_vec(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn escape() {
+        use super::{PrettySerializer, Serializer};
+        use crate::{json, Escape};
+        use serde::Serialize;
+
+        let v = json!({"</k>": ["a/b", "\u{e9}\u{1f600}", "x&y\u{2028}"]});
+        let e = Escape::new().ascii_only(true).html_safe(true).slash(true);
+        let expected = r#"{"\u003c\/k\u003e":["a\/b","\u00e9\ud83d\ude00","x\u0026y\u2028"]}"#;
+
+        let mut s = Serializer::new(Vec::new()).escape(e);
+        v.serialize(&mut s).expect("encoding failed");
+        assert_eq!(s.into_inner(), expected.as_bytes());
+        assert_eq!(v.encode_escaped(e), expected);
+
+        let mut s = PrettySerializer::new(Vec::new()).escape(e);
+        v.serialize(&mut s).expect("encoding failed");
+        let pretty = String::from_utf8(s.into_inner()).expect("invalid utf8");
+        assert_eq!(pretty, v.encode_pp_escaped(e));
+        assert_eq!(
+            crate::to_string_pretty(&v).expect("encoding failed"),
+            v.encode_pp_escaped(Escape::new())
+        );
+    }
+
     #[test]
     fn prety_print_serde() {
         #[derive(Clone, Debug, PartialEq, serde::Serialize)]
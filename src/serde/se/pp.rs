@@ -1,4 +1,5 @@
-use crate::{serde_ext, stry, Error, ErrorType};
+use crate::serde::raw::{RawEmitter, TOKEN};
+use crate::{serde_ext, Error, ErrorType, Escape, Formatter, PrettyFormatter};
 use serde_ext::ser;
 use std::io::Write;
 use std::result::Result;
@@ -48,19 +49,50 @@ where
     let mut s = PrettySerializer::new(writer);
     to.serialize(&mut s)
 }
-struct PrettySerializer<W: Write> {
+/// Serializer that lays out JSON with a `Formatter`, by default it
+/// indents by two spaces.
+pub struct PrettySerializer<W: Write, F: Formatter = PrettyFormatter> {
     writer: W,
-    dent: u32,
+    formatter: F,
+    escape: Escape,
+    dent: usize,
 }
+
 impl<W: Write> PrettySerializer<W> {
-    fn new(writer: W) -> Self {
-        Self { writer, dent: 0 }
+    /// Creates a serializer writing to `writer`, indenting by two spaces
+    pub fn new(writer: W) -> Self {
+        Self::with_formatter(writer, PrettyFormatter::new())
+    }
+}
+
+impl<W: Write, F: Formatter> PrettySerializer<W, F> {
+    /// Creates a serializer writing to `writer` laid out by `formatter`
+    pub fn with_formatter(writer: W, formatter: F) -> Self {
+        Self {
+            writer,
+            formatter,
+            escape: Escape::new(),
+            dent: 0,
+        }
+    }
+
+    /// Sets which characters are escaped in keys and strings
+    #[must_use]
+    pub fn escape(mut self, escape: Escape) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    /// Returns the writer
+    pub fn into_inner(self) -> W {
+        self.writer
     }
 }
 
-impl<'writer, W> BaseGenerator for PrettySerializer<W>
+impl<W, F> BaseGenerator for PrettySerializer<W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type T = W;
     #[inline]
@@ -68,29 +100,12 @@ where
         &mut self.writer
     }
     #[inline]
-    fn write_min(&mut self, _slice: &[u8], min: u8) -> std::io::Result<()> {
-        self.writer.write_all(&[min])
+    fn write_min(&mut self, _slice: &[u8], _min: u8) -> std::io::Result<()> {
+        self.formatter.write_key_separator(&mut self.writer)
     }
     #[inline]
     fn new_line(&mut self) -> std::io::Result<()> {
-        self.write_char(b'\n').and_then(|_| match self.dent {
-            0 => Ok(()),
-            1 => self.get_writer().write_all(b"  "),
-            2 => self.get_writer().write_all(b"    "),
-            3 => self.get_writer().write_all(b"      "),
-            4 => self.get_writer().write_all(b"        "),
-            5 => self.get_writer().write_all(b"          "),
-            6 => self.get_writer().write_all(b"            "),
-            7 => self.get_writer().write_all(b"              "),
-            8 => self.get_writer().write_all(b"                "),
-            9 => self.get_writer().write_all(b"                  "),
-            _ => {
-                for _ in 0..(self.dent * 2) {
-                    stry!(self.get_writer().write_all(b" "));
-                }
-                Ok(())
-            }
-        })
+        self.formatter.write_new_line(&mut self.writer, self.dent)
     }
 
     fn indent(&mut self) {
@@ -100,14 +115,25 @@ where
     fn dedent(&mut self) {
         self.dent -= 1;
     }
+
+    #[inline]
+    fn write_string_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
+    }
+
+    #[inline]
+    fn write_simple_str_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
+    }
 }
-struct SerializeSeq<'serializer, W: Write + 'serializer> {
-    s: &'serializer mut PrettySerializer<W>,
+pub struct SerializeSeq<'serializer, W: Write + 'serializer, F: Formatter + 'serializer> {
+    s: &'serializer mut PrettySerializer<W, F>,
     first: bool,
 }
-impl<'serializer, W> ser::SerializeSeq for SerializeSeq<'serializer, W>
+impl<'serializer, W, F> ser::SerializeSeq for SerializeSeq<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -139,9 +165,10 @@ where
     }
 }
 
-impl<'serializer, W> ser::SerializeTuple for SerializeSeq<'serializer, W>
+impl<'serializer, W, F> ser::SerializeTuple for SerializeSeq<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -172,9 +199,10 @@ where
     }
 }
 
-impl<'serializer, W> ser::SerializeTupleStruct for SerializeSeq<'serializer, W>
+impl<'serializer, W, F> ser::SerializeTupleStruct for SerializeSeq<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -205,9 +233,10 @@ where
     }
 }
 
-impl<'serializer, W> ser::SerializeTupleVariant for SerializeSeq<'serializer, W>
+impl<'serializer, W, F> ser::SerializeTupleVariant for SerializeSeq<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -238,14 +267,15 @@ where
     }
 }
 
-struct SerializeMap<'serializer, W: Write + 'serializer> {
-    s: &'serializer mut PrettySerializer<W>,
+pub struct SerializeMap<'serializer, W: Write + 'serializer, F: Formatter + 'serializer> {
+    s: &'serializer mut PrettySerializer<W, F>,
     first: bool,
 }
 
-impl<'serializer, W> ser::SerializeMap for SerializeMap<'serializer, W>
+impl<'serializer, W, F> ser::SerializeMap for SerializeMap<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -264,11 +294,11 @@ where
             *first = false;
             iomap!(s.new_line())
                 .and_then(|_| key.serialize(&mut **s))
-                .and_then(|_| iomap!(s.write(b": ")))
+                .and_then(|_| iomap!(s.write_min(b": ", b':')))
         } else {
             iomap!(s.write(b",").and_then(|_| s.new_line()))
                 .and_then(|_| key.serialize(&mut **s))
-                .and_then(|_| iomap!(s.write(b": ")))
+                .and_then(|_| iomap!(s.write_min(b": ", b':')))
         }
     }
     #[inline]
@@ -290,9 +320,10 @@ where
     }
 }
 
-impl<'serializer, W> ser::SerializeStruct for SerializeMap<'serializer, W>
+impl<'serializer, W, F> ser::SerializeStruct for SerializeMap<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -315,13 +346,14 @@ where
             iomap!(s
                 .new_line()
                 .and_then(|_| s.write_simple_string(key))
-                .and_then(|_| s.write(b": ")))
+                .and_then(|_| s.write_min(b": ", b':')))
             .and_then(|_| value.serialize(&mut **s))
         } else {
             iomap!(s
                 .write(b",")
+                .and_then(|_| s.new_line())
                 .and_then(|_| s.write_simple_string(key))
-                .and_then(|_| s.write(b": ")))
+                .and_then(|_| s.write_min(b": ", b':')))
             .and_then(|_| value.serialize(&mut **s))
         }
     }
@@ -336,14 +368,15 @@ where
     }
 }
 
-struct SerializeStructVariant<'serializer, W: Write + 'serializer> {
-    s: &'serializer mut PrettySerializer<W>,
+pub struct SerializeStructVariant<'serializer, W: Write + 'serializer, F: Formatter + 'serializer> {
+    s: &'serializer mut PrettySerializer<W, F>,
     first: bool,
 }
 
-impl<'serializer, W> ser::SerializeStructVariant for SerializeStructVariant<'serializer, W>
+impl<'serializer, W, F> ser::SerializeStructVariant for SerializeStructVariant<'serializer, W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
@@ -367,13 +400,14 @@ where
             iomap!(s
                 .new_line()
                 .and_then(|_| s.write_simple_string(key))
-                .and_then(|_| s.write(b": ")))
+                .and_then(|_| s.write_min(b": ", b':')))
             .and_then(|_| value.serialize(&mut **s))
         } else {
             iomap!(s
                 .write(b",")
+                .and_then(|_| s.new_line())
                 .and_then(|_| s.write_simple_string(key))
-                .and_then(|_| s.write(b": ")))
+                .and_then(|_| s.write_min(b": ", b':')))
             .and_then(|_| value.serialize(&mut **s))
         }
     }
@@ -392,19 +426,20 @@ where
     }
 }
 
-impl<'writer, W> ser::Serializer for &'writer mut PrettySerializer<W>
+impl<'writer, W, F> ser::Serializer for &'writer mut PrettySerializer<W, F>
 where
     W: Write,
+    F: Formatter,
 {
     type Ok = ();
     type Error = Error;
-    type SerializeSeq = SerializeSeq<'writer, W>;
-    type SerializeTuple = SerializeSeq<'writer, W>;
-    type SerializeTupleStruct = SerializeSeq<'writer, W>;
-    type SerializeTupleVariant = SerializeSeq<'writer, W>;
-    type SerializeMap = SerializeMap<'writer, W>;
-    type SerializeStruct = SerializeMap<'writer, W>;
-    type SerializeStructVariant = SerializeStructVariant<'writer, W>;
+    type SerializeSeq = SerializeSeq<'writer, W, F>;
+    type SerializeTuple = SerializeSeq<'writer, W, F>;
+    type SerializeTupleStruct = SerializeSeq<'writer, W, F>;
+    type SerializeTupleVariant = SerializeSeq<'writer, W, F>;
+    type SerializeMap = SerializeMap<'writer, W, F>;
+    type SerializeStruct = SerializeMap<'writer, W, F>;
+    type SerializeStructVariant = SerializeStructVariant<'writer, W, F>;
     #[inline]
     fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
         if v {
@@ -526,13 +561,17 @@ where
     #[inline]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
         T: serde_ext::Serialize,
     {
-        value.serialize(self)
+        if name == TOKEN {
+            value.serialize(RawEmitter(&mut self.writer))
+        } else {
+            value.serialize(self)
+        }
     }
 
     #[inline]
@@ -549,7 +588,7 @@ where
         iomap!(self
             .write(b"{")
             .and_then(|_| self.write_simple_string(variant))
-            .and_then(|_| self.write(b": ")))
+            .and_then(|_| self.write_min(b": ", b':')))
         .and_then(|_| value.serialize(&mut *self))
         .and_then(|_| iomap!(self.write(b"}")))
     }
@@ -594,7 +633,7 @@ where
             .write(b"{")
             .and_then(|_| self.new_line())
             .and_then(|_| self.write_simple_string(variant))
-            .and_then(|_| self.write(b": ")))
+            .and_then(|_| self.write_min(b": ", b':')))
         .and_then(move |_| self.serialize_seq(Some(len)))
     }
 
@@ -634,7 +673,7 @@ where
             .write(b"{")
             .and_then(|_| self.new_line())
             .and_then(|_| self.write_simple_string(variant))
-            .and_then(|_| self.write(b": ")))
+            .and_then(|_| self.write_min(b": ", b':')))
         .and_then(move |_| {
             if len == 0 {
                 iomap!(self.write(b"{}"))
@@ -746,4 +785,54 @@ mod test {
         let res = crate::to_string_pretty(&v).expect("encoding failed");
         assert_eq!(expected, res);
     }
+
+    #[derive(serde::Serialize)]
+    struct Point {
+        x: u8,
+        y: Vec<u8>,
+    }
+
+    #[derive(serde::Serialize)]
+    enum Shape {
+        Dot { at: Point },
+    }
+
+    #[test]
+    fn structs() {
+        let p = Point { x: 1, y: vec![2] };
+        assert_eq!(
+            crate::to_string_pretty(&p).expect("encoding failed"),
+            "{\n  \"x\": 1,\n  \"y\": [\n    2\n  ]\n}"
+        );
+        let s = Shape::Dot {
+            at: Point { x: 1, y: vec![] },
+        };
+        assert_eq!(
+            crate::to_string_pretty(&s).expect("encoding failed"),
+            "{\n  \"Dot\": {\n    \"at\": {\n      \"x\": 1,\n      \"y\": []\n    }\n  }\n}"
+        );
+    }
+
+    #[test]
+    fn formatter() {
+        use super::PrettySerializer;
+        use crate::{CompactFormatter, PrettyFormatter};
+        use serde::Serialize;
+
+        let p = Point { x: 1, y: vec![2] };
+        let f = PrettyFormatter::new()
+            .indent(b"\t")
+            .line_ending(b"\r\n")
+            .space_after_colon(false);
+        let mut s = PrettySerializer::with_formatter(Vec::new(), f);
+        p.serialize(&mut s).expect("encoding failed");
+        assert_eq!(
+            String::from_utf8(s.into_inner()).expect("invalid utf8"),
+            "{\r\n\t\"x\":1,\r\n\t\"y\":[\r\n\t\t2\r\n\t]\r\n}"
+        );
+
+        let mut s = PrettySerializer::with_formatter(Vec::new(), CompactFormatter);
+        p.serialize(&mut s).expect("encoding failed");
+        assert_eq!(s.into_inner(), crate::to_vec(&p).expect("encoding failed"));
+    }
 }
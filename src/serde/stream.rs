@@ -0,0 +1,347 @@
+//! Reading a sequence of JSON values from one buffer.
+//!
+//! The values are found one at a time with a scalar scan that only tracks
+//! strings and nesting, then each of them is parsed on its own. Nothing is
+//! read ahead of the value that is returned, so a broken value only shows
+//! up when the stream gets to it.
+
+use crate::{AlignedBuf, Deserializer, Error, ErrorType, Result, Span, SIMDJSON_PADDING};
+use serde::de::Deserialize;
+use std::marker::PhantomData;
+
+/// Deserializes a sequence of JSON values from one buffer, such as
+/// `{"a":1}{"a":2}` or newline separated values.
+///
+/// Values may follow each other directly or be separated by whitespace.
+/// Each value is found and parsed on its own so the input is rewritten in
+/// place one value at a time, and values that borrow from the input stay
+/// valid while the iteration goes on.
+///
+/// The values are parsed without holding on to their source, so they
+/// can't contain a `RawValue`; reading one fails just like it does for
+/// [`from_slice`](crate::serde::from_slice).
+///
+/// ```
+/// use serde::Deserialize;
+/// use simd_json::serde::StreamDeserializer;
+/// use simd_json::{json, OwnedValue};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Line<'a> {
+///     a: u8,
+///     b: &'a str,
+/// }
+///
+/// let mut d = br#"{"a": 1, "b": "x"}{"a": 2, "b": "y"}
+/// {"a": 3, "b": "z"}"#.to_vec();
+/// let mut stream = StreamDeserializer::<Line>::new(&mut d);
+/// assert_eq!(stream.next().unwrap().unwrap(), Line { a: 1, b: "x" });
+/// assert_eq!(stream.byte_offset(), 18);
+/// let rest: Vec<Line> = stream.collect::<Result<_, _>>().unwrap();
+/// assert_eq!(rest, vec![Line { a: 2, b: "y" }, Line { a: 3, b: "z" }]);
+///
+/// let mut d = b"1 [2] \"three\"\n".to_vec();
+/// let values: Vec<OwnedValue> = StreamDeserializer::new(&mut d)
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+/// assert_eq!(values, vec![json!(1), json!([2]), json!("three")]);
+/// ```
+pub struct StreamDeserializer<'de, T> {
+    rest: &'de mut [u8],
+    rest_start: usize,
+    span: Option<Span>,
+    input_buffer: AlignedBuf,
+    string_buffer: Vec<u8>,
+    _marker: PhantomData<T>,
+}
+
+impl<'de, T> StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    /// Creates a stream over the values in `input`, the slice is
+    /// rewritten while the values are read.
+    #[must_use]
+    pub fn new(input: &'de mut [u8]) -> Self {
+        let len = input.len();
+        Self {
+            rest: input,
+            rest_start: 0,
+            span: None,
+            input_buffer: AlignedBuf::with_capacity(len + SIMDJSON_PADDING * 2),
+            string_buffer: vec![0; len + SIMDJSON_PADDING],
+            _marker: PhantomData,
+        }
+    }
+
+    /// The offset just past the last value that was read, this is where
+    /// the next value, or whitespace before it, starts.
+    #[must_use]
+    pub fn byte_offset(&self) -> usize {
+        self.span.map_or(0, |s| s.end)
+    }
+
+    /// The span of the last value that was read.
+    #[must_use]
+    pub fn span(&self) -> Option<Span> {
+        self.span
+    }
+}
+
+impl<'de, T> Iterator for StreamDeserializer<'de, T>
+where
+    T: Deserialize<'de>,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let span = match next_span(self.rest) {
+            Ok(Some(span)) => span,
+            Ok(None) => return None,
+            Err(e) => {
+                // there is no telling where the next value starts
+                self.rest = &mut [];
+                let index = e.index() + self.rest_start;
+                return Some(Err(e.with_index(index)));
+            }
+        };
+        let (_, tail) = std::mem::take(&mut self.rest).split_at_mut(span.start);
+        let (value, tail) = tail.split_at_mut(span.len());
+        self.rest = tail;
+        let span = Span {
+            start: self.rest_start + span.start,
+            end: self.rest_start + span.end,
+        };
+        self.rest_start = span.end;
+        self.span = Some(span);
+
+        // parse errors point into the value, errors from `T` don't point
+        // into the input at all so they are left alone
+        let mut de = match Deserializer::from_slice_with_buffers(
+            value,
+            &mut self.input_buffer,
+            &mut self.string_buffer,
+        ) {
+            Ok(de) => de,
+            Err(e) => {
+                let index = e.index() + span.start;
+                return Some(Err(e.with_index(index)));
+            }
+        };
+        Some(T::deserialize(&mut de))
+    }
+}
+
+fn is_whitespace(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+/// Finds the span of the first value in `input`, `None` if there is only
+/// whitespace left. The value itself isn't checked, that is left to the
+/// parser, but a closing bracket or a separator where a value should
+/// start is an error.
+fn next_span(input: &[u8]) -> Result<Option<Span>> {
+    let start = match input.iter().position(|c| !is_whitespace(*c)) {
+        Some(start) => start,
+        None => return Ok(None),
+    };
+    let end = match input[start] {
+        c @ (b'}' | b']' | b',' | b':') => {
+            return Err(Error::new(start, c as char, ErrorType::Syntax));
+        }
+        b'{' | b'[' => container_end(input, start),
+        b'"' => string_end(input, start),
+        _ => input[start..]
+            .iter()
+            .position(|c| {
+                is_whitespace(*c) || matches!(*c, b'{' | b'}' | b'[' | b']' | b'"' | b',' | b':')
+            })
+            .map_or(input.len(), |len| start + len),
+    };
+    Ok(Some(Span { start, end }))
+}
+
+/// The end of the string starting at `start`, or the end of the input if
+/// it is never closed.
+fn string_end(input: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < input.len() {
+        match input[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    input.len()
+}
+
+/// The end of the object or array starting at `start`, or the end of the
+/// input if it is never closed. Brackets are only counted, whether they
+/// match is up to the parser.
+fn container_end(input: &[u8], start: usize) -> usize {
+    let mut depth = 0_usize;
+    let mut i = start;
+    while i < input.len() {
+        match input[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            b'"' => {
+                i = string_end(input, i);
+                continue;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    input.len()
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::StreamDeserializer;
+    use crate::{json, OwnedValue, Span};
+
+    #[test]
+    fn concatenated() {
+        let mut d = br#"{"a":1}{"a":2}[3]"four"5 true null"#.to_vec();
+        let values: Vec<OwnedValue> = StreamDeserializer::new(&mut d)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                json!({"a": 1}),
+                json!({"a": 2}),
+                json!([3]),
+                json!("four"),
+                json!(5),
+                json!(true),
+                json!(null)
+            ]
+        );
+    }
+
+    #[test]
+    fn offsets() {
+        let mut d = b" 1\n  [2, 3]  \n{\"a\\n\": \"b\"}\n".to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert_eq!(stream.byte_offset(), 0);
+        assert_eq!(stream.next().unwrap().unwrap(), json!(1));
+        assert_eq!(stream.span(), Some(Span { start: 1, end: 2 }));
+        assert_eq!(stream.next().unwrap().unwrap(), json!([2, 3]));
+        assert_eq!(stream.span(), Some(Span { start: 5, end: 11 }));
+        assert_eq!(stream.next().unwrap().unwrap(), json!({"a\n": "b"}));
+        assert_eq!(stream.span(), Some(Span { start: 14, end: 26 }));
+        assert_eq!(stream.byte_offset(), 26);
+        assert!(stream.next().is_none());
+    }
+
+    #[test]
+    fn empty() {
+        let mut d = b"".to_vec();
+        assert!(StreamDeserializer::<OwnedValue>::new(&mut d)
+            .next()
+            .is_none());
+        let mut d = b" \n\t ".to_vec();
+        assert!(StreamDeserializer::<OwnedValue>::new(&mut d)
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn errors() {
+        // a bad value is reported with its offset and the stream goes on
+        let mut d = br#"{"a": 1} {"a": nope} {"a": 3}"#.to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert!(stream.next().unwrap().is_ok());
+        let e = stream.next().unwrap().unwrap_err();
+        assert!(e.index() >= 9);
+        assert_eq!(stream.next().unwrap().unwrap(), json!({"a": 3}));
+        assert!(stream.next().is_none());
+
+        // unbalanced input stops it
+        let mut d = b"[1] 2] [3]".to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert_eq!(stream.next().unwrap().unwrap(), json!([1]));
+        assert_eq!(stream.next().unwrap().unwrap(), json!(2));
+        let e = stream.next().unwrap().unwrap_err();
+        assert_eq!(e.index(), 5);
+        assert!(stream.next().is_none());
+
+        let mut d = br#"[1, "open"#.to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+
+        let mut d = br#"{"a": 1}"#.to_vec();
+        let e = StreamDeserializer::<u8>::new(&mut d)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(format!("{:?}", e).contains("ExpectedUnsigned"));
+
+        // only parse errors are moved to the offset of the value
+        let mut d = b"1 [2, x]".to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().unwrap_err().index() >= 2);
+        let mut d = b"1 {\"a\": 1}".to_vec();
+        let mut stream = StreamDeserializer::<u8>::new(&mut d);
+        assert!(stream.next().unwrap().is_ok());
+        let e = stream.next().unwrap().unwrap_err();
+        assert!(format!("{:?}", e).contains("ExpectedUnsigned"));
+        assert_eq!(e.index(), 0);
+    }
+
+    #[test]
+    fn raw_values() {
+        let mut d = br#"{"a": 1}"#.to_vec();
+        let e = StreamDeserializer::<Box<crate::serde::RawValue>>::new(&mut d)
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(e.to_string().contains("from_slice_with_raw"));
+    }
+
+    #[test]
+    fn lazy() {
+        // values before a broken one are read even if it can't be scanned
+        let mut d = b"1 {\"a\": [2]} \"open".to_vec();
+        let mut stream = StreamDeserializer::<OwnedValue>::new(&mut d);
+        assert_eq!(stream.next().unwrap().unwrap(), json!(1));
+        assert_eq!(stream.next().unwrap().unwrap(), json!({"a": [2]}));
+        assert!(stream.next().unwrap().is_err());
+        assert_eq!(stream.span(), Some(Span { start: 13, end: 18 }));
+        assert!(stream.next().is_none());
+
+        // brackets and quotes in strings don't count
+        let mut d = br#"["]", "\"[{"] {"}": 1}"#.to_vec();
+        let values: Vec<OwnedValue> = StreamDeserializer::new(&mut d)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, vec![json!(["]", "\"[{"]), json!({"}": 1})]);
+    }
+
+    #[test]
+    fn typed() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Point<'a> {
+            x: i8,
+            name: &'a str,
+        }
+        let mut d = b"{\"x\": 1, \"name\": \"a\"}\n{\"x\": -1, \"name\": \"b\"}\n".to_vec();
+        let points: Vec<Point> = StreamDeserializer::new(&mut d)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            points,
+            vec![Point { x: 1, name: "a" }, Point { x: -1, name: "b" }]
+        );
+    }
+}
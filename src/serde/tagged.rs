@@ -0,0 +1,613 @@
+//! Internally and adjacently tagged enums read straight from the tape.
+//!
+//! Serde's derived code for `#[serde(tag = "...")]` buffers the whole
+//! object before it knows the variant. Our deserializer has the complete
+//! tape up front, so [`Tagged`] looks the tag up with `skip_value` and
+//! hands the object to the variant right away. Other deserializers go
+//! through a buffer, just like serde does.
+
+use crate::{stry, Deserializer, Error, ErrorType, Node, Result};
+use serde::de::value::{BorrowedStrDeserializer, EnumAccessDeserializer, MapAccessDeserializer};
+use serde::de::{
+    self, Deserialize, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, VariantAccess,
+    Visitor,
+};
+use serde_ext::forward_to_deserialize_any;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The name of the struct [`Tagged`] deserializes as, our deserializer
+/// uses it to read the tag from the tape.
+pub(crate) const TOKEN: &str = "$simd_json::private::Tagged";
+
+/// Names the keys of an internally or adjacently tagged enum.
+///
+/// The enum itself derives `Deserialize` without a `tag` attribute, as
+/// if it was externally tagged, and is read as a [`Tagged`] or with
+/// [`tagged`] in `deserialize_with`.
+///
+/// ```
+/// use serde::Deserialize;
+/// use simd_json::serde::{Tagged, TaggedEnum};
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// enum Event {
+///     Click { x: u64, y: u64 },
+///     Key { code: u32 },
+/// }
+///
+/// impl TaggedEnum for Event {
+///     const TAG: &'static str = "type";
+/// }
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// enum Adjacent {
+///     Point(u64, u64),
+///     Empty,
+/// }
+///
+/// impl TaggedEnum for Adjacent {
+///     const TAG: &'static str = "t";
+///     const CONTENT: Option<&'static str> = Some("c");
+/// }
+///
+/// #[derive(Deserialize, Debug, PartialEq)]
+/// struct Log {
+///     #[serde(deserialize_with = "simd_json::serde::tagged")]
+///     last: Event,
+///     events: Vec<Tagged<Event>>,
+/// }
+///
+/// let mut d = br#"{"last": {"type": "Key", "code": 13},
+///     "events": [{"x": 1, "type": "Click", "y": 2}]}"#.to_vec();
+/// let log: Log = simd_json::serde::from_slice(&mut d).unwrap();
+/// assert_eq!(log.last, Event::Key { code: 13 });
+/// assert_eq!(log.events, vec![Tagged(Event::Click { x: 1, y: 2 })]);
+///
+/// let mut d = br#"[{"c": [1, 2], "t": "Point"}, {"t": "Empty"}]"#.to_vec();
+/// let adjacent: Vec<Tagged<Adjacent>> = simd_json::serde::from_slice(&mut d).unwrap();
+/// assert_eq!(adjacent, vec![Tagged(Adjacent::Point(1, 2)), Tagged(Adjacent::Empty)]);
+/// ```
+pub trait TaggedEnum {
+    /// The key of the variant name
+    const TAG: &'static str;
+    /// The key of the content for adjacently tagged enums, `None` for
+    /// internally tagged ones that keep the fields next to the tag
+    const CONTENT: Option<&'static str> = None;
+    #[doc(hidden)]
+    const KEYS: &'static [&'static str] = match Self::CONTENT {
+        Some(content) => &[Self::TAG, content],
+        None => &[Self::TAG],
+    };
+}
+
+/// Deserializes an internally or adjacently tagged enum, the keys are
+/// named by its [`TaggedEnum`] implementation.
+///
+/// Deserializers other than ours buffer the object like serde does for
+/// `#[serde(tag = "...")]`, so the enum can't borrow from their input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Tagged<T>(pub T);
+
+impl<T> Tagged<T> {
+    /// Unwraps the enum
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Tagged<T>
+where
+    T: TaggedEnum + Deserialize<'de>,
+{
+    #[inline]
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        tagged(deserializer).map(Tagged)
+    }
+}
+
+/// Deserializes an internally or adjacently tagged enum, for use with
+/// `#[serde(deserialize_with = "simd_json::serde::tagged")]`. See
+/// [`TaggedEnum`].
+///
+/// # Errors
+///
+/// Fails if the value isn't an object, the tag is missing or not a
+/// string, or the variant doesn't deserialize.
+#[inline]
+pub fn tagged<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: de::Deserializer<'de>,
+    T: TaggedEnum + Deserialize<'de>,
+{
+    deserializer.deserialize_struct(
+        TOKEN,
+        T::KEYS,
+        TaggedVisitor {
+            marker: PhantomData,
+        },
+    )
+}
+
+struct TaggedVisitor<T> {
+    marker: PhantomData<T>,
+}
+
+impl<'de, T> Visitor<'de> for TaggedVisitor<T>
+where
+    T: TaggedEnum + Deserialize<'de>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "an object tagged with `{}`", T::TAG)
+    }
+
+    // our deserializer found the variant on the tape
+    #[inline]
+    fn visit_enum<A>(self, data: A) -> std::result::Result<T, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        T::deserialize(EnumAccessDeserializer::new(data))
+    }
+
+    // everyone else hands us the object, so it has to be buffered
+    fn visit_map<A>(self, map: A) -> std::result::Result<T, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut fields = stry!(serde_json::Map::deserialize(MapAccessDeserializer::new(
+            map
+        )));
+        let variant = match fields.remove(T::TAG) {
+            Some(serde_json::Value::String(variant)) => variant,
+            Some(_) => return Err(de::Error::custom(format!("`{}` is not a string", T::TAG))),
+            None => return Err(de::Error::missing_field(T::TAG)),
+        };
+        let content = match T::CONTENT {
+            Some(content) => fields.remove(content),
+            None => Some(serde_json::Value::Object(fields)),
+        };
+        T::deserialize(EnumAccessDeserializer::new(BufferedEnum {
+            variant,
+            content,
+            adjacent: T::CONTENT.is_some(),
+        }))
+        .map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    /// Deserializes the object at the cursor as a tagged enum, `keys` are
+    /// the tag key and the content key of adjacently tagged enums.
+    pub(crate) fn deserialize_tagged<V>(
+        &mut self,
+        keys: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let tag = keys.first().copied().unwrap_or_default();
+        let content = keys.get(1).copied();
+        let (len, end) = match stry!(self.next_node()) {
+            Node::Object(len, end) => (len, end),
+            _ => return Err(Self::error(ErrorType::ExpectedMap)),
+        };
+        let start = self.idx;
+
+        // Look ahead for the tag and content keys, skipping the values in
+        // between, then go back to the start of the object.
+        let mut variant = None;
+        let mut content_idx = None;
+        for _ in 0..len {
+            let key = stry!(self.next_node());
+            let value = self.idx + 1;
+            match key {
+                Node::String(k) if k == tag => match stry!(self.peek_node()) {
+                    Node::String(s) => variant = Some(s),
+                    _ => return Err(Self::error(ErrorType::ExpectedString)),
+                },
+                Node::String(k) if Some(k) == content => content_idx = Some(value),
+                _ => (),
+            }
+            stry!(self.skip_value());
+        }
+        let variant = stry!(variant.ok_or_else(|| missing_field(tag)));
+        self.idx = start;
+
+        visitor.visit_enum(TapeEnum {
+            de: self,
+            variant,
+            tag,
+            content,
+            content_idx,
+            len,
+            end,
+        })
+    }
+}
+
+fn missing_field(field: &str) -> Error {
+    Deserializer::error(ErrorType::Serde(format!("missing field `{}`", field)))
+}
+
+struct TapeEnum<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    variant: &'de str,
+    tag: &'static str,
+    content: Option<&'static str>,
+    content_idx: Option<usize>,
+    len: usize,
+    end: usize,
+}
+
+impl<'a, 'de> TapeEnum<'a, 'de> {
+    /// Moves the cursor onto the content of an adjacently tagged enum,
+    /// runs `f` and moves the cursor past the whole object.
+    fn with_content<T, F>(self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Deserializer<'de>) -> Result<T>,
+    {
+        let content = self.content.unwrap_or_default();
+        let idx = stry!(self.content_idx.ok_or_else(|| missing_field(content)));
+        self.de.idx = idx - 1;
+        let r = stry!(f(self.de));
+        self.de.idx = self.end - 1;
+        Ok(r)
+    }
+
+    /// Hands the fields of an internally tagged object to `f` and moves
+    /// the cursor past the whole object.
+    fn with_fields<T, F>(self, f: F) -> Result<T>
+    where
+        F: FnOnce(TapeFields<'_, 'de>) -> Result<T>,
+    {
+        let r = stry!(f(TapeFields {
+            de: &mut *self.de,
+            tag: self.tag,
+            len: self.len,
+        }));
+        self.de.idx = self.end - 1;
+        Ok(r)
+    }
+}
+
+impl<'a, 'de> EnumAccess<'de> for TapeEnum<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = BorrowedStrDeserializer::<Error>::new(self.variant);
+        Ok((stry!(seed.deserialize(variant)), self))
+    }
+}
+
+impl<'a, 'de> VariantAccess<'de> for TapeEnum<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        self.de.idx = self.end - 1;
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.content.is_some() {
+            self.with_content(|de| seed.deserialize(de))
+        } else {
+            self.with_fields(|fields| seed.deserialize(TapeContent(fields)))
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.content.is_some() {
+            self.with_content(|de| de::Deserializer::deserialize_seq(de, visitor))
+        } else {
+            Err(Deserializer::error(ErrorType::Serde(
+                "tuple variants can not be internally tagged".to_string(),
+            )))
+        }
+    }
+
+    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.content.is_some() {
+            self.with_content(|de| de::Deserializer::deserialize_map(de, visitor))
+        } else {
+            self.with_fields(|fields| visitor.visit_map(fields))
+        }
+    }
+}
+
+/// The fields of an internally tagged object, without the tag.
+struct TapeFields<'a, 'de: 'a> {
+    de: &'a mut Deserializer<'de>,
+    tag: &'static str,
+    len: usize,
+}
+
+impl<'de, 'a> MapAccess<'de> for TapeFields<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        while self.len > 0 {
+            self.len -= 1;
+            if let Node::String(k) = stry!(self.de.peek_node()) {
+                if k == self.tag {
+                    self.de.idx += 1;
+                    stry!(self.de.skip_value());
+                    continue;
+                }
+            }
+            return seed.deserialize(&mut *self.de).map(Some);
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// Presents the fields of an internally tagged object as a map to the
+/// content of a newtype variant.
+struct TapeContent<'a, 'de: 'a>(TapeFields<'a, 'de>);
+
+impl<'a, 'de> de::Deserializer<'de> for TapeContent<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(self.0)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // the cursor is moved past the object once the variant is done
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier
+    }
+}
+
+/// A tagged enum buffered by a deserializer that isn't ours, `content`
+/// holds the other fields of an internally tagged enum or the content of
+/// an adjacently tagged one.
+struct BufferedEnum {
+    variant: String,
+    content: Option<serde_json::Value>,
+    adjacent: bool,
+}
+
+impl<'de> EnumAccess<'de> for BufferedEnum {
+    type Error = serde_json::Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> serde_json::Result<(V::Value, Self)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant.as_str().into_deserializer());
+        Ok((stry!(variant), self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for BufferedEnum {
+    type Error = serde_json::Error;
+
+    fn unit_variant(self) -> serde_json::Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> serde_json::Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(stry!(self.content()))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> serde_json::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        if self.adjacent {
+            de::Deserializer::deserialize_seq(stry!(self.content()), visitor)
+        } else {
+            Err(de::Error::custom(
+                "tuple variants can not be internally tagged",
+            ))
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_map(stry!(self.content()), visitor)
+    }
+}
+
+impl BufferedEnum {
+    fn content(self) -> serde_json::Result<serde_json::Value> {
+        self.content
+            .ok_or_else(|| de::Error::custom("missing content of the tagged enum"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::{tagged, Tagged, TaggedEnum};
+    use serde_ext::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Inner {
+        a: Vec<u8>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Message {
+        Request { id: usize, method: String },
+        Response { id: String, result: Inner },
+        Wrapped(Inner),
+        Unit,
+    }
+
+    impl TaggedEnum for Message {
+        const TAG: &'static str = "type";
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Adjacent {
+        Request { id: usize, method: String },
+        Point(u8, u8),
+        Wrapped(String),
+        Unit,
+    }
+
+    impl TaggedEnum for Adjacent {
+        const TAG: &'static str = "t";
+        const CONTENT: Option<&'static str> = Some("c");
+    }
+
+    const MESSAGES: &str = r#"[
+        {"id": 1, "type": "Request", "params": {"type": "Unit"}, "method": "snot"},
+        {"type": "Response", "id": "1", "result": {"a": [1, 2]}},
+        {"a": [3], "type": "Wrapped"},
+        {"type": "Unit", "data": [{}]}
+    ]"#;
+
+    const ADJACENT: &str = r#"[
+        {"c": {"method": "snot", "id": 1}, "t": "Request"},
+        {"t": "Point", "c": [1, 2]},
+        {"t": "Wrapped", "x": [], "c": "badger"},
+        {"t": "Unit"}
+    ]"#;
+
+    fn messages() -> Vec<Tagged<Message>> {
+        vec![
+            Tagged(Message::Request {
+                id: 1,
+                method: "snot".to_string(),
+            }),
+            Tagged(Message::Response {
+                id: "1".to_string(),
+                result: Inner { a: vec![1, 2] },
+            }),
+            Tagged(Message::Wrapped(Inner { a: vec![3] })),
+            Tagged(Message::Unit),
+        ]
+    }
+
+    fn adjacent() -> Vec<Tagged<Adjacent>> {
+        vec![
+            Tagged(Adjacent::Request {
+                id: 1,
+                method: "snot".to_string(),
+            }),
+            Tagged(Adjacent::Point(1, 2)),
+            Tagged(Adjacent::Wrapped("badger".to_string())),
+            Tagged(Adjacent::Unit),
+        ]
+    }
+
+    #[test]
+    fn internally_tagged() {
+        let mut json = MESSAGES.as_bytes().to_vec();
+        let ms: Vec<Tagged<Message>> = crate::serde::from_slice(&mut json).unwrap();
+        assert_eq!(ms, messages());
+
+        let mut json = br#"{"id": 1, "method": "snot"}"#.to_vec();
+        assert!(crate::serde::from_slice::<Tagged<Message>>(&mut json).is_err());
+        let mut json = br#"{"type": "Nope"}"#.to_vec();
+        assert!(crate::serde::from_slice::<Tagged<Message>>(&mut json).is_err());
+        let mut json = br#"{"type": 1}"#.to_vec();
+        assert!(crate::serde::from_slice::<Tagged<Message>>(&mut json).is_err());
+        let mut json = br#"["Unit"]"#.to_vec();
+        assert!(crate::serde::from_slice::<Tagged<Message>>(&mut json).is_err());
+    }
+
+    #[test]
+    fn adjacently_tagged() {
+        let mut json = ADJACENT.as_bytes().to_vec();
+        let ms: Vec<Tagged<Adjacent>> = crate::serde::from_slice(&mut json).unwrap();
+        assert_eq!(ms, adjacent());
+
+        let mut json = br#"{"t": "Point"}"#.to_vec();
+        assert!(crate::serde::from_slice::<Tagged<Adjacent>>(&mut json).is_err());
+    }
+
+    #[test]
+    fn deserialize_with() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Envelope {
+            #[serde(deserialize_with = "tagged")]
+            message: Message,
+            rest: Vec<u8>,
+        }
+
+        let mut json =
+            br#"{"message": {"a": [1], "type": "Wrapped", "b": {}}, "rest": [2]}"#.to_vec();
+        assert!(crate::serde::from_slice::<Envelope>(&mut json).is_err());
+        let mut json = br#"{"message": {"a": [1], "type": "Wrapped"}, "rest": [2]}"#.to_vec();
+        let e: Envelope = crate::serde::from_slice(&mut json).unwrap();
+        assert_eq!(
+            e,
+            Envelope {
+                message: Message::Wrapped(Inner { a: vec![1] }),
+                rest: vec![2]
+            }
+        );
+    }
+
+    #[test]
+    fn buffered() {
+        let ms: Vec<Tagged<Message>> = serde_json::from_str(MESSAGES).unwrap();
+        assert_eq!(ms, messages());
+        let ms: Vec<Tagged<Adjacent>> = serde_json::from_str(ADJACENT).unwrap();
+        assert_eq!(ms, adjacent());
+        let mut json = MESSAGES.as_bytes().to_vec();
+        let v = crate::to_owned_value(&mut json).unwrap();
+        let ms: Vec<Tagged<Message>> = crate::serde::from_owned_value(v).unwrap();
+        assert_eq!(ms, messages());
+
+        assert!(serde_json::from_str::<Tagged<Message>>(r#"{"type": "Nope"}"#).is_err());
+        assert!(serde_json::from_str::<Tagged<Message>>(r#"{"type": 1}"#).is_err());
+        assert!(serde_json::from_str::<Tagged<Adjacent>>(r#"{"t": "Point"}"#).is_err());
+    }
+}
@@ -1,6 +1,8 @@
 // A lot of this logic is a re-implementation or copy of serde_json::Value
 use super::super::shared::MapKeyDeserializer;
-use crate::value::borrowed::{Object, Value};
+use crate::serde::raw::TOKEN;
+use crate::value::borrowed::{HashedObject, HashedValue};
+use crate::value::map;
 use crate::Error;
 use crate::StaticNode;
 use crate::{cow::Cow, stry, ErrorType};
@@ -8,9 +10,15 @@ use serde_ext::de::{
     self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor,
 };
 use serde_ext::forward_to_deserialize_any;
+use std::hash::BuildHasher;
+use std::marker::PhantomData;
 use std::{fmt, slice};
+use value_trait::Writable;
 
-impl<'de> de::Deserializer<'de> for Value<'de> {
+impl<'de, S> de::Deserializer<'de> for HashedValue<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     // Look at the input data to decide what Serde data model type to
@@ -21,17 +29,17 @@ impl<'de> de::Deserializer<'de> for Value<'de> {
         V: Visitor<'de>,
     {
         match self {
-            Value::Static(StaticNode::Null) => visitor.visit_unit(),
-            Value::Static(StaticNode::Bool(b)) => visitor.visit_bool(b),
+            HashedValue::Static(StaticNode::Null) => visitor.visit_unit(),
+            HashedValue::Static(StaticNode::Bool(b)) => visitor.visit_bool(b),
             Self::Static(StaticNode::I64(n)) => visitor.visit_i64(n),
             #[cfg(feature = "128bit")]
             Self::Static(StaticNode::I128(n)) => visitor.visit_i128(n),
             Self::Static(StaticNode::U64(n)) => visitor.visit_u64(n),
             #[cfg(feature = "128bit")]
             Self::Static(StaticNode::U128(n)) => visitor.visit_u128(n),
-            Value::Static(StaticNode::F64(n)) => visitor.visit_f64(n),
+            HashedValue::Static(StaticNode::F64(n)) => visitor.visit_f64(n),
             #[cfg(feature = "beef")]
-            Value::String(s) => {
+            HashedValue::String(s) => {
                 if s.is_borrowed() {
                     visitor.visit_borrowed_str(s.unwrap_borrowed())
                 } else {
@@ -39,15 +47,16 @@ impl<'de> de::Deserializer<'de> for Value<'de> {
                 }
             }
             #[cfg(not(feature = "beef"))]
-            Value::String(s) => match s {
+            HashedValue::String(s) => match s {
                 Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
                 Cow::Owned(s) => visitor.visit_string(s),
             },
 
-            Value::Array(a) => visitor.visit_seq(Array(a.iter())),
-            Value::Object(o) => visitor.visit_map(ObjectAccess {
+            HashedValue::Array(a) => visitor.visit_seq(Array(a.iter(), 0)),
+            HashedValue::Object(o) => visitor.visit_map(ObjectAccess {
                 i: o.iter(),
-                v: &Value::Static(StaticNode::Null),
+                k: "",
+                v: &HashedValue::Static(StaticNode::Null),
             }),
         }
     }
@@ -76,27 +85,46 @@ impl<'de> de::Deserializer<'de> for Value<'de> {
     {
         match self {
             // Give the visitor access to each element of the sequence.
-            Value::Array(a) => visitor.visit_seq(Array(a.iter())),
-            Value::Object(o) => visitor.visit_map(ObjectAccess {
+            HashedValue::Array(a) => visitor.visit_seq(Array(a.iter(), 0)),
+            HashedValue::Object(o) => visitor.visit_map(ObjectAccess {
                 i: o.iter(),
-                v: &Value::Static(StaticNode::Null),
+                k: "",
+                v: &HashedValue::Static(StaticNode::Null),
             }),
             _ => Err(crate::Deserializer::error(ErrorType::ExpectedMap)),
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == TOKEN {
+            visitor.visit_string(self.encode())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            bytes byte_buf unit unit_struct seq tuple
             tuple_struct map enum identifier ignored_any
     }
 }
 
-struct Array<'de, 'value: 'de>(std::slice::Iter<'de, Value<'value>>);
+struct Array<'de, 'value: 'de, S>(std::slice::Iter<'de, HashedValue<'value, S>>, usize);
 
 // `SeqAccess` is provided to the `Visitor` to give it the ability to iterate
 // through elements of the sequence.
-impl<'de, 'value> SeqAccess<'de> for Array<'value, 'de> {
+impl<'de, 'value, S> SeqAccess<'de> for Array<'value, 'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
@@ -104,20 +132,28 @@ impl<'de, 'value> SeqAccess<'de> for Array<'value, 'de> {
         T: DeserializeSeed<'de>,
     {
         //TODO: This is ugly
-        self.0
-            .next()
-            .map_or(Ok(None), |v| seed.deserialize(v.clone()).map(Some))
+        let index = self.1;
+        self.1 += 1;
+        self.0.next().map_or(Ok(None), |v| {
+            seed.deserialize(v.clone())
+                .map(Some)
+                .map_err(|e| e.in_element(index))
+        })
     }
 }
 
-struct ObjectAccess<'de, 'value: 'de> {
-    i: halfbrown::Iter<'de, Cow<'value, str>, Value<'value>>,
-    v: &'de Value<'value>,
+struct ObjectAccess<'de, 'value: 'de, S> {
+    i: <&'de HashedObject<'value, S> as IntoIterator>::IntoIter,
+    k: &'de str,
+    v: &'de HashedValue<'value, S>,
 }
 
 // `MapAccess` is provided to the `Visitor` to give it the ability to iterate
 // through entries of the map.
-impl<'de, 'value> MapAccess<'de> for ObjectAccess<'value, 'de> {
+impl<'de, 'value, S> MapAccess<'de> for ObjectAccess<'value, 'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
@@ -125,8 +161,10 @@ impl<'de, 'value> MapAccess<'de> for ObjectAccess<'value, 'de> {
         K: DeserializeSeed<'de>,
     {
         if let Some((k, v)) = self.i.next() {
+            self.k = k;
             self.v = v;
-            seed.deserialize(Value::String(k.clone())).map(Some)
+            seed.deserialize(HashedValue::<S>::String(k.clone()))
+                .map(Some)
         } else {
             Ok(None)
         }
@@ -138,22 +176,29 @@ impl<'de, 'value> MapAccess<'de> for ObjectAccess<'value, 'de> {
     {
         //TODO: This is ugly
         seed.deserialize(self.v.clone())
+            .map_err(|e| e.in_key(self.k))
     }
 }
 
-impl<'de> Deserialize<'de> for Value<'de> {
-    fn deserialize<D>(deserializer: D) -> Result<Value<'de>, D::Error>
+impl<'de, S> Deserialize<'de> for HashedValue<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn deserialize<D>(deserializer: D) -> Result<HashedValue<'de, S>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_any(ValueVisitor(PhantomData))
     }
 }
 
-struct ValueVisitor;
+struct ValueVisitor<S>(PhantomData<S>);
 
-impl<'de> Visitor<'de> for ValueVisitor {
-    type Value = Value<'de>;
+impl<'de, S> Visitor<'de> for ValueVisitor<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Value = HashedValue<'de, S>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("an JSONesque value")
@@ -162,19 +207,19 @@ impl<'de> Visitor<'de> for ValueVisitor {
     /****************** unit ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_unit<E>(self) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Null))
+        Ok(HashedValue::Static(StaticNode::Null))
     }
 
     /****************** bool ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Bool(value)))
+        Ok(HashedValue::Static(StaticNode::Bool(value)))
     }
 
     /****************** Option ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_none<E>(self) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Null))
+        Ok(HashedValue::Static(StaticNode::Null))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -199,7 +244,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -207,7 +252,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -215,7 +260,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -223,7 +268,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(value)))
+        Ok(HashedValue::Static(StaticNode::I64(value)))
     }
 
     #[cfg(feature = "128bit")]
@@ -232,7 +277,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I128(value)))
+        Ok(HashedValue::Static(StaticNode::I128(value)))
     }
 
     /****************** u64 ******************/
@@ -242,7 +287,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -250,7 +295,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -258,7 +303,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -266,7 +311,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(value)))
+        Ok(HashedValue::Static(StaticNode::U64(value)))
     }
 
     #[cfg(feature = "128bit")]
@@ -275,7 +320,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U128(value)))
+        Ok(HashedValue::Static(StaticNode::U128(value)))
     }
 
     /****************** f64 ******************/
@@ -285,7 +330,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::F64(f64::from(value))))
+        Ok(HashedValue::Static(StaticNode::F64(f64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -293,7 +338,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::F64(value)))
+        Ok(HashedValue::Static(StaticNode::F64(value)))
     }
 
     /****************** stringy stuff ******************/
@@ -302,7 +347,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::from(value.to_string()))
+        Ok(HashedValue::from(value.to_string()))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -310,7 +355,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::from(value))
+        Ok(HashedValue::from(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -318,7 +363,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value.to_owned().into()))
+        Ok(HashedValue::String(value.to_owned().into()))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -326,7 +371,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value.into()))
+        Ok(HashedValue::String(value.into()))
     }
 
     /****************** byte stuff ******************/
@@ -337,7 +382,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value))
+        Ok(HashedValue::String(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -346,7 +391,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     'a: 'de
         E: de::Error,
     {
-      Ok(Value::String(value))
+      Ok(HashedValue::String(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -354,7 +399,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-      Ok(Value::String(&value))
+      Ok(HashedValue::String(&value))
     }
      */
     /****************** nexted stuff ******************/
@@ -366,12 +411,12 @@ impl<'de> Visitor<'de> for ValueVisitor {
     {
         let size = map.size_hint().unwrap_or_default();
 
-        let mut m = Object::with_capacity(size);
+        let mut m = map::with_capacity(size);
         while let Some(k) = map.next_key::<&str>()? {
             let v = map.next_value()?;
             m.insert(k.into(), v);
         }
-        Ok(Value::from(m))
+        Ok(HashedValue::from(m))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -385,13 +430,17 @@ impl<'de> Visitor<'de> for ValueVisitor {
         while let Some(e) = seq.next_element()? {
             v.push(e);
         }
-        Ok(Value::Array(v))
+        Ok(HashedValue::Array(v))
     }
 }
 
-fn visit_array_ref<'de, V>(array: &'de [Value<'de>], visitor: V) -> Result<V::Value, Error>
+fn visit_array_ref<'de, V, S>(
+    array: &'de [HashedValue<'de, S>],
+    visitor: V,
+) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = array.len();
     let mut deserializer = SeqRefDeserializer::new(array);
@@ -407,9 +456,13 @@ where
     }
 }
 
-fn visit_object_ref<'de, V>(object: &'de Object<'de>, visitor: V) -> Result<V::Value, Error>
+fn visit_object_ref<'de, V, S>(
+    object: &'de HashedObject<'de, S>,
+    visitor: V,
+) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = object.len();
     let mut deserializer = MapRefDeserializer::new(object);
@@ -424,7 +477,10 @@ where
     }
 }
 
-impl<'de> de::Deserializer<'de> for &'de Value<'de> {
+impl<'de, S> de::Deserializer<'de> for &'de HashedValue<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     // Look at the input data to decide what Serde data model type to
@@ -435,18 +491,18 @@ impl<'de> de::Deserializer<'de> for &'de Value<'de> {
         V: Visitor<'de>,
     {
         match self {
-            Value::Static(StaticNode::Null) => visitor.visit_unit(),
-            Value::Static(StaticNode::Bool(b)) => visitor.visit_bool(*b),
-            Value::Static(StaticNode::I64(n)) => visitor.visit_i64(*n),
+            HashedValue::Static(StaticNode::Null) => visitor.visit_unit(),
+            HashedValue::Static(StaticNode::Bool(b)) => visitor.visit_bool(*b),
+            HashedValue::Static(StaticNode::I64(n)) => visitor.visit_i64(*n),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::I128(n)) => visitor.visit_i128(*n),
-            Value::Static(StaticNode::U64(n)) => visitor.visit_u64(*n),
+            HashedValue::Static(StaticNode::I128(n)) => visitor.visit_i128(*n),
+            HashedValue::Static(StaticNode::U64(n)) => visitor.visit_u64(*n),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::U128(n)) => visitor.visit_u128(*n),
-            Value::Static(StaticNode::F64(n)) => visitor.visit_f64(*n),
-            Value::String(ref s) => visitor.visit_borrowed_str(s),
-            Value::Array(ref a) => visit_array_ref(a, visitor),
-            Value::Object(ref o) => visit_object_ref(o, visitor),
+            HashedValue::Static(StaticNode::U128(n)) => visitor.visit_u128(*n),
+            HashedValue::Static(StaticNode::F64(n)) => visitor.visit_f64(*n),
+            HashedValue::String(ref s) => visitor.visit_borrowed_str(s),
+            HashedValue::Array(ref a) => visit_array_ref(a, visitor),
+            HashedValue::Object(ref o) => visit_object_ref(o, visitor),
         }
     }
 
@@ -455,7 +511,7 @@ impl<'de> de::Deserializer<'de> for &'de Value<'de> {
     where
         V: Visitor<'de>,
     {
-        if self == &Value::Static(StaticNode::Null) {
+        if self == &HashedValue::Static(StaticNode::Null) {
             visitor.visit_unit()
         } else {
             visitor.visit_some(self)
@@ -474,30 +530,55 @@ impl<'de> de::Deserializer<'de> for &'de Value<'de> {
     {
         match self {
             // Give the visitor access to each element of the sequence.
-            Value::Array(ref a) => visit_array_ref(a, visitor),
-            Value::Object(ref o) => visit_object_ref(o, visitor),
+            HashedValue::Array(ref a) => visit_array_ref(a, visitor),
+            HashedValue::Object(ref o) => visit_object_ref(o, visitor),
             _ => Err(crate::Deserializer::error(ErrorType::ExpectedMap)),
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == TOKEN {
+            visitor.visit_string(self.encode())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            bytes byte_buf unit unit_struct seq tuple
             tuple_struct map enum identifier ignored_any
     }
 }
 
-struct SeqRefDeserializer<'de> {
-    iter: slice::Iter<'de, Value<'de>>,
+struct SeqRefDeserializer<'de, S> {
+    iter: slice::Iter<'de, HashedValue<'de, S>>,
+    index: usize,
 }
 
-impl<'de> SeqRefDeserializer<'de> {
-    fn new(slice: &'de [Value<'de>]) -> Self {
-        SeqRefDeserializer { iter: slice.iter() }
+impl<'de, S> SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn new(slice: &'de [HashedValue<'de, S>]) -> Self {
+        SeqRefDeserializer {
+            iter: slice.iter(),
+            index: 0,
+        }
     }
 }
 
-impl<'de> serde::Deserializer<'de> for SeqRefDeserializer<'de> {
+impl<'de, S> serde::Deserializer<'de> for SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -529,7 +610,10 @@ impl<'de> serde::Deserializer<'de> for SeqRefDeserializer<'de> {
     }
 }
 
-impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
+impl<'de, S> SeqAccess<'de> for SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
@@ -537,7 +621,13 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value)
+                    .map(Some)
+                    .map_err(|e| e.in_element(index))
+            }
             None => Ok(None),
         }
     }
@@ -550,21 +640,29 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
     }
 }
 
-struct MapRefDeserializer<'de> {
-    iter: <&'de Object<'de> as IntoIterator>::IntoIter,
-    value: Option<&'de Value<'de>>,
+struct MapRefDeserializer<'de, S> {
+    iter: <&'de HashedObject<'de, S> as IntoIterator>::IntoIter,
+    key: &'de str,
+    value: Option<&'de HashedValue<'de, S>>,
 }
 
-impl<'de> MapRefDeserializer<'de> {
-    fn new(map: &'de Object<'de>) -> Self {
+impl<'de, S> MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn new(map: &'de HashedObject<'de, S>) -> Self {
         MapRefDeserializer {
             iter: map.into_iter(),
+            key: "",
             value: None,
         }
     }
 }
 
-impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
+impl<'de, S> MapAccess<'de> for MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
@@ -573,6 +671,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
     {
         match self.iter.next() {
             Some((key, value)) => {
+                self.key = key;
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer {
                     key: Cow::from(&**key),
@@ -588,7 +687,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|e| e.in_key(self.key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -601,7 +700,10 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
     }
 }
 
-impl<'de> serde::Deserializer<'de> for MapRefDeserializer<'de> {
+impl<'de, S> serde::Deserializer<'de> for MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -1,8 +1,9 @@
 use super::to_value;
+use crate::serde::raw::{RawEmitter, TOKEN};
 use crate::StaticNode;
 use crate::{
     cow::Cow,
-    value::borrowed::{Object, Value},
+    value::borrowed::{HashedValue, Object, Value},
 };
 use crate::{stry, Error, ErrorType, Result};
 use serde_ext::ser::{
@@ -11,30 +12,30 @@ use serde_ext::ser::{
 // use serde_json::Map;
 use std::marker::PhantomData;
 
-impl<'value> Serialize for Value<'value> {
+impl<'value, H> Serialize for HashedValue<'value, H> {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: ser::Serializer,
     {
         match self {
-            Value::Static(StaticNode::Null) => serializer.serialize_unit(),
-            Value::Static(StaticNode::Bool(b)) => serializer.serialize_bool(*b),
-            Value::Static(StaticNode::F64(f)) => serializer.serialize_f64(*f),
-            Value::Static(StaticNode::U64(i)) => serializer.serialize_u64(*i),
+            Self::Static(StaticNode::Null) => serializer.serialize_unit(),
+            Self::Static(StaticNode::Bool(b)) => serializer.serialize_bool(*b),
+            Self::Static(StaticNode::F64(f)) => serializer.serialize_f64(*f),
+            Self::Static(StaticNode::U64(i)) => serializer.serialize_u64(*i),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::U128(i)) => serializer.serialize_u128(*i),
-            Value::Static(StaticNode::I64(i)) => serializer.serialize_i64(*i),
+            Self::Static(StaticNode::U128(i)) => serializer.serialize_u128(*i),
+            Self::Static(StaticNode::I64(i)) => serializer.serialize_i64(*i),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::I128(i)) => serializer.serialize_i128(*i),
-            Value::String(s) => serializer.serialize_str(&s),
-            Value::Array(v) => {
+            Self::Static(StaticNode::I128(i)) => serializer.serialize_i128(*i),
+            Self::String(s) => serializer.serialize_str(&s),
+            Self::Array(v) => {
                 let mut seq = serializer.serialize_seq(Some(v.len()))?;
                 for e in v {
                     seq.serialize_element(e)?;
                 }
                 seq.end()
             }
-            Value::Object(m) => {
+            Self::Object(m) => {
                 let mut map = serializer.serialize_map(Some(m.len()))?;
                 for (k, v) in m.iter() {
                     let k: &str = &k;
@@ -179,13 +180,19 @@ impl<'se> serde::Serializer for Serializer<'se> {
     #[inline]
     fn serialize_newtype_struct<T: ?Sized>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Value<'se>>
     where
         T: Serialize,
     {
-        value.serialize(self)
+        if name == TOKEN {
+            let mut json = Vec::new();
+            stry!(value.serialize(RawEmitter(&mut json)));
+            crate::to_owned_value(&mut json).map(Value::from)
+        } else {
+            value.serialize(self)
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
@@ -678,7 +685,7 @@ impl<'se> serde::ser::SerializeStructVariant for SerializeStructVariant<'se> {
 mod test {
     use super::Value;
     use crate::serde::from_slice;
-    use halfbrown::HashMap;
+    use crate::value::borrowed::Object;
     use serde::{Deserialize, Serialize};
     use serde_json;
     use value_trait::StaticNode;
@@ -741,7 +748,7 @@ mod test {
 
     #[test]
     fn map() {
-        let mut m = HashMap::new();
+        let mut m = Object::new();
         m.insert("a".into(), Value::from(42));
         m.insert("b".into(), Value::from(23));
         let v = Value::Object(Box::new(m));
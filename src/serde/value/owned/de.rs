@@ -1,14 +1,22 @@
 // A lot of this logic is a re-implementation or copy of serde_json::Value
 use super::super::shared::MapKeyDeserializer;
-use crate::value::owned::{Object, Value};
+use crate::serde::raw::TOKEN;
+use crate::value::map;
+use crate::value::owned::{HashedObject, HashedValue};
 use crate::StaticNode;
 use crate::{cow::Cow, ErrorType};
 use crate::{stry, Error};
 use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::forward_to_deserialize_any;
+use std::hash::BuildHasher;
+use std::marker::PhantomData;
 use std::{fmt, slice};
+use value_trait::Writable;
 
-impl<'de> de::Deserializer<'de> for Value {
+impl<'de, S> de::Deserializer<'de> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     // Look at the input data to decide what Serde data model type to
@@ -64,16 +72,32 @@ impl<'de> de::Deserializer<'de> for Value {
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == TOKEN {
+            visitor.visit_string(self.encode())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            bytes byte_buf unit unit_struct seq tuple
             tuple_struct map enum identifier ignored_any
     }
 }
 
-fn visit_array<'de, V>(array: Vec<Value>, visitor: V) -> Result<V::Value, Error>
+fn visit_array<'de, V, S>(array: Vec<HashedValue<S>>, visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = array.len();
     let mut deserializer = SeqDeserializer::new(array);
@@ -89,9 +113,10 @@ where
     }
 }
 
-fn visit_object<'de, V>(object: Box<Object>, visitor: V) -> Result<V::Value, Error>
+fn visit_object<'de, V, S>(object: Box<HashedObject<S>>, visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = object.len();
     let mut deserializer = ObjectDeserializer::new(object);
@@ -106,19 +131,27 @@ where
         ))
     }
 }
-struct SeqDeserializer {
-    iter: std::vec::IntoIter<Value>,
+struct SeqDeserializer<S> {
+    iter: std::vec::IntoIter<HashedValue<S>>,
+    index: usize,
 }
 
-impl SeqDeserializer {
-    fn new(vec: Vec<Value>) -> Self {
+impl<S> SeqDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn new(vec: Vec<HashedValue<S>>) -> Self {
         Self {
             iter: vec.into_iter(),
+            index: 0,
         }
     }
 }
 
-impl<'de> serde::Deserializer<'de> for SeqDeserializer {
+impl<'de, S> serde::Deserializer<'de> for SeqDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -150,7 +183,10 @@ impl<'de> serde::Deserializer<'de> for SeqDeserializer {
     }
 }
 
-impl<'de> SeqAccess<'de> for SeqDeserializer {
+impl<'de, S> SeqAccess<'de> for SeqDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
@@ -158,7 +194,13 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value)
+                    .map(Some)
+                    .map_err(|e| e.in_element(index))
+            }
             None => Ok(None),
         }
     }
@@ -171,22 +213,30 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
     }
 }
 
-struct ObjectDeserializer {
-    iter: <Object as IntoIterator>::IntoIter,
-    value: Option<Value>,
+struct ObjectDeserializer<S> {
+    iter: <HashedObject<S> as IntoIterator>::IntoIter,
+    key: String,
+    value: Option<HashedValue<S>>,
 }
 
-impl ObjectDeserializer {
+impl<S> ObjectDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[allow(clippy::boxed_local, clippy::needless_pass_by_value)]
-    fn new(map: Box<Object>) -> Self {
+    fn new(map: Box<HashedObject<S>>) -> Self {
         Self {
             iter: map.into_iter(),
+            key: String::new(),
             value: None,
         }
     }
 }
 
-impl<'de> MapAccess<'de> for ObjectDeserializer {
+impl<'de, S> MapAccess<'de> for ObjectDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
@@ -196,6 +246,8 @@ impl<'de> MapAccess<'de> for ObjectDeserializer {
         match self.iter.next() {
             Some((key, value)) => {
                 self.value = Some(value);
+                // keep the key around in case the value fails
+                self.key.clone_from(&key);
                 let key_de = MapKeyDeserializer {
                     key: Cow::from(key),
                 };
@@ -210,7 +262,7 @@ impl<'de> MapAccess<'de> for ObjectDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|e| e.in_key(&self.key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -223,7 +275,10 @@ impl<'de> MapAccess<'de> for ObjectDeserializer {
     }
 }
 
-impl<'de> serde::Deserializer<'de> for ObjectDeserializer {
+impl<'de, S> serde::Deserializer<'de> for ObjectDeserializer<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -241,19 +296,32 @@ impl<'de> serde::Deserializer<'de> for ObjectDeserializer {
     }
 }
 
-impl<'de> Deserialize<'de> for Value {
+impl<'de, S> Deserialize<'de> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_any(ValueVisitor)
+        deserializer.deserialize_any(ValueVisitor(PhantomData))
+    }
+
+    fn deserialize_in_place<D>(deserializer: D, place: &mut Self) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueInPlaceVisitor(place))
     }
 }
 
-struct ValueVisitor;
+struct ValueVisitor<S>(PhantomData<S>);
 
-impl<'de> Visitor<'de> for ValueVisitor {
-    type Value = Value;
+impl<'de, S> Visitor<'de> for ValueVisitor<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Value = HashedValue<S>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("a JSONesque value")
@@ -262,19 +330,19 @@ impl<'de> Visitor<'de> for ValueVisitor {
     /****************** unit ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_unit<E>(self) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Null))
+        Ok(HashedValue::Static(StaticNode::Null))
     }
 
     /****************** bool ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Bool(value)))
+        Ok(HashedValue::Static(StaticNode::Bool(value)))
     }
 
     /****************** Option ******************/
     #[cfg_attr(not(feature = "no-inline"), inline)]
     fn visit_none<E>(self) -> Result<Self::Value, E> {
-        Ok(Value::Static(StaticNode::Null))
+        Ok(HashedValue::Static(StaticNode::Null))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -299,7 +367,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -307,7 +375,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -315,7 +383,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(i64::from(value))))
+        Ok(HashedValue::Static(StaticNode::I64(i64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -323,7 +391,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I64(value)))
+        Ok(HashedValue::Static(StaticNode::I64(value)))
     }
 
     #[cfg(feature = "128bit")]
@@ -332,7 +400,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::I128(value)))
+        Ok(HashedValue::Static(StaticNode::I128(value)))
     }
 
     /****************** u64 ******************/
@@ -342,7 +410,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -350,7 +418,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -358,7 +426,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(u64::from(value))))
+        Ok(HashedValue::Static(StaticNode::U64(u64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -366,7 +434,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U64(value)))
+        Ok(HashedValue::Static(StaticNode::U64(value)))
     }
 
     #[cfg(feature = "128bit")]
@@ -375,7 +443,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::U128(value)))
+        Ok(HashedValue::Static(StaticNode::U128(value)))
     }
     /****************** f64 ******************/
 
@@ -384,7 +452,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::F64(f64::from(value))))
+        Ok(HashedValue::Static(StaticNode::F64(f64::from(value))))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -392,7 +460,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::Static(StaticNode::F64(value)))
+        Ok(HashedValue::Static(StaticNode::F64(value)))
     }
 
     /****************** stringy stuff ******************/
@@ -401,7 +469,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::from(value.to_string()))
+        Ok(HashedValue::from(value.to_string()))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -409,7 +477,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::from(value))
+        Ok(HashedValue::from(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -417,7 +485,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value.to_owned()))
+        Ok(HashedValue::String(value.to_owned()))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -425,7 +493,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value))
+        Ok(HashedValue::String(value))
     }
 
     /****************** byte stuff ******************/
@@ -436,7 +504,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-        Ok(Value::String(value))
+        Ok(HashedValue::String(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -445,7 +513,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     'a: 'de
         E: de::Error,
     {
-      Ok(Value::String(value))
+      Ok(HashedValue::String(value))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -453,7 +521,7 @@ impl<'de> Visitor<'de> for ValueVisitor {
     where
         E: de::Error,
     {
-      Ok(Value::String(&value))
+      Ok(HashedValue::String(&value))
     }
      */
     /****************** nexted stuff ******************/
@@ -465,12 +533,12 @@ impl<'de> Visitor<'de> for ValueVisitor {
     {
         let size = map.size_hint().unwrap_or_default();
 
-        let mut m = Object::with_capacity(size);
+        let mut m = map::with_capacity(size);
         while let Some(k) = map.next_key()? {
             let v = map.next_value()?;
             m.insert(k, v);
         }
-        Ok(Value::from(m))
+        Ok(HashedValue::from(m))
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline)]
@@ -484,13 +552,207 @@ impl<'de> Visitor<'de> for ValueVisitor {
         while let Some(e) = seq.next_element()? {
             v.push(e);
         }
-        Ok(Value::Array(v))
+        Ok(HashedValue::Array(v))
+    }
+}
+
+// Deserializes into an existing value, strings and arrays keep their
+// allocations when the new value has the same type. Objects are rebuilt
+// from their old entries so values under known keys are reused too.
+struct ValueInPlaceVisitor<'a, S>(&'a mut HashedValue<S>);
+
+impl<'a, 'de, S> Visitor<'de> for ValueInPlaceVisitor<'a, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSONesque value")
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        *self.0 = HashedValue::Static(StaticNode::Null);
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        *self.0 = HashedValue::Static(StaticNode::Bool(value));
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        *self.0 = HashedValue::Static(StaticNode::Null);
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::Static(StaticNode::I64(value));
+        Ok(())
+    }
+
+    #[cfg(feature = "128bit")]
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::Static(StaticNode::I128(value));
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::Static(StaticNode::U64(value));
+        Ok(())
+    }
+
+    #[cfg(feature = "128bit")]
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_u128<E>(self, value: u128) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::Static(StaticNode::U128(value));
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::Static(StaticNode::F64(value));
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        if let HashedValue::String(s) = self.0 {
+            s.clear();
+            s.push_str(value);
+        } else {
+            *self.0 = HashedValue::String(value.to_owned());
+        }
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        *self.0 = HashedValue::String(value);
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut old = if let HashedValue::Object(m) = self.0 {
+            std::mem::take(&mut **m)
+        } else {
+            *self.0 = ValueVisitor(PhantomData).visit_map(map)?;
+            return Ok(());
+        };
+        let size = map.size_hint().unwrap_or_else(|| old.len());
+        let mut m = map::with_capacity(size);
+        let mut key = String::new();
+        while map.next_key_seed(KeyInPlace(&mut key))?.is_some() {
+            if let Some((k, mut v)) = old.remove_entry(key.as_str()) {
+                map.next_value_seed(InPlace(&mut v))?;
+                m.insert(k, v);
+            } else {
+                let v = map.next_value()?;
+                m.insert(key.clone(), v);
+            }
+        }
+        *self.0 = HashedValue::from(m);
+        Ok(())
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline)]
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let v = if let HashedValue::Array(v) = self.0 {
+            v
+        } else {
+            *self.0 = ValueVisitor(PhantomData).visit_seq(seq)?;
+            return Ok(());
+        };
+        for i in 0..v.len() {
+            if seq.next_element_seed(InPlace(&mut v[i]))?.is_none() {
+                v.truncate(i);
+                return Ok(());
+            }
+        }
+        v.reserve(seq.size_hint().unwrap_or_default());
+        while let Some(e) = seq.next_element()? {
+            v.push(e);
+        }
+        Ok(())
     }
 }
 
-fn visit_array_ref<'de, V>(array: &'de [Value], visitor: V) -> Result<V::Value, Error>
+struct InPlace<'a, S>(&'a mut HashedValue<S>);
+
+impl<'a, 'de, S> DeserializeSeed<'de> for InPlace<'a, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        HashedValue::deserialize_in_place(deserializer, self.0)
+    }
+}
+
+// Reads an object key into a reused buffer.
+struct KeyInPlace<'a>(&'a mut String);
+
+impl<'a, 'de> DeserializeSeed<'de> for KeyInPlace<'a> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize_in_place(deserializer, self.0)
+    }
+}
+
+fn visit_array_ref<'de, V, S>(array: &'de [HashedValue<S>], visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = array.len();
     let mut deserializer = SeqRefDeserializer::new(array);
@@ -506,9 +768,10 @@ where
     }
 }
 
-fn visit_object_ref<'de, V>(object: &'de Object, visitor: V) -> Result<V::Value, Error>
+fn visit_object_ref<'de, V, S>(object: &'de HashedObject<S>, visitor: V) -> Result<V::Value, Error>
 where
     V: Visitor<'de>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     let len = object.len();
     let mut deserializer = MapRefDeserializer::new(object);
@@ -523,7 +786,10 @@ where
     }
 }
 
-impl<'de> de::Deserializer<'de> for &'de Value {
+impl<'de, S> de::Deserializer<'de> for &'de HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     // Look at the input data to decide what Serde data model type to
@@ -534,18 +800,18 @@ impl<'de> de::Deserializer<'de> for &'de Value {
         V: Visitor<'de>,
     {
         match self {
-            Value::Static(StaticNode::Null) => visitor.visit_unit(),
-            Value::Static(StaticNode::Bool(b)) => visitor.visit_bool(*b),
-            Value::Static(StaticNode::I64(n)) => visitor.visit_i64(*n),
+            HashedValue::Static(StaticNode::Null) => visitor.visit_unit(),
+            HashedValue::Static(StaticNode::Bool(b)) => visitor.visit_bool(*b),
+            HashedValue::Static(StaticNode::I64(n)) => visitor.visit_i64(*n),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::I128(n)) => visitor.visit_i128(*n),
-            Value::Static(StaticNode::U64(n)) => visitor.visit_u64(*n),
+            HashedValue::Static(StaticNode::I128(n)) => visitor.visit_i128(*n),
+            HashedValue::Static(StaticNode::U64(n)) => visitor.visit_u64(*n),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::U128(n)) => visitor.visit_u128(*n),
-            Value::Static(StaticNode::F64(n)) => visitor.visit_f64(*n),
-            Value::String(ref s) => visitor.visit_borrowed_str(s),
-            Value::Array(ref a) => visit_array_ref(a, visitor),
-            Value::Object(ref o) => visit_object_ref(o, visitor),
+            HashedValue::Static(StaticNode::U128(n)) => visitor.visit_u128(*n),
+            HashedValue::Static(StaticNode::F64(n)) => visitor.visit_f64(*n),
+            HashedValue::String(ref s) => visitor.visit_borrowed_str(s),
+            HashedValue::Array(ref a) => visit_array_ref(a, visitor),
+            HashedValue::Object(ref o) => visit_object_ref(o, visitor),
         }
     }
 
@@ -554,7 +820,7 @@ impl<'de> de::Deserializer<'de> for &'de Value {
     where
         V: Visitor<'de>,
     {
-        if self == &Value::Static(StaticNode::Null) {
+        if self == &HashedValue::Static(StaticNode::Null) {
             visitor.visit_unit()
         } else {
             visitor.visit_some(self)
@@ -573,30 +839,55 @@ impl<'de> de::Deserializer<'de> for &'de Value {
     {
         match self {
             // Give the visitor access to each element of the sequence.
-            Value::Array(ref a) => visit_array_ref(a, visitor),
-            Value::Object(ref o) => visit_object_ref(o, visitor),
+            HashedValue::Array(ref a) => visit_array_ref(a, visitor),
+            HashedValue::Object(ref o) => visit_object_ref(o, visitor),
             _ => Err(crate::Deserializer::error(ErrorType::ExpectedMap)),
         }
     }
 
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name == TOKEN {
+            visitor.visit_string(self.encode())
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            bytes byte_buf unit unit_struct seq tuple
             tuple_struct map enum identifier ignored_any
     }
 }
 
-struct SeqRefDeserializer<'de> {
-    iter: slice::Iter<'de, Value>,
+struct SeqRefDeserializer<'de, S> {
+    iter: slice::Iter<'de, HashedValue<S>>,
+    index: usize,
 }
 
-impl<'de> SeqRefDeserializer<'de> {
-    fn new(slice: &'de [Value]) -> Self {
-        SeqRefDeserializer { iter: slice.iter() }
+impl<'de, S> SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn new(slice: &'de [HashedValue<S>]) -> Self {
+        SeqRefDeserializer {
+            iter: slice.iter(),
+            index: 0,
+        }
     }
 }
 
-impl<'de> serde::Deserializer<'de> for SeqRefDeserializer<'de> {
+impl<'de, S> serde::Deserializer<'de> for SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -628,7 +919,10 @@ impl<'de> serde::Deserializer<'de> for SeqRefDeserializer<'de> {
     }
 }
 
-impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
+impl<'de, S> SeqAccess<'de> for SeqRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
@@ -636,7 +930,13 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value)
+                    .map(Some)
+                    .map_err(|e| e.in_element(index))
+            }
             None => Ok(None),
         }
     }
@@ -649,21 +949,29 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
     }
 }
 
-struct MapRefDeserializer<'de> {
-    iter: <&'de Object as IntoIterator>::IntoIter,
-    value: Option<&'de Value>,
+struct MapRefDeserializer<'de, S> {
+    iter: <&'de HashedObject<S> as IntoIterator>::IntoIter,
+    key: &'de str,
+    value: Option<&'de HashedValue<S>>,
 }
 
-impl<'de> MapRefDeserializer<'de> {
-    fn new(map: &'de Object) -> Self {
+impl<'de, S> MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn new(map: &'de HashedObject<S>) -> Self {
         MapRefDeserializer {
             iter: map.into_iter(),
+            key: "",
             value: None,
         }
     }
 }
 
-impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
+impl<'de, S> MapAccess<'de> for MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     fn next_key_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
@@ -672,6 +980,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
     {
         match self.iter.next() {
             Some((key, value)) => {
+                self.key = key;
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer {
                     key: Cow::from(&**key),
@@ -687,7 +996,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|e| e.in_key(self.key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -700,7 +1009,10 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
     }
 }
 
-impl<'de> serde::Deserializer<'de> for MapRefDeserializer<'de> {
+impl<'de, S> serde::Deserializer<'de> for MapRefDeserializer<'de, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Error = Error;
 
     #[inline]
@@ -1,12 +1,13 @@
 use super::to_value;
-use crate::value::owned::{Object, Value};
+use crate::serde::raw::{RawEmitter, TOKEN};
+use crate::value::owned::{HashedValue, Object, Value};
 use crate::{stry, Error, ErrorType, Result, StaticNode};
 use serde::ser::{self, Serialize};
 use serde_ext::ser::{SerializeMap as SerializeMapTrait, SerializeSeq as SerializeSeqTrait};
 
 type Impossible<T> = ser::Impossible<T, Error>;
 
-impl Serialize for Value {
+impl<H> Serialize for HashedValue<H> {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: ser::Serializer,
@@ -166,11 +167,17 @@ impl serde::Serializer for Serializer {
     }
 
     #[inline]
-    fn serialize_newtype_struct<T: ?Sized>(self, _name: &'static str, value: &T) -> Result<Value>
+    fn serialize_newtype_struct<T: ?Sized>(self, name: &'static str, value: &T) -> Result<Value>
     where
         T: Serialize,
     {
-        value.serialize(self)
+        if name == TOKEN {
+            let mut json = Vec::new();
+            stry!(value.serialize(RawEmitter(&mut json)));
+            crate::to_owned_value(&mut json)
+        } else {
+            value.serialize(self)
+        }
     }
 
     fn serialize_newtype_variant<T: ?Sized>(
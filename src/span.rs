@@ -0,0 +1,166 @@
+//! Source positions of the nodes on a tape.
+
+use crate::path::Structurals;
+use crate::{Deserializer, Indexes, Node, Result, StructuralIndex};
+
+/// Byte offsets of a value in the input, `end` is exclusive and trailing
+/// whitespace is not part of the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    /// Offset of the first byte of the value
+    pub start: usize,
+    /// Offset one past the last byte of the value
+    pub end: usize,
+}
+
+impl Span {
+    /// Length of the value in bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the span covers no input, only the root of an empty
+    /// input can do that
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl Deserializer<'_> {
+    /// Computes the span of every node on the tape, `spans()[i]` is the
+    /// span of `tape[i]`. The first node is the root placeholder and spans
+    /// the entire input.
+    ///
+    /// Spans are only computed on request, and need the structural indexes
+    /// of the input so only deserializers created with
+    /// [`Deserializer::from_slice_with_source`] can compute them, for all
+    /// others this returns `None`.
+    ///
+    /// ```
+    /// use simd_json::{Deserializer, Node, Span};
+    ///
+    /// let mut d = br#"{"key": [1, true]}"#.to_vec();
+    /// let input = d.clone();
+    /// let deserializer = Deserializer::from_slice_with_source(&mut d).unwrap();
+    /// let spans = deserializer.spans().unwrap();
+    /// let tape = deserializer.into_tape();
+    /// assert_eq!(tape[4], Node::Static(simd_json::StaticNode::U64(1)));
+    /// assert_eq!(&input[spans[3].start..spans[3].end], b"[1, true]");
+    /// assert_eq!(spans[4], Span { start: 9, end: 10 });
+    /// ```
+    #[must_use]
+    pub fn spans(&self) -> Option<Vec<Span>> {
+        let source = self.source.as_ref()?;
+        Some(match &source.indexes {
+            Indexes::U32(idx) => spans(source.input, idx, self.tape.len()),
+            Indexes::U64(idx) => spans(source.input, idx, self.tape.len()),
+        })
+    }
+}
+
+fn spans<I: StructuralIndex>(input: &[u8], idx: &[I], nodes: usize) -> Vec<Span> {
+    let s = Structurals { input, idx };
+    let mut spans = Vec::with_capacity(nodes);
+    spans.push(Span {
+        start: 0,
+        end: input.len(),
+    });
+    // spans of the arrays and objects we are in
+    let mut open = Vec::new();
+    for p in 1..s.len() {
+        let start = s.at(p);
+        match input[start] {
+            b',' | b':' => (),
+            b'{' | b'[' => {
+                open.push(spans.len());
+                spans.push(Span { start, end: start });
+            }
+            b'}' | b']' => {
+                if let Some(i) = open.pop() {
+                    spans[i].end = start + 1;
+                }
+            }
+            _ => spans.push(Span {
+                start,
+                end: s.value_end(p, p + 1),
+            }),
+        }
+    }
+    debug_assert_eq!(spans.len(), nodes);
+    spans
+}
+
+/// Creates a tape from the input along with the span of every node, see
+/// [`Deserializer::spans`]
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_tape_with_spans(s: &mut [u8]) -> Result<(Vec<Node<'_>>, Vec<Span>)> {
+    let deserializer = stry!(Deserializer::from_slice_with_source(s));
+    let spans = deserializer.spans().unwrap_or_default();
+    Ok((deserializer.into_tape(), spans))
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::StaticNode;
+
+    fn text<'input>(input: &'input str, span: &Span) -> &'input str {
+        &input[span.start..span.end]
+    }
+
+    #[test]
+    fn nested() {
+        let input = r#" { "a" : [ 1 , -2.5e3 , "x" ] , "b" : { } , "c" : null } "#;
+        let mut d = input.as_bytes().to_vec();
+        let (tape, spans) = to_tape_with_spans(&mut d).unwrap();
+        assert_eq!(tape.len(), spans.len());
+        let texts: Vec<&str> = spans.iter().map(|s| text(input, s)).collect();
+        assert_eq!(
+            texts,
+            vec![
+                input,
+                r#"{ "a" : [ 1 , -2.5e3 , "x" ] , "b" : { } , "c" : null }"#,
+                r#""a""#,
+                r#"[ 1 , -2.5e3 , "x" ]"#,
+                "1",
+                "-2.5e3",
+                r#""x""#,
+                r#""b""#,
+                "{ }",
+                r#""c""#,
+                "null",
+            ]
+        );
+        assert_eq!(tape[10], Node::Static(StaticNode::Null));
+    }
+
+    #[test]
+    fn escaped() {
+        // strings are unescaped in place but their spans cover the raw text
+        let input = r#"["a\"b", "é", 1]"#;
+        let mut d = input.as_bytes().to_vec();
+        let (tape, spans) = to_tape_with_spans(&mut d).unwrap();
+        assert_eq!(tape[2], Node::String("a\"b"));
+        assert_eq!(text(input, &spans[2]), r#""a\"b""#);
+        assert_eq!(text(input, &spans[3]), r#""é""#);
+        assert_eq!(text(input, &spans[4]), "1");
+        assert_eq!(spans[4].len(), 1);
+    }
+
+    #[test]
+    fn scalar() {
+        let input = "  42\n";
+        let mut d = input.as_bytes().to_vec();
+        let (_, spans) = to_tape_with_spans(&mut d).unwrap();
+        assert_eq!(
+            spans,
+            vec![Span { start: 0, end: 5 }, Span { start: 2, end: 4 }]
+        );
+    }
+}
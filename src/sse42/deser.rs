@@ -65,7 +65,8 @@ impl<'de> Deserializer<'de> {
                 ///////////////////////
                 // Above, check for overflow in case someone has a crazy string (>=4GB?)
                 // But only add the overflow check when the document itself exceeds 4GB
-                // Currently unneeded because we refuse to parse docs larger or equal to 4GB.
+                // Unneeded for us, `idx`, `len`, `src_i` and `dst_i` are all `usize`
+                // so they can't overflow for strings in documents of any size.
                 ////////////////////////
 
                 // we advance the point, accounting for the fact that we have a NULl termination
@@ -129,7 +130,8 @@ impl<'de> Deserializer<'de> {
                 ///////////////////////
                 // Above, check for overflow in case someone has a crazy string (>=4GB?)
                 // But only add the overflow check when the document itself exceeds 4GB
-                // Currently unneeded because we refuse to parse docs larger or equal to 4GB.
+                // Unneeded for us, `idx`, `len`, `src_i` and `dst_i` are all `usize`
+                // so they can't overflow for strings in documents of any size.
                 ////////////////////////
 
                 // we advance the point, accounting for the fact that we have a NULl termination
@@ -0,0 +1,32 @@
+#[cfg(target_arch = "x86")]
+use std::arch::x86::{
+    __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_max_epu8, _mm_movemask_epi8, _mm_or_si128,
+    _mm_set1_epi8,
+};
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_max_epu8, _mm_movemask_epi8, _mm_or_si128,
+    _mm_set1_epi8,
+};
+
+use std::mem;
+
+/// Number of bytes `escape_bits` looks at
+pub(crate) const ESCAPE_BLOCK: usize = 16;
+
+/// Returns a bit for every byte in the `ESCAPE_BLOCK` bytes at `ptr` that
+/// has to be escaped in a JSON string, those are quotes, backslashes and
+/// control characters.
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+#[allow(clippy::cast_possible_wrap, clippy::cast_ptr_alignment)]
+pub(crate) unsafe fn escape_bits(ptr: *const u8) -> u32 {
+    let data: __m128i = _mm_loadu_si128(ptr.cast::<__m128i>());
+    let bs_or_quote = _mm_or_si128(
+        _mm_cmpeq_epi8(data, _mm_set1_epi8(b'\\' as i8)),
+        _mm_cmpeq_epi8(data, _mm_set1_epi8(b'"' as i8)),
+    );
+    // only bytes up to 0x1f are left unchanged by the unsigned maximum
+    let control_range = _mm_set1_epi8(0x1f);
+    let control = _mm_cmpeq_epi8(_mm_max_epu8(data, control_range), control_range);
+    static_cast_u32!(_mm_movemask_epi8(_mm_or_si128(bs_or_quote, control)))
+}
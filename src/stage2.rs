@@ -1,7 +1,7 @@
 #![allow(dead_code)]
 use crate::charutils::is_not_structural_or_whitespace;
 use crate::value::tape::Node;
-use crate::{Deserializer, Error, ErrorType, Result};
+use crate::{Deserializer, Error, ErrorType, Handler, Result, StructuralIndex};
 use value_trait::StaticNode;
 
 #[cfg_attr(not(feature = "no-inline"), inline(always))]
@@ -109,29 +109,197 @@ enum StackState {
     Array,
 }
 
+/// Records the events of the stage 2 state machine on a tape.
+///
+/// Every node on the tape (and every scope on the stack) is created by a
+/// distinct structural character, the leading `null` stands in for the
+/// skipped zero index. So both never grow beyond the number of structural
+/// indexes they are allocated for, which lets us skip the capacity checks.
+struct TapeBuilder<'de> {
+    tape: Vec<Node<'de>>,
+    // `last_start` and `cnt` of the containers we are nested in
+    stack: Vec<(usize, usize)>,
+    // index of the container we are currently in on the tape
+    last_start: usize,
+    // number of elements in the current container so far
+    cnt: usize,
+}
+
+impl<'de> TapeBuilder<'de> {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn with_capacity(capacity: usize) -> Self {
+        // While a valid json can have at max len/2 (`[[[]]]`)elements that are relevant
+        // a invalid json might exceed this `[[[[[[` and we need to pretect against that.
+        let mut tape = Vec::with_capacity(capacity.max(1));
+        tape.push(Node::Static(StaticNode::Null));
+        Self {
+            tape,
+            stack: Vec::with_capacity(capacity),
+            last_start: 0,
+            cnt: 0,
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn push(&mut self, node: Node<'de>) {
+        let len = self.tape.len();
+        debug_assert!(len < self.tape.capacity());
+        unsafe {
+            std::ptr::write(self.tape.as_mut_ptr().add(len), node);
+            self.tape.set_len(len + 1);
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn value(&mut self, node: Node<'de>) {
+        self.cnt += 1;
+        self.push(node);
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn start(&mut self, node: Node<'de>) {
+        let depth = self.stack.len();
+        debug_assert!(depth < self.stack.capacity());
+        unsafe {
+            std::ptr::write(
+                self.stack.as_mut_ptr().add(depth),
+                (self.last_start, self.cnt + 1),
+            );
+            self.stack.set_len(depth + 1);
+        }
+        self.last_start = self.tape.len();
+        self.cnt = 0;
+        self.push(node);
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn end(&mut self) {
+        let end = self.tape.len();
+        if let Node::Array(len, e) | Node::Object(len, e) = get_mut!(self.tape, self.last_start) {
+            *len = self.cnt;
+            *e = end;
+        }
+        if let Some((last_start, cnt)) = self.stack.pop() {
+            self.last_start = last_start;
+            self.cnt = cnt;
+        }
+    }
+}
+
+impl<'de> Handler<'de> for TapeBuilder<'de> {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn start_object(&mut self) -> Result<()> {
+        self.start(Node::Object(0, 0));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn key(&mut self, key: &'de str) -> Result<()> {
+        self.push(Node::String(key));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn end_object(&mut self) -> Result<()> {
+        self.end();
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn start_array(&mut self) -> Result<()> {
+        self.start(Node::Array(0, 0));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn end_array(&mut self) -> Result<()> {
+        self.end();
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn string(&mut self, s: &'de str) -> Result<()> {
+        self.value(Node::String(s));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn null(&mut self) -> Result<()> {
+        self.value(Node::Static(StaticNode::Null));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn bool(&mut self, b: bool) -> Result<()> {
+        self.value(Node::Static(StaticNode::Bool(b)));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn i64(&mut self, n: i64) -> Result<()> {
+        self.value(Node::Static(StaticNode::I64(n)));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn u64(&mut self, n: u64) -> Result<()> {
+        self.value(Node::Static(StaticNode::U64(n)));
+        Ok(())
+    }
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn f64(&mut self, n: f64) -> Result<()> {
+        self.value(Node::Static(StaticNode::F64(n)));
+        Ok(())
+    }
+    #[cfg(feature = "128bit")]
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn i128(&mut self, n: i128) -> Result<()> {
+        self.value(Node::Static(StaticNode::I128(n)));
+        Ok(())
+    }
+    #[cfg(feature = "128bit")]
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn u128(&mut self, n: u128) -> Result<()> {
+        self.value(Node::Static(StaticNode::U128(n)));
+        Ok(())
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    pub(crate) fn build_tape<I: StructuralIndex>(
+        input: &'de mut [u8],
+        input2: &[u8],
+        buffer: &mut [u8],
+        structural_indexes: &[I],
+    ) -> Result<(Vec<Node<'de>>, &'de [u8])> {
+        let mut builder = TapeBuilder::with_capacity(structural_indexes.len());
+        let input = stry!(Self::drive_handler(
+            input,
+            input2,
+            buffer,
+            structural_indexes,
+            &mut builder
+        ));
+        Ok((builder.tape, input))
+    }
+}
+
 impl<'de> Deserializer<'de> {
+    /// Runs the stage 2 state machine over the structural indexes and
+    /// reports every element to `handler` as it is encountered, the tape
+    /// is built by the `TapeBuilder` handler.
+    ///
+    /// Returns the input, with strings unescaped in place, as the shared
+    /// slice the reported strings borrow from.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
     #[allow(clippy::cognitive_complexity, clippy::too_many_lines, unused_unsafe)]
-    pub(crate) fn build_tape(
+    pub(crate) fn drive_handler<I: StructuralIndex, H: Handler<'de>>(
         input: &'de mut [u8],
         input2: &[u8],
         buffer: &mut [u8],
-        structural_indexes: &[u32],
-    ) -> Result<Vec<Node<'de>>> {
-        // While a valid json can have at max len/2 (`[[[]]]`)elements that are relevant
-        // a invalid json might exceed this `[[[[[[` and we need to pretect against that.
-        let mut res: Vec<Node<'de>> = Vec::with_capacity(structural_indexes.len());
-        let mut stack = Vec::with_capacity(structural_indexes.len());
-        unsafe {
-            stack.set_len(structural_indexes.len());
-            res.set_len(structural_indexes.len());
-        }
+        structural_indexes: &[I],
+        handler: &mut H,
+    ) -> Result<&'de [u8]> {
+        // strings are unescaped in place by `parse_str_` which only takes
+        // the input as a shared slice
+        let input: &'de [u8] = input;
 
-        let mut depth: usize = 0;
-        let mut last_start = 1;
-        let mut cnt: usize = 0;
-        let mut r_i = 0;
+        // We only need to remember what kind of scope we return to, the
+        // handler is responsible for tracking everything else.
+        let mut stack: Vec<StackState> = Vec::with_capacity(structural_indexes.len());
 
-        // let mut i: usize = 0; // index of the structural character (0,1,2,3...)
         // location of the structural character in the input (buf)
         let mut idx: usize = 0;
         // used to track the (structural) character we are looking at, updated
@@ -141,43 +309,10 @@ impl<'de> Deserializer<'de> {
         let mut i: usize = 1;
         let mut state;
 
-        macro_rules! s2try {
-            ($e:expr) => {
-                match $e {
-                    ::std::result::Result::Ok(val) => val,
-                    ::std::result::Result::Err(err) => {
-                        // We need to ensure that rust doens't
-                        // try to free strings that we never
-                        // allocated
-                        unsafe {
-                            res.set_len(r_i);
-                        };
-                        return ::std::result::Result::Err(err);
-                    }
-                }
-            };
-        }
-
-        macro_rules! insert_res {
-            ($t:expr) => {
-                unsafe {
-                    std::ptr::write(get_mut!(res, r_i), $t);
-                    r_i += 1;
-                }
-            };
-        }
-        macro_rules! success {
-            () => {
-                unsafe {
-                    res.set_len(r_i);
-                    return Ok(res);
-                }
-            };
-        }
         macro_rules! update_char {
             () => {
                 if i < structural_indexes.len() {
-                    idx = *get!(structural_indexes, i) as usize;
+                    idx = get!(structural_indexes, i).as_usize();
                     i += 1;
                     c = *get!(input2, idx);
                 } else {
@@ -193,29 +328,84 @@ impl<'de> Deserializer<'de> {
             }};
         }
 
-        insert_res!(Node::Static(StaticNode::Null));
+        macro_rules! fail {
+            () => {
+                return Err(Error::new(idx, c as char, ErrorType::InternalError));
+            };
+            ($t:expr) => {
+                return Err(Error::new(idx, c as char, $t));
+            };
+        }
+
+        macro_rules! success {
+            () => {
+                return Ok(input);
+            };
+        }
 
-        macro_rules! insert_str {
+        macro_rules! parse_str {
             () => {
-                insert_res!(Node::String(s2try!(Self::parse_str_(
-                    input, &input2, buffer, idx
-                ))));
+                stry!(Self::parse_str_(input, &input2, buffer, idx))
+            };
+        }
+
+        // Every scope is opened by a distinct structural character, so the
+        // stack never outgrows its capacity.
+        macro_rules! push_state {
+            ($s:expr) => {{
+                let depth = stack.len();
+                debug_assert!(depth < stack.capacity());
+                unsafe {
+                    std::ptr::write(stack.as_mut_ptr().add(depth), $s);
+                    stack.set_len(depth + 1);
+                }
+            }};
+        }
+
+        // Validates an atom or number at `idx` and hands it to the handler.
+        macro_rules! handle_value {
+            ($error:expr) => {
+                match c {
+                    b't' => {
+                        if !is_valid_true_atom(get!(input2, idx..)) {
+                            fail!($error); // TODO: better error
+                        }
+                        stry!(handler.bool(true));
+                    }
+                    b'f' => {
+                        if !is_valid_false_atom(get!(input2, idx..)) {
+                            fail!($error); // TODO: better error
+                        }
+                        stry!(handler.bool(false));
+                    }
+                    b'n' => {
+                        if !is_valid_null_atom(get!(input2, idx..)) {
+                            fail!(ErrorType::ExpectedNull); // TODO: better error
+                        }
+                        stry!(handler.null());
+                    }
+                    b'-' => {
+                        let n = stry!(Self::parse_number_int(idx, get!(input2, idx..), true));
+                        stry!(handle_number(handler, n));
+                    }
+                    _ => {
+                        let n = stry!(Self::parse_number_int(idx, get!(input2, idx..), false));
+                        stry!(handle_number(handler, n));
+                    }
+                }
             };
         }
 
-        // The continue cases are the most frequently called onces it's
-        // worth pulling them out into a macro (aka inlining them)
-        // Since we don't have a 'gogo' in rust.
         macro_rules! array_continue {
             () => {{
                 update_char!();
                 match c {
                     b',' => {
-                        cnt += 1;
                         update_char!();
                         goto!(MainArraySwitch);
                     }
                     b']' => {
+                        stry!(handler.end_array());
                         goto!(ScopeEnd);
                     }
                     _c => {
@@ -230,15 +420,15 @@ impl<'de> Deserializer<'de> {
                 update_char!();
                 match c {
                     b',' => {
-                        cnt += 1;
                         update_char!();
                         if c == b'"' {
-                            insert_str!();
+                            stry!(handler.key(parse_str!()));
                             goto!(ObjectKey);
                         }
                         fail!(ErrorType::ExpectedObjectKey);
                     }
                     b'}' => {
+                        stry!(handler.end_object());
                         goto!(ScopeEnd);
                     }
                     _ => {
@@ -250,9 +440,10 @@ impl<'de> Deserializer<'de> {
 
         macro_rules! array_begin {
             () => {
+                stry!(handler.start_array());
                 update_char!();
                 if c == b']' {
-                    cnt = 0;
+                    stry!(handler.end_array());
                     goto!(ScopeEnd);
                 }
                 goto!(MainArraySwitch);
@@ -261,14 +452,15 @@ impl<'de> Deserializer<'de> {
 
         macro_rules! object_begin {
             () => {{
+                stry!(handler.start_object());
                 update_char!();
                 match c {
                     b'"' => {
-                        insert_str!();
+                        stry!(handler.key(parse_str!()));
                         goto!(ObjectKey)
                     }
                     b'}' => {
-                        cnt = 0;
+                        stry!(handler.end_object());
                         goto!(ScopeEnd);
                     }
                     _c => {
@@ -278,47 +470,22 @@ impl<'de> Deserializer<'de> {
             }};
         }
 
-        macro_rules! fail {
-            () => {
-                // We need to ensure that rust doens't
-                // try to free strings that we never
-                // allocated
-                unsafe {
-                    res.set_len(r_i);
-                };
-                return Err(Error::new(idx, c as char, ErrorType::InternalError));
-            };
-            ($t:expr) => {
-                // We need to ensure that rust doens't
-                // try to free strings that we never
-                // allocated
-                unsafe {
-                    res.set_len(r_i);
-                };
-                return Err(Error::new(idx, c as char, $t));
-            };
-        }
         // State start, we pull this outside of the
         // loop to reduce the number of requried checks
         update_char!();
         match c {
             b'{' => {
-                *get_mut!(stack, depth) = (StackState::Start, last_start, cnt);
-
-                last_start = r_i;
-                insert_res!(Node::Object(0, 0));
-
-                depth += 1;
-                cnt = 1;
+                push_state!(StackState::Start);
 
+                stry!(handler.start_object());
                 update_char!();
                 match c {
                     b'"' => {
-                        insert_str!();
+                        stry!(handler.key(parse_str!()));
                         state = State::ObjectKey;
                     }
                     b'}' => {
-                        cnt = 0;
+                        stry!(handler.end_object());
                         state = State::ScopeEnd;
                     }
                     _c => {
@@ -327,84 +494,26 @@ impl<'de> Deserializer<'de> {
                 }
             }
             b'[' => {
-                *get_mut!(stack, depth) = (StackState::Start, last_start, cnt);
-
-                last_start = r_i;
-                insert_res!(Node::Array(0, 0));
-
-                depth += 1;
-                cnt = 1;
+                push_state!(StackState::Start);
 
+                stry!(handler.start_array());
                 update_char!();
                 if c == b']' {
-                    cnt = 0;
+                    stry!(handler.end_array());
                     state = State::ScopeEnd;
                 } else {
-                    state = State::MainArraySwitch
-                }
-            }
-            b't' => {
-                unsafe {
-                    if !is_valid_true_atom(get!(input2, idx..)) {
-                        fail!(ErrorType::ExpectedNull); // TODO: better error
-                    }
-                };
-                insert_res!(Node::Static(StaticNode::Bool(true)));
-                if i == structural_indexes.len() {
-                    success!();
-                }
-                fail!(ErrorType::TrailingCharacters);
-            }
-            b'f' => {
-                unsafe {
-                    if !is_valid_false_atom(get!(input2, idx..)) {
-                        fail!(ErrorType::ExpectedNull); // TODO: better error
-                    }
-                };
-                insert_res!(Node::Static(StaticNode::Bool(false)));
-                if i == structural_indexes.len() {
-                    success!();
-                }
-                fail!(ErrorType::TrailingCharacters);
-            }
-            b'n' => {
-                unsafe {
-                    if !is_valid_null_atom(get!(input2, idx..)) {
-                        fail!(ErrorType::ExpectedNull); // TODO: better error
-                    }
-                };
-                insert_res!(Node::Static(StaticNode::Null));
-                if i == structural_indexes.len() {
-                    success!();
+                    state = State::MainArraySwitch;
                 }
-                fail!(ErrorType::TrailingCharacters);
             }
             b'"' => {
-                insert_str!();
+                stry!(handler.string(parse_str!()));
                 if i == structural_indexes.len() {
                     success!();
                 }
                 fail!(ErrorType::TrailingCharacters);
             }
-            b'-' => {
-                insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                    idx,
-                    get!(input2, idx..),
-                    true
-                ))));
-
-                if i == structural_indexes.len() {
-                    success!();
-                }
-                fail!(ErrorType::TrailingCharacters);
-            }
-            b'0'..=b'9' => {
-                insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                    idx,
-                    get!(input2, idx..),
-                    false
-                ))));
-
+            b't' | b'f' | b'n' | b'-' | b'0'..=b'9' => {
+                handle_value!(ErrorType::ExpectedNull);
                 if i == structural_indexes.len() {
                     success!();
                 }
@@ -427,66 +536,19 @@ impl<'de> Deserializer<'de> {
                     update_char!();
                     match c {
                         b'"' => {
-                            insert_str!();
-                            object_continue!()
-                        }
-                        b't' => {
-                            insert_res!(Node::Static(StaticNode::Bool(true)));
-                            if !is_valid_true_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedBoolean); // TODO: better error
-                            }
-                            object_continue!();
-                        }
-                        b'f' => {
-                            insert_res!(Node::Static(StaticNode::Bool(false)));
-                            if !is_valid_false_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedBoolean); // TODO: better error
-                            }
-                            object_continue!();
-                        }
-                        b'n' => {
-                            insert_res!(Node::Static(StaticNode::Null));
-                            if !is_valid_null_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedNull); // TODO: better error
-                            }
+                            stry!(handler.string(parse_str!()));
                             object_continue!();
                         }
-                        b'-' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                                idx,
-                                get!(input2, idx..),
-                                true
-                            ))));
-                            object_continue!();
-                        }
-                        b'0'..=b'9' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                                idx,
-                                get!(input2, idx..),
-                                false
-                            ))));
+                        b't' | b'f' | b'n' | b'-' | b'0'..=b'9' => {
+                            handle_value!(ErrorType::ExpectedBoolean);
                             object_continue!();
                         }
                         b'{' => {
-                            unsafe {
-                                *stack.get_unchecked_mut(depth) =
-                                    (StackState::Object, last_start, cnt);
-                            }
-                            last_start = r_i;
-                            insert_res!(Node::Object(0, 0));
-                            depth += 1;
-                            cnt = 1;
+                            push_state!(StackState::Object);
                             object_begin!();
                         }
                         b'[' => {
-                            unsafe {
-                                *stack.get_unchecked_mut(depth) =
-                                    (StackState::Object, last_start, cnt);
-                            }
-                            last_start = r_i;
-                            insert_res!(Node::Array(0, 0));
-                            depth += 1;
-                            cnt = 1;
+                            push_state!(StackState::Object);
                             array_begin!();
                         }
                         _c => {
@@ -496,37 +558,18 @@ impl<'de> Deserializer<'de> {
                 }
                 ////////////////////////////// COMMON STATE /////////////////////////////
                 ScopeEnd => {
-                    if depth == 0 {
-                        fail!(ErrorType::Syntax);
-                    }
-                    depth -= 1;
-                    unsafe {
-                        match res.get_unchecked_mut(last_start) {
-                            Node::Array(ref mut len, ref mut end)
-                            | Node::Object(ref mut len, ref mut end) => {
-                                *len = cnt;
-                                *end = r_i;
-                            }
-                            _ => unreachable!(),
-                        };
-                    }
-
-                    let (a_state, a_last_start, a_cnt) = unsafe { stack.get_unchecked(depth) };
-                    //                    let (a_state, a_last_start, a_cnt) = unsafe {  };
-                    //s2try!(stack.pop().ok_or_else(|| Error::generic(ErrorType::Syntax)));
-
-                    last_start = *a_last_start;
-                    cnt = *a_cnt;
-
-                    match &a_state {
-                        StackState::Object => object_continue!(),
-                        StackState::Array => array_continue!(),
-                        StackState::Start => {
+                    match stack.pop() {
+                        Some(StackState::Object) => object_continue!(),
+                        Some(StackState::Array) => array_continue!(),
+                        Some(StackState::Start) => {
                             if i == structural_indexes.len() {
                                 success!();
                             }
                             fail!();
                         }
+                        None => {
+                            fail!(ErrorType::Syntax);
+                        }
                     };
                 }
 
@@ -536,66 +579,19 @@ impl<'de> Deserializer<'de> {
                     // on paths that can accept a close square brace (post-, and at start)
                     match c {
                         b'"' => {
-                            insert_str!();
-                            array_continue!()
-                        }
-                        b't' => {
-                            insert_res!(Node::Static(StaticNode::Bool(true)));
-                            if !is_valid_true_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedBoolean); // TODO: better error
-                            }
+                            stry!(handler.string(parse_str!()));
                             array_continue!();
                         }
-                        b'f' => {
-                            insert_res!(Node::Static(StaticNode::Bool(false)));
-                            if !is_valid_false_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedBoolean); // TODO: better error
-                            }
-                            array_continue!();
-                        }
-                        b'n' => {
-                            insert_res!(Node::Static(StaticNode::Null));
-                            if !is_valid_null_atom(get!(input2, idx..)) {
-                                fail!(ErrorType::ExpectedNull); // TODO: better error
-                            }
-                            array_continue!();
-                        }
-                        b'-' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                                idx,
-                                get!(input2, idx..),
-                                true
-                            ))));
-                            array_continue!();
-                        }
-                        b'0'..=b'9' => {
-                            insert_res!(Node::Static(s2try!(Self::parse_number_int(
-                                idx,
-                                get!(input2, idx..),
-                                false
-                            ))));
+                        b't' | b'f' | b'n' | b'-' | b'0'..=b'9' => {
+                            handle_value!(ErrorType::ExpectedBoolean);
                             array_continue!();
                         }
                         b'{' => {
-                            unsafe {
-                                *stack.get_unchecked_mut(depth) =
-                                    (StackState::Array, last_start, cnt);
-                            }
-                            last_start = r_i;
-                            insert_res!(Node::Object(0, 0));
-                            depth += 1;
-                            cnt = 1;
+                            push_state!(StackState::Array);
                             object_begin!();
                         }
                         b'[' => {
-                            unsafe {
-                                *stack.get_unchecked_mut(depth) =
-                                    (StackState::Array, last_start, cnt);
-                            }
-                            last_start = r_i;
-                            insert_res!(Node::Array(0, 0));
-                            depth += 1;
-                            cnt = 1;
+                            push_state!(StackState::Array);
                             array_begin!();
                         }
                         _c => {
@@ -608,6 +604,20 @@ impl<'de> Deserializer<'de> {
     }
 }
 
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+fn handle_number<'de, H: Handler<'de>>(handler: &mut H, n: StaticNode) -> Result<()> {
+    match n {
+        StaticNode::I64(n) => handler.i64(n),
+        StaticNode::U64(n) => handler.u64(n),
+        StaticNode::F64(n) => handler.f64(n),
+        #[cfg(feature = "128bit")]
+        StaticNode::I128(n) => handler.i128(n),
+        #[cfg(feature = "128bit")]
+        StaticNode::U128(n) => handler.u128(n),
+        StaticNode::Null | StaticNode::Bool(_) => Err(Error::generic(ErrorType::InternalError)),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
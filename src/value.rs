@@ -50,22 +50,31 @@
 /// assert_eq!(o.encode(), r#"{"key":[{"other":"value"}]}"#);
 /// ```
 
+/// Values whose arrays and objects are allocated in one arena per document
+#[cfg(feature = "arena")]
+pub mod arena;
 /// Borrowed values, using Cow's for strings using in situ parsing strategies wherever possible
 pub mod borrowed;
+/// Map for the members of objects
+pub mod map;
 /// Owned, lifetimeless version of the value for times when lifetimes are to be avoided
 pub mod owned;
 /// Tape implementation
 pub mod tape;
+#[cfg(feature = "arena")]
+pub use self::arena::{to_value as to_arena_value, Arena, Value as ArenaValue};
 pub use self::borrowed::{
-    to_value as to_borrowed_value, to_value_with_buffers as to_borrowed_value_with_buffers,
-    Value as BorrowedValue,
+    to_value as to_borrowed_value, to_value_any_encoding as to_borrowed_value_any_encoding,
+    to_value_with_buffers as to_borrowed_value_with_buffers,
+    to_value_with_hasher as to_borrowed_value_with_hasher, Value as BorrowedValue,
 };
+pub use self::map::{ObjectMap, ObjectView};
 pub use self::owned::{
-    to_value as to_owned_value, to_value_with_buffers as to_owned_value_with_buffers,
-    Value as OwnedValue,
+    to_value as to_owned_value, to_value_any_encoding as to_owned_value_any_encoding,
+    to_value_with_buffers as to_owned_value_with_buffers,
+    to_value_with_hasher as to_owned_value_with_hasher, Value as OwnedValue,
 };
 use crate::{Deserializer, Result};
-use halfbrown::HashMap;
 use std::hash::Hash;
 use std::marker::PhantomData;
 use tape::Node;
@@ -81,7 +90,7 @@ pub use value_trait::*;
 /// Will return `Err` if `s` is invalid JSON.
 pub fn deserialize<'de, Value, Key>(s: &'de mut [u8]) -> Result<Value>
 where
-    Value: Builder<'de> + From<Vec<Value>> + From<HashMap<Key, Value>> + 'de,
+    Value: Builder<'de> + From<Vec<Value>> + From<ObjectMap<Key, Value>> + 'de,
     Key: Hash + Eq + From<&'de str>,
 {
     match Deserializer::from_slice(s) {
@@ -92,7 +101,7 @@ where
 
 struct ValueDeserializer<'de, Value, Key>
 where
-    Value: Builder<'de> + From<Vec<Value>> + From<HashMap<Key, Value>> + 'de,
+    Value: Builder<'de> + From<Vec<Value>> + From<ObjectMap<Key, Value>> + 'de,
     Key: Hash + Eq + From<&'de str>,
 {
     de: Deserializer<'de>,
@@ -101,7 +110,7 @@ where
 
 impl<'de, Value, Key> ValueDeserializer<'de, Value, Key>
 where
-    Value: Builder<'de> + From<&'de str> + From<Vec<Value>> + From<HashMap<Key, Value>> + 'de,
+    Value: Builder<'de> + From<&'de str> + From<Vec<Value>> + From<ObjectMap<Key, Value>> + 'de,
     Key: Hash + Eq + From<&'de str>,
 {
     pub fn from_deserializer(de: Deserializer<'de>) -> Self {
@@ -138,7 +147,7 @@ where
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
     fn parse_map(&mut self, len: usize) -> Value {
-        let mut res: HashMap<Key, Value> = HashMap::with_capacity(len);
+        let mut res: ObjectMap<Key, Value> = ObjectMap::with_capacity(len);
 
         // Since we checked if it's empty we know that we at least have one
         // element so we eat this
@@ -0,0 +1,380 @@
+//! A dom whose arrays and objects live in an [`Arena`] instead of each
+//! getting an allocation of their own. Parsing into an arena is a bump
+//! of a pointer per container, and the whole document is freed at once
+//! when the arena is dropped or reset instead of walking the dom.
+//!
+//! Strings reference the input like they do for the borrowed value, and
+//! objects are slices of key value pairs in the order of the input, so
+//! looking up a key takes time linear in the size of the object.
+//!
+//! ```rust
+//! use simd_json::value::arena::{self, Arena};
+//!
+//! let mut arena = Arena::new();
+//! for _ in 0..2 {
+//!     let mut d = br#"{"key": [1, "two", null]}"#.to_vec();
+//!     let v = arena::to_value(&mut d, &arena).unwrap();
+//!     assert_eq!(v["key"][1], "two");
+//!     assert_eq!(v["key"].as_array().map(<[_]>::len), Some(3));
+//!     // frees the whole document and keeps the memory for the next one
+//!     arena.reset();
+//! }
+//! ```
+
+use crate::cow::Cow;
+use crate::prelude::*;
+use crate::value::{borrowed, map};
+use crate::{Deserializer, Node, Result, StaticNode};
+use bumpalo::Bump;
+use std::hash::BuildHasher;
+use std::ops::Index;
+
+/// Memory the containers of arena values are allocated from.
+#[derive(Default)]
+pub struct Arena {
+    bump: Bump,
+}
+
+impl Arena {
+    /// Creates an empty arena, it allocates on the first use
+    #[must_use]
+    pub fn new() -> Self {
+        Self { bump: Bump::new() }
+    }
+
+    /// Creates an arena that can hold `bytes` worth of containers before
+    /// it has to grow
+    #[must_use]
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self {
+            bump: Bump::with_capacity(bytes),
+        }
+    }
+
+    /// Frees all values allocated in the arena at once and keeps its
+    /// largest chunk of memory for the values parsed next
+    pub fn reset(&mut self) {
+        self.bump.reset();
+    }
+
+    /// The number of bytes the arena has allocated for values
+    #[must_use]
+    pub fn allocated_bytes(&self) -> usize {
+        self.bump.allocated_bytes()
+    }
+}
+
+/// Representation of a JSON object, its members in the order of the input
+pub type Object<'value> = [(&'value str, Value<'value>)];
+
+/// Arena allocated JSON-DOM Value
+///
+/// Objects are not hashed, looking up a key with [`Value::get`] or
+/// indexing scans the members of the object one by one. That is cheap
+/// for the small objects most documents have, for large objects that are
+/// looked up often a [`BorrowedValue`](crate::BorrowedValue) is faster.
+#[derive(Debug, Clone, Copy)]
+pub enum Value<'value> {
+    /// Static values
+    Static(StaticNode),
+    /// string type
+    String(&'value str),
+    /// array type
+    Array(&'value [Value<'value>]),
+    /// object type
+    Object(&'value Object<'value>),
+}
+
+/// Parses a slice of bytes into a Value dom whose arrays and objects are
+/// allocated in `arena`. This function will rewrite the slice to
+/// de-escape strings.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_value<'value>(s: &'value mut [u8], arena: &'value Arena) -> Result<Value<'value>> {
+    match Deserializer::from_slice(s) {
+        Ok(de) => Ok(ArenaDeserializer {
+            de,
+            bump: &arena.bump,
+        }
+        .parse()),
+        Err(e) => Err(e),
+    }
+}
+
+impl<'value> Value<'value> {
+    /// Returns the type of the value
+    #[must_use]
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Static(s) => s.value_type(),
+            Self::String(_) => ValueType::String,
+            Self::Array(_) => ValueType::Array,
+            Self::Object(_) => ValueType::Object,
+        }
+    }
+
+    /// returns true if the value is null
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Static(StaticNode::Null))
+    }
+
+    /// Tries to represent the value as a bool
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Static(s) => s.as_bool(),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as an i64
+    #[must_use]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Static(s) => s.as_i64(),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as an u64
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Self::Static(s) => s.as_u64(),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as a f64
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Static(s) => s.as_f64(),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as a str
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'value str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as an array
+    #[must_use]
+    pub fn as_array(&self) -> Option<&'value [Value<'value>]> {
+        match self {
+            Self::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    /// Tries to represent the value as an object
+    #[must_use]
+    pub fn as_object(&self) -> Option<&'value Object<'value>> {
+        match self {
+            Self::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    /// Gets the value of the first member named `key` if the value is an
+    /// object, this is a linear scan over the members
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&'value Value<'value>> {
+        self.as_object()
+            .and_then(|o| o.iter().find(|(k, _)| *k == key))
+            .map(|(_, v)| v)
+    }
+
+    /// Gets the element at `i` if the value is an array
+    #[must_use]
+    pub fn get_idx(&self, i: usize) -> Option<&'value Value<'value>> {
+        self.as_array().and_then(|a| a.get(i))
+    }
+}
+
+impl<'value> Index<&str> for Value<'value> {
+    type Output = Value<'value>;
+    #[inline]
+    fn index(&self, index: &str) -> &Self::Output {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<'value> Index<usize> for Value<'value> {
+    type Output = Value<'value>;
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get_idx(index).expect("index out of bounds")
+    }
+}
+
+impl<'value> PartialEq for Value<'value> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Static(s1), Self::Static(s2)) => s1 == s2,
+            (Self::String(s1), Self::String(s2)) => s1 == s2,
+            (Self::Array(a1), Self::Array(a2)) => a1 == a2,
+            // members are compared regardless of their order like for
+            // the other values
+            (Self::Object(o1), Self::Object(o2)) => {
+                o1.len() == o2.len() && o1.iter().all(|(k, v)| other.get(k) == Some(v))
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<'value> PartialEq<&str> for Value<'value> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == Some(*other)
+    }
+}
+
+impl<'value> PartialEq<i64> for Value<'value> {
+    fn eq(&self, other: &i64) -> bool {
+        match self {
+            Self::Static(s) => s == other,
+            _ => false,
+        }
+    }
+}
+
+impl<'value> PartialEq<bool> for Value<'value> {
+    fn eq(&self, other: &bool) -> bool {
+        self.as_bool() == Some(*other)
+    }
+}
+
+impl<'value> PartialEq<()> for Value<'value> {
+    fn eq(&self, _other: &()) -> bool {
+        self.is_null()
+    }
+}
+
+impl<'value, S> From<Value<'value>> for borrowed::HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    fn from(v: Value<'value>) -> Self {
+        match v {
+            Value::Static(s) => Self::Static(s),
+            Value::String(s) => Self::String(Cow::from(s)),
+            Value::Array(a) => Self::Array(a.iter().map(|v| Self::from(*v)).collect()),
+            Value::Object(o) => {
+                let mut res: borrowed::HashedObject<S> = map::with_capacity(o.len());
+                for (k, v) in o {
+                    res.insert_nocheck(Cow::from(*k), Self::from(*v));
+                }
+                Self::from(res)
+            }
+        }
+    }
+}
+
+struct ArenaDeserializer<'value> {
+    de: Deserializer<'value>,
+    bump: &'value Bump,
+}
+
+impl<'value> ArenaDeserializer<'value> {
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn parse(&mut self) -> Value<'value> {
+        match unsafe { self.de.next_() } {
+            Node::Static(s) => Value::Static(s),
+            Node::String(s) => Value::String(s),
+            Node::Array(len, _) => self.parse_array(len),
+            Node::Object(len, _) => self.parse_map(len),
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn parse_array(&mut self, len: usize) -> Value<'value> {
+        let bump = self.bump;
+        Value::Array(bump.alloc_slice_fill_with(len, |_| self.parse()))
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn parse_map(&mut self, len: usize) -> Value<'value> {
+        let bump = self.bump;
+        Value::Object(bump.alloc_slice_fill_with(len, |_| {
+            if let Node::String(key) = unsafe { self.de.next_() } {
+                (key, self.parse())
+            } else {
+                unreachable!()
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::{to_value, Arena, Value};
+    use crate::{BorrowedValue, StaticNode};
+    use value_trait::ValueType;
+
+    #[test]
+    fn parse() {
+        let arena = Arena::new();
+        let mut d = br#"{"a": [1, -2, 3.5, true, null], "b": {"c": "d\ne"}, "e": []}"#.to_vec();
+        let v = to_value(&mut d, &arena).unwrap();
+        assert_eq!(v.value_type(), ValueType::Object);
+        assert_eq!(v["a"][0], 1);
+        assert_eq!(v["a"][1].as_i64(), Some(-2));
+        assert_eq!(v["a"][2].as_f64(), Some(3.5));
+        assert_eq!(v["a"][3], true);
+        assert_eq!(v["a"][4], ());
+        assert_eq!(v["b"]["c"], "d\ne");
+        assert_eq!(v["e"].as_array(), Some(&[][..]));
+        assert!(v.get("f").is_none());
+        assert!(v["a"].get_idx(5).is_none());
+        assert!(arena.allocated_bytes() > 0);
+
+        assert_eq!(
+            BorrowedValue::from(v),
+            json!({"a": [1, -2, 3.5, true, null], "b": {"c": "d\ne"}, "e": []})
+        );
+
+        let mut d = b"\"top\"".to_vec();
+        assert_eq!(to_value(&mut d, &arena).unwrap(), Value::String("top"));
+        let mut d = b"[1, ".to_vec();
+        assert!(to_value(&mut d, &arena).is_err());
+    }
+
+    #[test]
+    fn eq() {
+        let arena = Arena::with_capacity(1024);
+        let mut d1 = br#"{"a": 1, "b": [2, {"c": null}]}"#.to_vec();
+        let mut d2 = br#"{"b": [2, {"c": null}], "a": 1}"#.to_vec();
+        let mut d3 = br#"{"b": [2, {"c": 3}], "a": 1}"#.to_vec();
+        let v1 = to_value(&mut d1, &arena).unwrap();
+        let v2 = to_value(&mut d2, &arena).unwrap();
+        let v3 = to_value(&mut d3, &arena).unwrap();
+        assert_eq!(v1, v2);
+        assert_ne!(v1, v3);
+        assert_ne!(v1["b"], v1["a"]);
+        assert_eq!(v1["a"], Value::Static(StaticNode::I64(1)));
+    }
+
+    #[test]
+    fn reset() {
+        let mut arena = Arena::new();
+        let mut d = br#"[[1, 2], [3, 4], {"a": [5]}]"#.to_vec();
+        let len = to_value(&mut d, &arena).unwrap().as_array().unwrap().len();
+        assert_eq!(len, 3);
+        let allocated = arena.allocated_bytes();
+        arena.reset();
+        let mut d = br#"[[1, 2], [3, 4], {"a": [5]}]"#.to_vec();
+        let v = to_value(&mut d, &arena).unwrap();
+        assert_eq!(v[2]["a"][0], 5);
+        assert_eq!(arena.allocated_bytes(), allocated);
+    }
+}
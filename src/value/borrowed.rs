@@ -26,14 +26,24 @@ mod serialize;
 
 use crate::cow::Cow;
 use crate::prelude::*;
+use crate::value::map::{self, ObjectMap, ObjectView};
 use crate::{AlignedBuf, Deserializer, Node, Result, StaticNode};
-use halfbrown::HashMap;
+use halfbrown::DefaultHashBuilder;
 use std::fmt;
+use std::hash::BuildHasher;
 use std::ops::{Index, IndexMut};
 use value_trait::ValueAccess;
 
 /// Representation of a JSON object
-pub type Object<'value> = HashMap<Cow<'value, str>, Value<'value>>;
+pub type Object<'value> = HashedObject<'value, DefaultHashBuilder>;
+
+/// Representation of a JSON object whose keys are hashed with `S`
+pub type HashedObject<'value, S> = ObjectMap<Cow<'value, str>, HashedValue<'value, S>, S>;
+
+/// Borrowed JSON-DOM Value, consider using the `ValueTrait`
+/// to access its content. Objects hash their keys with the default
+/// hasher, see [`HashedValue`] to pick another one.
+pub type Value<'value> = HashedValue<'value, DefaultHashBuilder>;
 
 /// Parses a slice of bytes into a Value dom. This function will
 /// rewrite the slice to de-escape strings.
@@ -44,6 +54,29 @@ pub type Object<'value> = HashMap<Cow<'value, str>, Value<'value>>;
 ///
 /// Will return `Err` if `s` is invalid JSON.
 pub fn to_value(s: &mut [u8]) -> Result<Value> {
+    to_value_with_hasher(s)
+}
+
+/// Parses a slice of bytes into a Value dom whose objects hash their
+/// keys with `S`, for example a DOS resistant hasher for untrusted input.
+///
+/// ```
+/// use simd_json::prelude::*;
+/// use std::collections::hash_map::RandomState;
+///
+/// let mut d = br#"{"key": [1, 2]}"#.to_vec();
+/// let v: simd_json::value::borrowed::HashedValue<RandomState> =
+///     simd_json::to_borrowed_value_with_hasher(&mut d).unwrap();
+/// assert_eq!(v["key"][1], 2);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_value_with_hasher<S>(s: &mut [u8]) -> Result<HashedValue<'_, S>>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     match Deserializer::from_slice(s) {
         Ok(de) => Ok(BorrowDeserializer::from_deserializer(de).parse()),
         Err(e) => Err(e),
@@ -69,49 +102,73 @@ pub fn to_value_with_buffers<'value>(
     }
 }
 
-/// Borrowed JSON-DOM Value, consider using the `ValueTrait`
-/// to access its content
-#[derive(Debug, Clone)]
-pub enum Value<'value> {
+/// Parses a buffer in any of the encodings JSON can come in into a
+/// Value dom. A UTF-8 byte order mark is skipped while UTF-16 and UTF-32
+/// input is transcoded to UTF-8, replacing the content of `s`.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, error offsets refer to the
+/// original encoding of `s`.
+pub fn to_value_any_encoding(s: &mut Vec<u8>) -> Result<Value<'_>> {
+    match Deserializer::from_vec_any_encoding(s) {
+        Ok(de) => Ok(BorrowDeserializer::from_deserializer(de).parse()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Borrowed JSON-DOM Value whose objects hash their keys with `S`.
+/// [`Value`] names it with the default hasher.
+#[derive(Clone)]
+pub enum HashedValue<'value, S> {
     /// Static values
     Static(StaticNode),
     /// string type
     String(Cow<'value, str>),
     /// array type
-    Array(Vec<Value<'value>>),
+    Array(Vec<HashedValue<'value, S>>),
     /// object type
-    Object(Box<Object<'value>>),
+    Object(Box<HashedObject<'value, S>>),
+}
+
+impl<'value, S> fmt::Debug for HashedValue<'value, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Static(s) => f.debug_tuple("Static").field(s).finish(),
+            Self::String(s) => f.debug_tuple("String").field(s).finish(),
+            Self::Array(a) => f.debug_tuple("Array").field(a).finish(),
+            Self::Object(o) => f.debug_tuple("Object").field(o).finish(),
+        }
+    }
 }
 
-impl<'value> Value<'value> {
+impl<'value, S> HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     /// Enforces static lifetime on a borrowed value, this will
     /// force all strings to become owned COW's, the same applies for
     /// Object keys.
     #[inline]
     #[must_use]
-    pub fn into_static(self) -> Value<'static> {
+    pub fn into_static(self) -> HashedValue<'static, S> {
         match self {
-            // Ensure strings are static by turing the cow into a 'static
-            // This cow has static lifetime as it's owned, this information however is lost
-            // by the borrow checker so we need to transmute it to static.
-            // This invariant is guaranteed by the implementation of the cow, cloning an owned
-            // value will produce a owned value again see:
-            // https://docs.rs/beef/0.4.4/src/beef/generic.rs.html#379-391
-            Self::String(s) => unsafe {
-                std::mem::transmute::<Value<'value>, Value<'static>>(Self::String(Cow::from(
-                    s.to_string(),
-                )))
-            },
+            // Owned cows don't borrow from the input so they can be
+            // 'static
+            Self::String(s) => HashedValue::String(Cow::from(s.to_string())),
             // For an array we turn every value into a static
-            Self::Array(arr) => arr.into_iter().map(Value::into_static).collect(),
+            Self::Array(arr) => {
+                HashedValue::Array(arr.into_iter().map(Self::into_static).collect())
+            }
             // For an object, we turn all keys into owned Cows and all values into 'static Values
-            Self::Object(obj) => obj
-                .into_iter()
-                .map(|(k, v)| (Cow::from(k.into_owned()), v.into_static()))
-                .collect(),
+            Self::Object(obj) => HashedValue::Object(Box::new(
+                obj.into_iter()
+                    .map(|(k, v)| (Cow::from(k.into_owned()), v.into_static()))
+                    .collect(),
+            )),
 
             // Static nodes are always static
-            Value::Static(s) => Value::Static(s),
+            Self::Static(s) => HashedValue::Static(s),
         }
     }
 
@@ -119,34 +176,30 @@ impl<'value> Value<'value> {
     /// as `into_static` but includes cloning logic
     #[inline]
     #[must_use]
-    pub fn clone_static(&self) -> Value<'static> {
+    pub fn clone_static(&self) -> HashedValue<'static, S> {
         match self {
-            // Ensure strings are static by turing the cow into a 'static
-            // This cow has static lifetime as it's owned, this information however is lost
-            // by the borrow checker so we need to transmute it to static.
-            // This invariant is guaranteed by the implementation of the cow, cloning an owned
-            // value will produce a owned value again see:
-            // https://docs.rs/beef/0.4.4/src/beef/generic.rs.html#379-391
-            Self::String(s) => unsafe {
-                std::mem::transmute::<Value<'value>, Value<'static>>(Self::String(Cow::from(
-                    s.to_string(),
-                )))
-            },
+            // Owned cows don't borrow from the input so they can be
+            // 'static
+            Self::String(s) => HashedValue::String(Cow::from(s.to_string())),
             // For an array we turn every value into a static
-            Self::Array(arr) => arr.iter().cloned().map(Value::into_static).collect(),
+            Self::Array(arr) => HashedValue::Array(arr.iter().map(Self::clone_static).collect()),
             // For an object, we turn all keys into owned Cows and all values into 'static Values
-            Self::Object(obj) => obj
-                .iter()
-                .map(|(k, v)| (Cow::from(k.to_string()), v.clone_static()))
-                .collect(),
+            Self::Object(obj) => HashedValue::Object(Box::new(
+                obj.iter()
+                    .map(|(k, v)| (Cow::from(k.to_string()), v.clone_static()))
+                    .collect(),
+            )),
 
             // Static nodes are always static
-            Value::Static(s) => Value::Static(*s),
+            Self::Static(s) => HashedValue::Static(*s),
         }
     }
 }
 
-impl<'value> Builder<'value> for Value<'value> {
+impl<'value, S> Builder<'value> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn null() -> Self {
@@ -160,14 +213,17 @@ impl<'value> Builder<'value> for Value<'value> {
     #[inline]
     #[must_use]
     fn object_with_capacity(capacity: usize) -> Self {
-        Self::Object(Box::new(Object::with_capacity(capacity)))
+        Self::Object(Box::new(map::with_capacity(capacity)))
     }
 }
 
-impl<'value> Mutable for Value<'value> {
+impl<'value, S> Mutable for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn as_array_mut(&mut self) -> Option<&mut Vec<Value<'value>>> {
+    fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
         match self {
             Self::Array(a) => Some(a),
             _ => None,
@@ -175,15 +231,18 @@ impl<'value> Mutable for Value<'value> {
     }
     #[inline]
     #[must_use]
-    fn as_object_mut(&mut self) -> Option<&mut HashMap<<Self as ValueAccess>::Key, Self>> {
+    fn as_object_mut(&mut self) -> Option<&mut ObjectView<Cow<'value, str>, Self, S>> {
         match self {
-            Self::Object(m) => Some(m),
+            Self::Object(m) => Some(map::view_mut(m)),
             _ => None,
         }
     }
 }
 
-impl<'value> ValueTrait for Value<'value> {
+impl<'value, S> ValueTrait for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn value_type(&self) -> ValueType {
@@ -202,12 +261,14 @@ impl<'value> ValueTrait for Value<'value> {
     }
 }
 
-impl<'value> ValueAccess for Value<'value> {
+impl<'value, S> ValueAccess for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Target = Self;
     type Key = Cow<'value, str>;
     type Array = Vec<Self>;
-    type Object = HashMap<Self::Key, Self>;
-
+    type Object = ObjectView<Cow<'value, str>, Self, S>;
     #[inline]
     #[must_use]
     fn as_bool(&self) -> Option<bool> {
@@ -287,7 +348,7 @@ impl<'value> ValueAccess for Value<'value> {
 
     #[inline]
     #[must_use]
-    fn as_array(&self) -> Option<&Vec<Value<'value>>> {
+    fn as_array(&self) -> Option<&Vec<Self>> {
         match self {
             Self::Array(a) => Some(a),
             _ => None,
@@ -296,16 +357,16 @@ impl<'value> ValueAccess for Value<'value> {
 
     #[inline]
     #[must_use]
-    fn as_object(&self) -> Option<&HashMap<Self::Key, Self>> {
+    fn as_object(&self) -> Option<&ObjectView<Cow<'value, str>, Self, S>> {
         match self {
-            Self::Object(m) => Some(m),
+            Self::Object(m) => Some(map::view(m)),
             _ => None,
         }
     }
 }
 
 #[cfg(not(tarpaulin_include))]
-impl<'value> fmt::Display for Value<'value> {
+impl<'value, S> fmt::Display for HashedValue<'value, S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Static(s) => write!(f, "{}", s),
@@ -316,8 +377,11 @@ impl<'value> fmt::Display for Value<'value> {
     }
 }
 
-impl<'value> Index<&str> for Value<'value> {
-    type Output = Value<'value>;
+impl<'value, S> Index<&str> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Output = Self;
     #[inline]
     #[must_use]
     fn index(&self, index: &str) -> &Self::Output {
@@ -325,8 +389,11 @@ impl<'value> Index<&str> for Value<'value> {
     }
 }
 
-impl<'value> Index<usize> for Value<'value> {
-    type Output = Value<'value>;
+impl<'value, S> Index<usize> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Output = Self;
     #[inline]
     #[must_use]
     fn index(&self, index: usize) -> &Self::Output {
@@ -334,7 +401,10 @@ impl<'value> Index<usize> for Value<'value> {
     }
 }
 
-impl<'value> IndexMut<&str> for Value<'value> {
+impl<'value, S> IndexMut<&str> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn index_mut(&mut self, index: &str) -> &mut Self::Output {
@@ -342,7 +412,10 @@ impl<'value> IndexMut<&str> for Value<'value> {
     }
 }
 
-impl<'value> IndexMut<usize> for Value<'value> {
+impl<'value, S> IndexMut<usize> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
@@ -350,7 +423,7 @@ impl<'value> IndexMut<usize> for Value<'value> {
     }
 }
 
-impl<'value> Default for Value<'value> {
+impl<'value, S> Default for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn default() -> Self {
@@ -366,17 +439,23 @@ impl<'de> BorrowDeserializer<'de> {
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    pub fn parse(&mut self) -> Value<'de> {
+    pub fn parse<S>(&mut self) -> HashedValue<'de, S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
         match unsafe { self.0.next_() } {
-            Node::Static(s) => Value::Static(s),
-            Node::String(s) => Value::from(s),
+            Node::Static(s) => HashedValue::Static(s),
+            Node::String(s) => HashedValue::from(s),
             Node::Array(len, _) => self.parse_array(len),
             Node::Object(len, _) => self.parse_map(len),
         }
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    fn parse_array(&mut self, len: usize) -> Value<'de> {
+    fn parse_array<S>(&mut self, len: usize) -> HashedValue<'de, S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
         // Rust doens't optimize the normal loop away here
         // so we write our own avoiding the lenght
         // checks during push
@@ -387,12 +466,15 @@ impl<'de> BorrowDeserializer<'de> {
                 std::ptr::write(res.get_unchecked_mut(i), self.parse());
             }
         }
-        Value::Array(res)
+        HashedValue::Array(res)
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    fn parse_map(&mut self, len: usize) -> Value<'de> {
-        let mut res = Object::with_capacity(len);
+    fn parse_map<S>(&mut self, len: usize) -> HashedValue<'de, S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
+        let mut res = map::with_capacity(len);
 
         // Since we checked if it's empty we know that we at least have one
         // element so we eat this
@@ -403,7 +485,7 @@ impl<'de> BorrowDeserializer<'de> {
                 unreachable!()
             }
         }
-        Value::from(res)
+        HashedValue::from(res)
     }
 }
 
@@ -412,6 +494,24 @@ mod test {
     #![allow(clippy::cognitive_complexity)]
     use super::*;
 
+    #[test]
+    fn custom_hasher() {
+        use std::collections::hash_map::RandomState;
+        let mut d = br#"{"a": [1, {"b": null}], "c": "d"}"#.to_vec();
+        let mut v: HashedValue<RandomState> =
+            to_value_with_hasher(&mut d).expect("failed to parse");
+        assert_eq!(v["a"][1]["b"], ());
+        assert_eq!(v["c"], "d");
+        assert_eq!(v.insert("e", 1), Ok(None));
+        let mut e = v.encode().into_bytes();
+        let again: HashedValue<RandomState> =
+            to_value_with_hasher(&mut e).expect("failed to parse");
+        assert_eq!(v, again);
+        let owned = crate::value::owned::HashedValue::from(v.clone());
+        assert_eq!(owned, v);
+        assert_eq!(HashedValue::from(owned), v);
+    }
+
     #[test]
     fn object_access() {
         let mut v = Value::null();
@@ -1,9 +1,13 @@
-use super::Value;
+use super::HashedValue;
 use crate::prelude::*;
-use crate::OwnedValue;
+use crate::value::owned;
+use std::hash::BuildHasher;
 
 #[allow(clippy::cast_sign_loss, clippy::default_trait_access)]
-impl<'value> PartialEq for Value<'value> {
+impl<'value, S> PartialEq for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &Self) -> bool {
@@ -17,9 +21,10 @@ impl<'value> PartialEq for Value<'value> {
     }
 }
 
-impl<'value, T> PartialEq<&T> for Value<'value>
+impl<'value, T, S> PartialEq<&T> for HashedValue<'value, S>
 where
-    Value<'value>: PartialEq<T>,
+    Self: PartialEq<T>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
@@ -28,16 +33,22 @@ where
     }
 }
 
-impl<'value> PartialEq<OwnedValue> for Value<'value> {
+impl<'value, S> PartialEq<owned::HashedValue<S>> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn eq(&self, other: &OwnedValue) -> bool {
+    fn eq(&self, other: &owned::HashedValue<S>) -> bool {
         // We only need to implement this once
         other.eq(self)
     }
 }
 
-impl<'v> PartialEq<()> for Value<'v> {
+impl<'v, S> PartialEq<()> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, _other: &()) -> bool {
@@ -45,7 +56,10 @@ impl<'v> PartialEq<()> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<bool> for Value<'v> {
+impl<'v, S> PartialEq<bool> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &bool) -> bool {
@@ -53,7 +67,10 @@ impl<'v> PartialEq<bool> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<str> for Value<'v> {
+impl<'v, S> PartialEq<str> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &str) -> bool {
@@ -61,7 +78,10 @@ impl<'v> PartialEq<str> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<&str> for Value<'v> {
+impl<'v, S> PartialEq<&str> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &&str) -> bool {
@@ -69,7 +89,10 @@ impl<'v> PartialEq<&str> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<String> for Value<'v> {
+impl<'v, S> PartialEq<String> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &String) -> bool {
@@ -77,7 +100,10 @@ impl<'v> PartialEq<String> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<i8> for Value<'v> {
+impl<'v, S> PartialEq<i8> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i8) -> bool {
@@ -85,7 +111,10 @@ impl<'v> PartialEq<i8> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<i16> for Value<'v> {
+impl<'v, S> PartialEq<i16> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i16) -> bool {
@@ -93,7 +122,10 @@ impl<'v> PartialEq<i16> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<i32> for Value<'v> {
+impl<'v, S> PartialEq<i32> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i32) -> bool {
@@ -101,7 +133,10 @@ impl<'v> PartialEq<i32> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<i64> for Value<'v> {
+impl<'v, S> PartialEq<i64> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i64) -> bool {
@@ -109,7 +144,10 @@ impl<'v> PartialEq<i64> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<i128> for Value<'v> {
+impl<'v, S> PartialEq<i128> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i128) -> bool {
@@ -117,7 +155,10 @@ impl<'v> PartialEq<i128> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<u8> for Value<'v> {
+impl<'v, S> PartialEq<u8> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u8) -> bool {
@@ -125,7 +166,10 @@ impl<'v> PartialEq<u8> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<u16> for Value<'v> {
+impl<'v, S> PartialEq<u16> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u16) -> bool {
@@ -133,7 +177,10 @@ impl<'v> PartialEq<u16> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<u32> for Value<'v> {
+impl<'v, S> PartialEq<u32> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u32) -> bool {
@@ -141,7 +188,10 @@ impl<'v> PartialEq<u32> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<u64> for Value<'v> {
+impl<'v, S> PartialEq<u64> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u64) -> bool {
@@ -149,7 +199,10 @@ impl<'v> PartialEq<u64> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<usize> for Value<'v> {
+impl<'v, S> PartialEq<usize> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &usize) -> bool {
@@ -157,7 +210,10 @@ impl<'v> PartialEq<usize> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<u128> for Value<'v> {
+impl<'v, S> PartialEq<u128> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u128) -> bool {
@@ -165,7 +221,10 @@ impl<'v> PartialEq<u128> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<f32> for Value<'v> {
+impl<'v, S> PartialEq<f32> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &f32) -> bool {
@@ -173,7 +232,10 @@ impl<'v> PartialEq<f32> for Value<'v> {
     }
 }
 
-impl<'v> PartialEq<f64> for Value<'v> {
+impl<'v, S> PartialEq<f64> for HashedValue<'v, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &f64) -> bool {
@@ -181,9 +243,10 @@ impl<'v> PartialEq<f64> for Value<'v> {
     }
 }
 
-impl<'v, T> PartialEq<&[T]> for Value<'v>
+impl<'v, T, S> PartialEq<&[T]> for HashedValue<'v, S>
 where
-    Value<'v>: PartialEq<T>,
+    Self: PartialEq<T>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
@@ -192,15 +255,16 @@ where
     }
 }
 
-impl<'v, K, T, S> PartialEq<std::collections::HashMap<K, T, S>> for Value<'v>
+impl<'v, K, T, H, S> PartialEq<std::collections::HashMap<K, T, H>> for HashedValue<'v, S>
 where
     K: AsRef<str> + std::hash::Hash + Eq,
-    Value<'v>: PartialEq<T>,
-    S: std::hash::BuildHasher,
+    Self: PartialEq<T>,
+    H: BuildHasher,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
-    fn eq(&self, other: &std::collections::HashMap<K, T, S>) -> bool {
+    fn eq(&self, other: &std::collections::HashMap<K, T, H>) -> bool {
         self.as_object().map_or(false, |object| {
             object.len() == other.len()
                 && other
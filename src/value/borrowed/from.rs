@@ -1,23 +1,27 @@
-use super::{Object, Value};
+use super::{HashedObject, HashedValue};
 use crate::cow::Cow;
-use crate::OwnedValue;
+use crate::value::owned;
 use crate::StaticNode;
+use std::hash::BuildHasher;
 use std::iter::FromIterator;
 
-impl<'value> From<OwnedValue> for Value<'value> {
+impl<'value, S> From<owned::HashedValue<S>> for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn from(b: OwnedValue) -> Self {
+    fn from(b: owned::HashedValue<S>) -> Self {
         match b {
-            OwnedValue::Static(s) => Value::from(s),
-            OwnedValue::String(s) => Value::from(s),
-            OwnedValue::Array(a) => a.into_iter().collect(),
-            OwnedValue::Object(m) => m.into_iter().collect(),
+            owned::HashedValue::Static(s) => Self::from(s),
+            owned::HashedValue::String(s) => Self::from(s),
+            owned::HashedValue::Array(a) => a.into_iter().collect(),
+            owned::HashedValue::Object(m) => m.into_iter().collect(),
         }
     }
 }
 
-impl<'value> From<StaticNode> for Value<'value> {
+impl<'value, S> From<StaticNode> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(s: StaticNode) -> Self {
@@ -25,45 +29,45 @@ impl<'value> From<StaticNode> for Value<'value> {
     }
 }
 
-impl<'value, T> From<Option<T>> for Value<'value>
+impl<'value, T, S> From<Option<T>> for HashedValue<'value, S>
 where
-    Value<'value>: From<T>,
+    Self: From<T>,
 {
     #[inline]
     #[must_use]
     fn from(s: Option<T>) -> Self {
-        s.map_or(Value::Static(StaticNode::Null), Value::from)
+        s.map_or(Self::Static(StaticNode::Null), Self::from)
     }
 }
 /********* str_ **********/
-impl<'value> From<&'value str> for Value<'value> {
+impl<'value, S> From<&'value str> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(s: &'value str) -> Self {
-        Value::String(Cow::from(s))
+        Self::String(Cow::from(s))
     }
 }
 
 #[cfg(feature = "beef")]
-impl<'value> From<std::borrow::Cow<'value, str>> for Value<'value> {
+impl<'value, S> From<std::borrow::Cow<'value, str>> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(c: std::borrow::Cow<'value, str>) -> Self {
-        Value::String(c.into())
+        Self::String(c.into())
     }
 }
 
 #[cfg(not(feature = "beef"))]
-impl<'value> From<std::borrow::Cow<'value, str>> for Value<'value> {
+impl<'value, S> From<std::borrow::Cow<'value, str>> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(c: std::borrow::Cow<'value, str>) -> Self {
-        Value::String(c)
+        Self::String(c)
     }
 }
 
 #[cfg(feature = "beef")]
-impl<'value> From<beef::lean::Cow<'value, str>> for Value<'value> {
+impl<'value, S> From<beef::lean::Cow<'value, str>> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(c: beef::lean::Cow<'value, str>) -> Self {
@@ -71,74 +75,74 @@ impl<'value> From<beef::lean::Cow<'value, str>> for Value<'value> {
     }
 }
 
-impl<'value> From<String> for Value<'value> {
+impl<'value, S> From<String> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(s: String) -> Self {
-        Value::String(s.into())
+        Self::String(s.into())
     }
 }
 
 /********* atoms **********/
-impl<'value> From<bool> for Value<'value> {
+impl<'value, S> From<bool> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(b: bool) -> Self {
-        Value::Static(StaticNode::Bool(b))
+        Self::Static(StaticNode::Bool(b))
     }
 }
-impl<'value> From<()> for Value<'value> {
+impl<'value, S> From<()> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(_b: ()) -> Self {
-        Value::Static(StaticNode::Null)
+        Self::Static(StaticNode::Null)
     }
 }
 
 /********* i_ **********/
-impl<'value> From<i8> for Value<'value> {
+impl<'value, S> From<i8> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: i8) -> Self {
-        Value::Static(StaticNode::I64(i64::from(i)))
+        Self::Static(StaticNode::I64(i64::from(i)))
     }
 }
 
-impl<'value> From<i16> for Value<'value> {
+impl<'value, S> From<i16> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: i16) -> Self {
-        Value::Static(StaticNode::I64(i64::from(i)))
+        Self::Static(StaticNode::I64(i64::from(i)))
     }
 }
 
-impl<'value> From<i32> for Value<'value> {
+impl<'value, S> From<i32> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: i32) -> Self {
-        Value::Static(StaticNode::I64(i64::from(i)))
+        Self::Static(StaticNode::I64(i64::from(i)))
     }
 }
 
-impl<'value> From<i64> for Value<'value> {
+impl<'value, S> From<i64> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: i64) -> Self {
-        Value::Static(StaticNode::I64(i))
+        Self::Static(StaticNode::I64(i))
     }
 }
 
 #[cfg(feature = "128bit")]
-impl<'value> From<i128> for Value<'value> {
+impl<'value, S> From<i128> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: i128) -> Self {
-        Value::Static(StaticNode::I128(i))
+        Self::Static(StaticNode::I128(i))
     }
 }
 
 /********* u_ **********/
-impl<'value> From<u8> for Value<'value> {
+impl<'value, S> From<u8> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: u8) -> Self {
@@ -146,7 +150,7 @@ impl<'value> From<u8> for Value<'value> {
     }
 }
 
-impl<'value> From<u16> for Value<'value> {
+impl<'value, S> From<u16> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: u16) -> Self {
@@ -154,7 +158,7 @@ impl<'value> From<u16> for Value<'value> {
     }
 }
 
-impl<'value> From<u32> for Value<'value> {
+impl<'value, S> From<u32> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: u32) -> Self {
@@ -162,24 +166,24 @@ impl<'value> From<u32> for Value<'value> {
     }
 }
 
-impl<'value> From<u64> for Value<'value> {
+impl<'value, S> From<u64> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: u64) -> Self {
-        Value::Static(StaticNode::U64(i))
+        Self::Static(StaticNode::U64(i))
     }
 }
 
 #[cfg(feature = "128bit")]
-impl<'value> From<u128> for Value<'value> {
+impl<'value, S> From<u128> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: u128) -> Self {
-        Value::Static(StaticNode::U128(i))
+        Self::Static(StaticNode::U128(i))
     }
 }
 
-impl<'value> From<usize> for Value<'value> {
+impl<'value, S> From<usize> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(i: usize) -> Self {
@@ -188,48 +192,54 @@ impl<'value> From<usize> for Value<'value> {
 }
 
 /********* f_ **********/
-impl<'value> From<f32> for Value<'value> {
+impl<'value, S> From<f32> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(f: f32) -> Self {
-        Value::Static(StaticNode::F64(f64::from(f)))
+        Self::Static(StaticNode::F64(f64::from(f)))
     }
 }
 
-impl<'value> From<f64> for Value<'value> {
+impl<'value, S> From<f64> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
     fn from(f: f64) -> Self {
-        Value::Static(StaticNode::F64(f))
+        Self::Static(StaticNode::F64(f))
     }
 }
 
-impl<'value, S> From<Vec<S>> for Value<'value>
+impl<'value, T, S> From<Vec<T>> for HashedValue<'value, S>
 where
-    Value<'value>: From<S>,
+    Self: From<T>,
 {
     #[inline]
     #[must_use]
-    fn from(v: Vec<S>) -> Self {
+    fn from(v: Vec<T>) -> Self {
         v.into_iter().collect()
     }
 }
 
-impl<'value, V: Into<Value<'value>>> FromIterator<V> for Value<'value> {
+impl<'value, V, S> FromIterator<V> for HashedValue<'value, S>
+where
+    V: Into<Self>,
+{
     #[inline]
     #[must_use]
     fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
-        Value::Array(iter.into_iter().map(Into::into).collect())
+        Self::Array(iter.into_iter().map(Into::into).collect())
     }
 }
 
-impl<'value, K: Into<Cow<'value, str>>, V: Into<Value<'value>>> FromIterator<(K, V)>
-    for Value<'value>
+impl<'value, K, V, S> FromIterator<(K, V)> for HashedValue<'value, S>
+where
+    K: Into<Cow<'value, str>>,
+    V: Into<Self>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        Value::Object(Box::new(
+        Self::Object(Box::new(
             iter.into_iter()
                 .map(|(k, v)| (Into::into(k), Into::into(v)))
                 .collect(),
@@ -237,10 +247,23 @@ impl<'value, K: Into<Cow<'value, str>>, V: Into<Value<'value>>> FromIterator<(K,
     }
 }
 
-impl<'value> From<Object<'value>> for Value<'value> {
+impl<'value, S> From<HashedObject<'value, S>> for HashedValue<'value, S> {
     #[inline]
     #[must_use]
-    fn from(v: Object<'value>) -> Self {
+    fn from(v: HashedObject<'value, S>) -> Self {
         Self::Object(Box::new(v))
     }
 }
+
+#[cfg(feature = "preserve_order")]
+impl<'value, S> From<halfbrown::HashMap<Cow<'value, str>, HashedValue<'value, S>, S>>
+    for HashedValue<'value, S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    #[inline]
+    #[must_use]
+    fn from(v: halfbrown::HashMap<Cow<'value, str>, Self, S>) -> Self {
+        Self::from(HashedObject::from(v))
+    }
+}
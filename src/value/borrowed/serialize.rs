@@ -4,10 +4,12 @@
 //
 // https://github.com/maciejhirsz/json-rust/blob/master/src/codegen.rs
 
-use super::{Object, Value};
+use super::{HashedObject, HashedValue, Value};
+use crate::format::{CompactGenerator, FormattedGenerator, Formatter};
 use crate::prelude::*;
 use crate::stry;
 use crate::StaticNode;
+use crate::{Escape, PrettyFormatter};
 use std::io;
 use std::io::Write;
 use value_trait::generator::{
@@ -16,18 +18,18 @@ use value_trait::generator::{
 
 //use util::print_dec;
 
-impl<'value> Writable for Value<'value> {
+impl<'value, S> Writable for HashedValue<'value, S> {
     #[inline]
     fn encode(&self) -> String {
-        let mut g = DumpGenerator::new();
-        let _r = g.write_json(&self);
+        let mut g = DumpGenerator::<Value<'value>>::new();
+        let _r = g.write_json(self);
         g.consume()
     }
 
     #[inline]
     fn encode_pp(&self) -> String {
-        let mut g = PrettyGenerator::new(2);
-        let _r = g.write_json(&self);
+        let mut g = PrettyGenerator::<Value<'value>>::new(2);
+        let _r = g.write_json(self);
         g.consume()
     }
 
@@ -36,7 +38,7 @@ impl<'value> Writable for Value<'value> {
     where
         W: 'writer + Write,
     {
-        let mut g = WriterGenerator::new(w);
+        let mut g = WriterGenerator::<_, Value<'value>>::new(w);
         g.write_json(self)
     }
 
@@ -45,7 +47,80 @@ impl<'value> Writable for Value<'value> {
     where
         W: 'writer + Write,
     {
-        let mut g = PrettyWriterGenerator::new(w, 2);
+        let mut g = PrettyWriterGenerator::<_, Value<'value>>::new(w, 2);
+        g.write_json(self)
+    }
+}
+
+impl<S> HashedValue<'_, S> {
+    /// Encodes the value into a string laid out by `formatter`
+    #[must_use]
+    pub fn encode_formatted<F>(&self, formatter: F) -> String
+    where
+        F: Formatter,
+    {
+        let mut json = Vec::with_capacity(512);
+        let _r = self.write_formatted(&mut json, formatter);
+        unsafe { String::from_utf8_unchecked(json) }
+    }
+
+    /// Writes the value to `w` laid out by `formatter`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails
+    pub fn write_formatted<W, F>(&self, w: &mut W, formatter: F) -> io::Result<()>
+    where
+        W: Write,
+        F: Formatter,
+    {
+        let mut g = FormattedGenerator::new(w, formatter, Escape::new());
+        g.write_json(self)
+    }
+
+    /// Encodes the value like `encode` but escapes strings according to
+    /// `escape`
+    #[must_use]
+    pub fn encode_escaped(&self, escape: Escape) -> String {
+        let mut json = Vec::with_capacity(512);
+        let _r = self.write_escaped(&mut json, escape);
+        unsafe { String::from_utf8_unchecked(json) }
+    }
+
+    /// Encodes the value like `encode_pp` but escapes strings according to
+    /// `escape`
+    #[must_use]
+    pub fn encode_pp_escaped(&self, escape: Escape) -> String {
+        let mut json = Vec::with_capacity(512);
+        let _r = self.write_pp_escaped(&mut json, escape);
+        unsafe { String::from_utf8_unchecked(json) }
+    }
+
+    /// Writes the value like `write` but escapes strings according to
+    /// `escape`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails
+    pub fn write_escaped<W>(&self, w: &mut W, escape: Escape) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut g = CompactGenerator::new(w, escape);
+        g.write_json(self)
+    }
+
+    /// Writes the value like `write_pp` but escapes strings according to
+    /// `escape`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing to `w` fails
+    pub fn write_pp_escaped<W>(&self, w: &mut W, escape: Escape) -> io::Result<()>
+    where
+        W: Write,
+    {
+        let mut g = FormattedGenerator::new(w, PrettyFormatter::new(), escape);
         g.write_json(self)
     }
 }
@@ -54,7 +129,7 @@ trait Generator: BaseGenerator {
     type T: Write;
 
     #[inline(always)]
-    fn write_object(&mut self, object: &Object) -> io::Result<()> {
+    fn write_object<S>(&mut self, object: &HashedObject<'_, S>) -> io::Result<()> {
         if object.is_empty() {
             self.write(b"{}")
         } else {
@@ -88,24 +163,24 @@ trait Generator: BaseGenerator {
     }
 
     #[inline(always)]
-    fn write_json(&mut self, json: &Value) -> io::Result<()> {
+    fn write_json<S>(&mut self, json: &HashedValue<'_, S>) -> io::Result<()> {
         match *json {
-            Value::Static(StaticNode::Null) => self.write(b"null"),
-            Value::Static(StaticNode::I64(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::Null) => self.write(b"null"),
+            HashedValue::Static(StaticNode::I64(number)) => self.write_int(number),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::I128(number)) => self.write_int(number),
-            Value::Static(StaticNode::U64(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::I128(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::U64(number)) => self.write_int(number),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::U128(number)) => self.write_int(number),
-            Value::Static(StaticNode::F64(number)) => self.write_float(number),
-            Value::Static(StaticNode::Bool(true)) => self.write(b"true"),
-            Value::Static(StaticNode::Bool(false)) => self.write(b"false"),
-            Value::String(ref string) => self.write_string(string),
-            Value::Array(ref array) => {
+            HashedValue::Static(StaticNode::U128(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::F64(number)) => self.write_float(number),
+            HashedValue::Static(StaticNode::Bool(true)) => self.write(b"true"),
+            HashedValue::Static(StaticNode::Bool(false)) => self.write(b"false"),
+            HashedValue::String(ref string) => self.write_string(string),
+            HashedValue::Array(ref array) => {
                 if array.is_empty() {
                     self.write(b"[]")
                 } else {
-                    let mut iter = <[Value]>::iter(array);
+                    let mut iter = <[HashedValue<'_, S>]>::iter(array);
                     // We know we have one item
 
                     let item = if let Some(v) = iter.next() {
@@ -130,7 +205,7 @@ trait Generator: BaseGenerator {
                     self.write(b"]")
                 }
             }
-            Value::Object(ref object) => self.write_object(object),
+            HashedValue::Object(ref object) => self.write_object(object),
         }
     }
 }
@@ -139,7 +214,7 @@ trait FastGenerator: BaseGenerator {
     type T: Write;
 
     #[inline(always)]
-    fn write_object(&mut self, object: &Object) -> io::Result<()> {
+    fn write_object<S>(&mut self, object: &HashedObject<'_, S>) -> io::Result<()> {
         if object.is_empty() {
             self.write(b"{}")
         } else {
@@ -168,24 +243,24 @@ trait FastGenerator: BaseGenerator {
     }
 
     #[inline(always)]
-    fn write_json(&mut self, json: &Value) -> io::Result<()> {
+    fn write_json<S>(&mut self, json: &HashedValue<'_, S>) -> io::Result<()> {
         match *json {
-            Value::Static(StaticNode::Null) => self.write(b"null"),
-            Value::Static(StaticNode::I64(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::Null) => self.write(b"null"),
+            HashedValue::Static(StaticNode::I64(number)) => self.write_int(number),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::I128(number)) => self.write_int(number),
-            Value::Static(StaticNode::U64(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::I128(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::U64(number)) => self.write_int(number),
             #[cfg(feature = "128bit")]
-            Value::Static(StaticNode::U128(number)) => self.write_int(number),
-            Value::Static(StaticNode::F64(number)) => self.write_float(number),
-            Value::Static(StaticNode::Bool(true)) => self.write(b"true"),
-            Value::Static(StaticNode::Bool(false)) => self.write(b"false"),
-            Value::String(ref string) => self.write_string(string),
-            Value::Array(ref array) => {
+            HashedValue::Static(StaticNode::U128(number)) => self.write_int(number),
+            HashedValue::Static(StaticNode::F64(number)) => self.write_float(number),
+            HashedValue::Static(StaticNode::Bool(true)) => self.write(b"true"),
+            HashedValue::Static(StaticNode::Bool(false)) => self.write(b"false"),
+            HashedValue::String(ref string) => self.write_string(string),
+            HashedValue::Array(ref array) => {
                 if array.is_empty() {
                     self.write(b"[]")
                 } else {
-                    let mut iter = <[Value]>::iter(array);
+                    let mut iter = <[HashedValue<'_, S>]>::iter(array);
                     // We know we have one item
                     let item = if let Some(v) = iter.next() {
                         v
@@ -204,7 +279,7 @@ trait FastGenerator: BaseGenerator {
                     self.write(b"]")
                 }
             }
-            Value::Object(ref object) => self.write_object(object),
+            HashedValue::Object(ref object) => self.write_object(object),
         }
     }
 }
@@ -231,10 +306,25 @@ where
     type T = W;
 }
 
+impl<W> FastGenerator for CompactGenerator<'_, W>
+where
+    W: Write,
+{
+    type T = W;
+}
+
+impl<W, F> Generator for FormattedGenerator<'_, W, F>
+where
+    W: Write,
+    F: Formatter,
+{
+    type T = W;
+}
+
 #[cfg(test)]
 mod test {
-    use super::Value;
     use crate::prelude::*;
+    use crate::value::borrowed::Value;
     use crate::StaticNode;
 
     #[test]
@@ -0,0 +1,306 @@
+//! Map that holds the members of objects.
+//!
+//! [`ObjectMap`] is a `halfbrown::HashMap`, or with the `preserve_order`
+//! feature a wrapper around an `indexmap::IndexMap` that iterates in
+//! insertion order. It is generic over the hasher so values can pick one
+//! per use, for example a DOS resistant hasher for untrusted input and a
+//! fixed one for internal data that is looked up with `KnownKey`.
+//!
+//! `value-trait` implements its `Object` trait for a `halfbrown::HashMap`
+//! with the default hasher only, so the `value_trait` accessors hand out
+//! an [`ObjectView`] of the map instead. It dereferences to the
+//! [`ObjectMap`], with `preserve_order` it is the [`ObjectMap`] itself.
+
+use std::hash::{BuildHasher, Hash};
+#[cfg(not(feature = "preserve_order"))]
+use std::{
+    borrow::Borrow,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(not(feature = "preserve_order"))]
+pub use halfbrown::{
+    IntoIter, Iter, IterMut, Keys, RawEntryBuilder, RawEntryBuilderMut, RawEntryMut,
+    RawOccupiedEntryMut, RawVacantEntryMut, Values, ValuesMut,
+};
+
+/// Map for the members of objects, hashing keys with `S`
+#[cfg(not(feature = "preserve_order"))]
+pub type ObjectMap<K, V, S = halfbrown::DefaultHashBuilder> = halfbrown::HashMap<K, V, S>;
+
+#[cfg(feature = "preserve_order")]
+mod ordered;
+#[cfg(feature = "preserve_order")]
+pub use self::ordered::*;
+
+/// An [`ObjectMap`] as the `value_trait` accessors see it
+#[cfg(not(feature = "preserve_order"))]
+#[repr(transparent)]
+pub struct ObjectView<K, V, S = halfbrown::DefaultHashBuilder>(ObjectMap<K, V, S>);
+
+/// An [`ObjectMap`] as the `value_trait` accessors see it
+#[cfg(feature = "preserve_order")]
+pub type ObjectView<K, V, S = halfbrown::DefaultHashBuilder> = ObjectMap<K, V, S>;
+
+/// Views a map as the `value_trait` accessors see it
+#[inline]
+pub(crate) fn view<K, V, S>(map: &ObjectMap<K, V, S>) -> &ObjectView<K, V, S> {
+    // `ObjectView` is a transparent wrapper of the map
+    #[cfg(not(feature = "preserve_order"))]
+    let view = unsafe { &*(map as *const ObjectMap<K, V, S>).cast::<ObjectView<K, V, S>>() };
+    #[cfg(feature = "preserve_order")]
+    let view = map;
+    view
+}
+
+/// Views a map mutably as the `value_trait` accessors see it
+#[inline]
+pub(crate) fn view_mut<K, V, S>(map: &mut ObjectMap<K, V, S>) -> &mut ObjectView<K, V, S> {
+    // `ObjectView` is a transparent wrapper of the map
+    #[cfg(not(feature = "preserve_order"))]
+    let view = unsafe { &mut *(map as *mut ObjectMap<K, V, S>).cast::<ObjectView<K, V, S>>() };
+    #[cfg(feature = "preserve_order")]
+    let view = map;
+    view
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<K, V, S> Deref for ObjectView<K, V, S> {
+    type Target = ObjectMap<K, V, S>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<K, V, S> DerefMut for ObjectView<K, V, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<K, V, S> fmt::Debug for ObjectView<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<K, V, S, S1> PartialEq<ObjectView<K, V, S1>> for ObjectView<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+    S1: BuildHasher,
+{
+    #[inline]
+    fn eq(&self, other: &ObjectView<K, V, S1>) -> bool {
+        self.0 == other.0
+    }
+}
+
+#[cfg(not(feature = "preserve_order"))]
+impl<MapK, MapE, S> value_trait::Object for ObjectView<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    type Key = MapK;
+    type Element = MapE;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0.get(k)
+    }
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0.get_mut(k)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        self.0.insert(k.into(), v.into())
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        self.0.remove(k)
+    }
+
+    #[inline]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&'i Self::Key, &'i Self::Element)> + 'i> {
+        Box::new(self.0.iter())
+    }
+
+    #[inline]
+    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Key> + 'i> {
+        Box::new(self.0.keys())
+    }
+
+    #[inline]
+    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i> {
+        Box::new(self.0.values())
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Creates an empty map with space for at least `capacity` entries and a
+/// default hasher, small maps start out as a vector just like
+/// `with_capacity` does for the default hasher
+#[cfg_attr(not(feature = "no-inline"), inline(always))]
+pub(crate) fn with_capacity<K, V, S>(capacity: usize) -> ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[cfg(not(feature = "preserve_order"))]
+    let map = if capacity > halfbrown::VEC_LIMIT_UPPER {
+        ObjectMap::with_capacity_and_hasher(capacity, S::default())
+    } else {
+        let mut map = ObjectMap::default();
+        map.reserve(capacity);
+        map
+    };
+    #[cfg(feature = "preserve_order")]
+    let map = ObjectMap::with_capacity_and_hasher(capacity, S::default());
+    map
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    #[cfg(feature = "preserve_order")]
+    use crate::prelude::*;
+    #[cfg(feature = "preserve_order")]
+    use crate::{to_borrowed_value, to_owned_value};
+
+    #[cfg(feature = "preserve_order")]
+    const INPUT: &str = r#"{"z":1,"a":{"y":[{"k":1,"c":2}],"b":null},"m":"x"}"#;
+
+    #[test]
+    fn hashers() {
+        use std::collections::hash_map::RandomState;
+        let mut m = ObjectMap::with_hasher(RandomState::new());
+        let mut big: ObjectMap<_, _, RandomState> = with_capacity(64);
+        for i in 0..64_u8 {
+            assert_eq!(big.insert(i, i), None);
+        }
+        assert_eq!(m.insert("a", 1), None);
+        assert_eq!(m.insert("b", 2), None);
+        assert_eq!(m.insert("a", 3), Some(1));
+        assert_eq!(m.get("a"), Some(&3));
+        let other: ObjectMap<_, _> = vec![("b", 2), ("a", 3)].into_iter().collect();
+        assert_eq!(m, other);
+        assert_eq!(big.len(), 64);
+        assert_eq!(big[&42], 42);
+        assert_eq!(big.remove(&42), Some(42));
+        assert!(!big.contains_key(&42));
+    }
+
+    #[cfg(not(feature = "preserve_order"))]
+    #[test]
+    fn views() {
+        use crate::prelude::*;
+        use std::collections::hash_map::RandomState;
+        let mut o: crate::value::owned::Object = halfbrown::HashMap::new();
+        o.insert("a".to_string(), 1.into());
+        let v = crate::OwnedValue::from(o);
+        let o: &crate::value::owned::Object = v.as_object().unwrap();
+        assert_eq!(o.get("a"), Some(&1.into()));
+        let mut d = br#"{"a": 1}"#.to_vec();
+        let mut v: crate::value::owned::HashedValue<RandomState> =
+            crate::to_owned_value_with_hasher(&mut d).unwrap();
+        assert_eq!(v.as_object().unwrap().len(), 1);
+        assert_eq!(v.insert("b", 2).unwrap(), None);
+        assert!(v.as_object_mut().unwrap().contains_key("b"));
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn insertion_order() {
+        let mut m = ObjectMap::new();
+        for k in &["z", "a", "m", "b"] {
+            assert_eq!(m.insert(*k, k.len()), None);
+        }
+        assert_eq!(m.insert("a", 2), Some(1));
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), ["z", "a", "m", "b"]);
+        assert_eq!(m.remove("a"), Some(2));
+        assert_eq!(m.keys().copied().collect::<Vec<_>>(), ["z", "m", "b"]);
+        assert_eq!(m["m"], 1);
+        let other: ObjectMap<_, _> = vec![("b", 1), ("m", 1), ("z", 1)].into_iter().collect();
+        assert_eq!(m, other);
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn roundtrip() {
+        let mut d = INPUT.as_bytes().to_vec();
+        assert_eq!(to_owned_value(&mut d).unwrap().encode(), INPUT);
+        let mut d = INPUT.as_bytes().to_vec();
+        let mut v = to_borrowed_value(&mut d).unwrap();
+        assert_eq!(v.encode(), INPUT);
+        v.insert("b", 2).unwrap();
+        v.remove("a").unwrap();
+        assert_eq!(v.encode(), r#"{"z":1,"m":"x","b":2}"#);
+        let keys: Vec<_> = v.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["z", "m", "b"]);
+    }
+
+    #[cfg(all(feature = "preserve_order", feature = "serde_impl"))]
+    #[test]
+    fn serde() {
+        let mut d = INPUT.as_bytes().to_vec();
+        let v: crate::OwnedValue = crate::from_slice(&mut d).unwrap();
+        assert_eq!(crate::to_string(&v).unwrap(), INPUT);
+        let m: ObjectMap<String, u8> = serde_json::from_str(r#"{"b":1,"a":2}"#).unwrap();
+        assert_eq!(serde_json::to_string(&m).unwrap(), r#"{"b":1,"a":2}"#);
+    }
+
+    #[cfg(all(feature = "preserve_order", feature = "known-key"))]
+    #[test]
+    fn known_key() {
+        let mut d = INPUT.as_bytes().to_vec();
+        let mut v = to_borrowed_value(&mut d).unwrap();
+        let m = crate::KnownKey::from("m");
+        let n = crate::KnownKey::from("n");
+        assert_eq!(m.lookup(&v).unwrap(), "x");
+        assert!(n.insert(&mut v, "y".into()).unwrap().is_none());
+        assert_eq!(*n.lookup_or_insert_mut(&mut v, || 1.into()).unwrap(), "y");
+        assert_eq!(
+            v.encode(),
+            r#"{"z":1,"a":{"y":[{"k":1,"c":2}],"b":null},"m":"x","n":"y"}"#
+        );
+    }
+}
@@ -0,0 +1,522 @@
+//! The `indexmap::IndexMap` backed [`ObjectMap`] of the `preserve_order`
+//! feature. It offers the `halfbrown::HashMap` methods the crate uses, so
+//! code works with either map, and dereferences to the `IndexMap` for
+//! everything else.
+
+use halfbrown::DefaultHashBuilder;
+use indexmap::map::{IndexMap, RawEntryApiV1};
+use std::borrow::Borrow;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+use std::ops::{Deref, DerefMut, Index};
+
+pub use indexmap::map::raw_entry_v1::{
+    RawEntryBuilder, RawEntryBuilderMut, RawEntryMut, RawOccupiedEntryMut, RawVacantEntryMut,
+};
+pub use indexmap::map::{IntoIter, Iter, IterMut, Keys, Values, ValuesMut};
+
+/// The map that is wrapped
+pub type Inner<K, V, S> = IndexMap<K, V, S>;
+
+/// Map for the members of objects, hashing keys with `S`
+#[derive(Clone)]
+pub struct ObjectMap<K, V, S = DefaultHashBuilder>(Inner<K, V, S>);
+
+impl<K, V, S> Default for ObjectMap<K, V, S>
+where
+    S: Default,
+{
+    #[inline]
+    fn default() -> Self {
+        Self(Inner::default())
+    }
+}
+
+impl<K, V, S> fmt::Debug for ObjectMap<K, V, S>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.0.iter()).finish()
+    }
+}
+
+impl<K, V, S> Deref for ObjectMap<K, V, S> {
+    type Target = Inner<K, V, S>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<K, V, S> DerefMut for ObjectMap<K, V, S> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<K, V> ObjectMap<K, V> {
+    /// Creates an empty map
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with space for at least `capacity` entries
+    #[inline]
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, DefaultHashBuilder::default())
+    }
+}
+
+impl<K, V, S> ObjectMap<K, V, S> {
+    /// Creates an empty map that hashes keys with `hash_builder`
+    #[inline]
+    #[must_use]
+    pub fn with_hasher(hash_builder: S) -> Self {
+        Self(Inner::with_hasher(hash_builder))
+    }
+
+    /// Creates an empty map with space for at least `capacity` entries
+    /// that hashes keys with `hash_builder`
+    #[inline]
+    #[must_use]
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self(Inner::with_capacity_and_hasher(capacity, hash_builder))
+    }
+
+    /// The hasher of the map
+    #[inline]
+    #[must_use]
+    pub fn hasher(&self) -> &S {
+        self.0.hasher()
+    }
+
+    /// Number of entries the map can hold without reallocating
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Iterates over the keys
+    #[inline]
+    #[must_use]
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.0.keys()
+    }
+
+    /// Iterates over the values
+    #[inline]
+    #[must_use]
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.0.values()
+    }
+
+    /// Iterates mutably over the values
+    #[inline]
+    #[must_use]
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.0.values_mut()
+    }
+
+    /// Iterates over the entries
+    #[inline]
+    #[must_use]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.0.iter()
+    }
+
+    /// Iterates over the entries with mutable values
+    #[inline]
+    #[must_use]
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.0.iter_mut()
+    }
+
+    /// Number of entries in the map
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the map has no entries
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Removes all entries
+    #[inline]
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Unwraps the wrapped map
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> Inner<K, V, S> {
+        self.0
+    }
+}
+
+impl<K, V, S> ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    /// Reserves space for at least `additional` more entries
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Gets a reference to the value of a key
+    #[inline]
+    #[must_use]
+    pub fn get<Q>(&self, k: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.get(k)
+    }
+
+    /// Returns `true` if the map contains the key
+    #[inline]
+    #[must_use]
+    pub fn contains_key<Q>(&self, k: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.contains_key(k)
+    }
+
+    /// Gets a mutable reference to the value of a key
+    #[inline]
+    #[must_use]
+    pub fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.get_mut(k)
+    }
+
+    /// Inserts a key value pair and returns the old value if the key was
+    /// present. A new key is appended and an existing one keeps its
+    /// position.
+    #[inline]
+    pub fn insert(&mut self, k: K, v: V) -> Option<V> {
+        self.0.insert(k, v)
+    }
+
+    /// Inserts a key value pair without checking if the key is present,
+    /// the caller has to know that it isn't. Unlike `halfbrown` the check
+    /// can't be skipped.
+    #[inline]
+    pub fn insert_nocheck(&mut self, k: K, v: V) {
+        self.0.insert(k, v);
+    }
+
+    /// Removes a key and returns its value, the entries after it move up
+    /// to keep their order
+    #[inline]
+    pub fn remove<Q>(&mut self, k: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.shift_remove(k)
+    }
+
+    /// Removes a key and returns it along with its value, the entries
+    /// after it move up to keep their order
+    #[inline]
+    pub fn remove_entry<Q>(&mut self, k: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.0.shift_remove_entry(k)
+    }
+
+    /// Keeps only the entries for which `f` returns `true`
+    #[inline]
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.0.retain(f);
+    }
+
+    /// Creates a raw entry builder for lookups with a precomputed hash
+    #[inline]
+    #[must_use]
+    pub fn raw_entry(&self) -> RawEntryBuilder<'_, K, V, S> {
+        self.0.raw_entry_v1()
+    }
+
+    /// Creates a raw entry builder for lookups and insertions with a
+    /// precomputed hash
+    #[inline]
+    #[must_use]
+    pub fn raw_entry_mut(&mut self) -> RawEntryBuilderMut<'_, K, V, S> {
+        self.0.raw_entry_mut_v1()
+    }
+}
+
+impl<K, V, S> From<halfbrown::HashMap<K, V, S>> for ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from(map: halfbrown::HashMap<K, V, S>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
+impl<K, V, S, S1> PartialEq<ObjectMap<K, V, S1>> for ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+    S: BuildHasher,
+    S1: BuildHasher,
+{
+    /// Maps are equal if they have the same entries, in any order
+    #[inline]
+    fn eq(&self, other: &ObjectMap<K, V, S1>) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<K, V, S> Eq for ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq,
+    S: BuildHasher,
+{
+}
+
+impl<K, Q, V, S> Index<&Q> for ObjectMap<K, V, S>
+where
+    K: Eq + Hash + Borrow<Q>,
+    Q: ?Sized + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    type Output = V;
+
+    #[inline]
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("no entry found for key")
+    }
+}
+
+impl<K, V, S> FromIterator<(K, V)> for ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for ObjectMap<K, V, S>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    #[inline]
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        for (k, v) in iter {
+            self.insert(k, v);
+        }
+    }
+}
+
+impl<K, V, S> IntoIterator for ObjectMap<K, V, S> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a ObjectMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a mut ObjectMap<K, V, S> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+impl<MapK, MapE, S> value_trait::Object for ObjectMap<MapK, MapE, S>
+where
+    MapK: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    type Key = MapK;
+    type Element = MapE;
+
+    #[inline]
+    fn get<Q>(&self, k: &Q) -> Option<&Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        ObjectMap::get(self, k)
+    }
+
+    #[inline]
+    fn get_mut<Q>(&mut self, k: &Q) -> Option<&mut Self::Element>
+    where
+        Self::Key: Borrow<Q> + Hash + Eq,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        ObjectMap::get_mut(self, k)
+    }
+
+    #[inline]
+    fn insert<K, V>(&mut self, k: K, v: V) -> Option<Self::Element>
+    where
+        K: Into<Self::Key>,
+        V: Into<Self::Element>,
+        Self::Key: Hash + Eq,
+    {
+        ObjectMap::insert(self, k.into(), v.into())
+    }
+
+    #[inline]
+    fn remove<Q>(&mut self, k: &Q) -> Option<Self::Element>
+    where
+        Self::Key: Borrow<Q>,
+        Q: ?Sized + Hash + Eq + Ord,
+    {
+        ObjectMap::remove(self, k)
+    }
+
+    #[inline]
+    fn iter<'i>(&'i self) -> Box<dyn Iterator<Item = (&'i Self::Key, &'i Self::Element)> + 'i> {
+        Box::new(ObjectMap::iter(self))
+    }
+
+    #[inline]
+    fn keys<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Key> + 'i> {
+        Box::new(ObjectMap::keys(self))
+    }
+
+    #[inline]
+    fn values<'i>(&'i self) -> Box<dyn Iterator<Item = &'i Self::Element> + 'i> {
+        Box::new(ObjectMap::values(self))
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        ObjectMap::len(self)
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+mod se {
+    use super::ObjectMap;
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+    use std::hash::Hash;
+
+    impl<K, V, H> Serialize for ObjectMap<K, V, H>
+    where
+        K: Eq + Hash + Serialize,
+        V: Serialize,
+    {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut map = serializer.serialize_map(Some(self.len()))?;
+            for (k, v) in self {
+                map.serialize_entry(k, v)?;
+            }
+            map.end()
+        }
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+mod de {
+    use super::ObjectMap;
+    use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+    use std::fmt;
+    use std::hash::{BuildHasher, Hash};
+    use std::marker::PhantomData;
+
+    impl<'de, K, V, H> Deserialize<'de> for ObjectMap<K, V, H>
+    where
+        K: Eq + Hash + Deserialize<'de>,
+        V: Deserialize<'de>,
+        H: BuildHasher + Default,
+    {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_map(ObjectMapVisitor {
+                marker: PhantomData,
+            })
+        }
+    }
+
+    struct ObjectMapVisitor<K, V, H> {
+        marker: PhantomData<ObjectMap<K, V, H>>,
+    }
+
+    impl<'de, K, V, H> Visitor<'de> for ObjectMapVisitor<K, V, H>
+    where
+        K: Eq + Hash + Deserialize<'de>,
+        V: Deserialize<'de>,
+        H: BuildHasher + Default,
+    {
+        type Value = ObjectMap<K, V, H>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            // cap the hint so a bogus length can't make us allocate
+            let capacity = map.size_hint().unwrap_or(0).min(4096);
+            let mut m = ObjectMap::with_capacity_and_hasher(capacity, H::default());
+            while let Some((k, v)) = map.next_entry()? {
+                m.insert(k, v);
+            }
+            Ok(m)
+        }
+    }
+}
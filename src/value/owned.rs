@@ -24,14 +24,28 @@ mod from;
 mod serialize;
 
 use crate::prelude::*;
+use crate::value::map::{self, ObjectMap, ObjectView};
 use crate::{AlignedBuf, Deserializer, Node, Result, StaticNode};
-use halfbrown::HashMap;
+use halfbrown::DefaultHashBuilder;
+#[cfg(not(feature = "preserve_order"))]
+use std::collections::HashSet;
 use std::fmt;
+use std::hash::BuildHasher;
 use std::ops::{Index, IndexMut};
 use value_trait::ValueAccess;
 
 /// Representation of a JSON object
-pub type Object = HashMap<String, Value>;
+pub type Object = HashedObject<DefaultHashBuilder>;
+
+/// Representation of a JSON object whose keys are hashed with `S`
+pub type HashedObject<S> = ObjectMap<String, HashedValue<S>, S>;
+
+/// Owned JSON-DOM Value, consider using the `ValueTrait`
+/// to access it's content.
+/// This is slower then the `BorrowedValue` as a tradeoff
+/// for getting rid of lifetimes. Objects hash their keys with the
+/// default hasher, see [`HashedValue`] to pick another one.
+pub type Value = HashedValue<DefaultHashBuilder>;
 
 /// Parses a slice of bytes into a Value dom. This function will
 /// rewrite the slice to de-escape strings.
@@ -43,6 +57,29 @@ pub type Object = HashMap<String, Value>;
 ///
 /// Will return `Err` if `s` is invalid JSON.
 pub fn to_value(s: &mut [u8]) -> Result<Value> {
+    to_value_with_hasher(s)
+}
+
+/// Parses a slice of bytes into a Value dom whose objects hash their
+/// keys with `S`, for example a DOS resistant hasher for untrusted input.
+///
+/// ```
+/// use simd_json::prelude::*;
+/// use std::collections::hash_map::RandomState;
+///
+/// let mut d = br#"{"key": [1, 2]}"#.to_vec();
+/// let v: simd_json::value::owned::HashedValue<RandomState> =
+///     simd_json::to_owned_value_with_hasher(&mut d).unwrap();
+/// assert_eq!(v["key"][1], 2);
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON.
+pub fn to_value_with_hasher<S>(s: &mut [u8]) -> Result<HashedValue<S>>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     match Deserializer::from_slice(s) {
         Ok(de) => Ok(OwnedDeserializer::from_deserializer(de).parse()),
         Err(e) => Err(e),
@@ -69,23 +106,131 @@ pub fn to_value_with_buffers(
     }
 }
 
-/// Owned JSON-DOM Value, consider using the `ValueTrait`
-/// to access it's content.
-/// This is slower then the `BorrowedValue` as a tradeoff
-/// for getting rid of lifetimes.
-#[derive(Debug, Clone)]
-pub enum Value {
+/// Parses a buffer in any of the encodings JSON can come in into a
+/// Value dom. A UTF-8 byte order mark is skipped while UTF-16 and UTF-32
+/// input is transcoded to UTF-8, replacing the content of `s`.
+///
+/// # Errors
+///
+/// Will return `Err` if `s` is invalid JSON, error offsets refer to the
+/// original encoding of `s`.
+pub fn to_value_any_encoding(s: &mut Vec<u8>) -> Result<Value> {
+    match Deserializer::from_vec_any_encoding(s) {
+        Ok(de) => Ok(OwnedDeserializer::from_deserializer(de).parse()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Owned JSON-DOM Value whose objects hash their keys with `S`.
+/// [`Value`] names it with the default hasher.
+#[derive(Clone)]
+pub enum HashedValue<S> {
     /// Static values
     Static(StaticNode),
     /// string type
     String(String),
     /// array type
-    Array(Vec<Value>),
+    Array(Vec<HashedValue<S>>),
     /// object type
-    Object(Box<Object>),
+    Object(Box<HashedObject<S>>),
+}
+
+impl<S> HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    /// Parses `s` into this value, reusing the strings, arrays and
+    /// objects it already holds wherever the new document has the same
+    /// shape. Parsing same shaped documents into one value over and over
+    /// doesn't allocate for the value this way, the parser still allocates
+    /// a fresh input buffer, tape and structural indexes on every call,
+    /// see [`update_from_slice_with_buffers`](Self::update_from_slice_with_buffers)
+    /// to keep the buffers around. Like [`to_value`] the slice is rewritten
+    /// to de-escape strings.
+    ///
+    /// With `preserve_order` the keys of updated objects are put in the
+    /// order of the new document.
+    ///
+    /// ```
+    /// use simd_json::{json, OwnedValue};
+    ///
+    /// let mut v = OwnedValue::default();
+    /// for i in 0..3 {
+    ///     let mut d = format!(r#"{{"id": {}, "tags": ["a", "b"]}}"#, i).into_bytes();
+    ///     v.update_from_slice(&mut d).unwrap();
+    ///     assert_eq!(v, json!({"id": i, "tags": ["a", "b"]}));
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON, the value is left
+    /// untouched in that case.
+    pub fn update_from_slice(&mut self, s: &mut [u8]) -> Result<()> {
+        match Deserializer::from_slice(s) {
+            Ok(de) => {
+                OwnedDeserializer::from_deserializer(de).update(self);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses `s` into this value like
+    /// [`update_from_slice`](Self::update_from_slice) using the given
+    /// buffers, so only the tape and structural indexes are allocated on
+    /// every call. The string buffer has to be larger than `s` by the
+    /// padding the SIMD reads need, the input buffer grows as needed.
+    ///
+    /// ```
+    /// use simd_json::{json, AlignedBuf, OwnedValue};
+    ///
+    /// let mut v = OwnedValue::default();
+    /// let mut input_buffer = AlignedBuf::with_capacity(1024);
+    /// let mut string_buffer = vec![0; 1024];
+    /// for i in 0..3 {
+    ///     let mut d = format!(r#"{{"id": {}}}"#, i).into_bytes();
+    ///     v.update_from_slice_with_buffers(&mut d, &mut input_buffer, &mut string_buffer)
+    ///         .unwrap();
+    ///     assert_eq!(v, json!({ "id": i }));
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `s` is invalid JSON, the value is left
+    /// untouched in that case.
+    pub fn update_from_slice_with_buffers(
+        &mut self,
+        s: &mut [u8],
+        input_buffer: &mut AlignedBuf,
+        string_buffer: &mut [u8],
+    ) -> Result<()> {
+        match Deserializer::from_slice_with_buffers(s, input_buffer, string_buffer) {
+            Ok(de) => {
+                OwnedDeserializer::from_deserializer(de).update(self);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
 }
 
-impl<'input> Builder<'input> for Value {
+impl<S> fmt::Debug for HashedValue<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Static(s) => f.debug_tuple("Static").field(s).finish(),
+            Self::String(s) => f.debug_tuple("String").field(s).finish(),
+            Self::Array(a) => f.debug_tuple("Array").field(a).finish(),
+            Self::Object(o) => f.debug_tuple("Object").field(o).finish(),
+        }
+    }
+}
+
+impl<'input, S> Builder<'input> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn null() -> Self {
@@ -99,11 +244,14 @@ impl<'input> Builder<'input> for Value {
     #[inline]
     #[must_use]
     fn object_with_capacity(capacity: usize) -> Self {
-        Self::Object(Box::new(Object::with_capacity(capacity)))
+        Self::Object(Box::new(map::with_capacity(capacity)))
     }
 }
 
-impl Mutable for Value {
+impl<S> Mutable for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
@@ -114,15 +262,18 @@ impl Mutable for Value {
     }
     #[inline]
     #[must_use]
-    fn as_object_mut(&mut self) -> Option<&mut HashMap<Self::Key, Self>> {
+    fn as_object_mut(&mut self) -> Option<&mut ObjectView<String, Self, S>> {
         match self {
-            Self::Object(m) => Some(m),
+            Self::Object(m) => Some(map::view_mut(m)),
             _ => None,
         }
     }
 }
 
-impl ValueTrait for Value {
+impl<S> ValueTrait for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn value_type(&self) -> ValueType {
@@ -140,11 +291,14 @@ impl ValueTrait for Value {
     }
 }
 
-impl ValueAccess for Value {
-    type Target = Value;
+impl<S> ValueAccess for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    type Target = Self;
     type Key = String;
     type Array = Vec<Self>;
-    type Object = HashMap<Self::Key, Self>;
+    type Object = ObjectView<String, Self, S>;
 
     #[inline]
     #[must_use]
@@ -233,16 +387,16 @@ impl ValueAccess for Value {
 
     #[inline]
     #[must_use]
-    fn as_object(&self) -> Option<&HashMap<Self::Key, Self>> {
+    fn as_object(&self) -> Option<&ObjectView<String, Self, S>> {
         match self {
-            Self::Object(m) => Some(m),
+            Self::Object(m) => Some(map::view(m)),
             _ => None,
         }
     }
 }
 
 #[cfg(not(tarpaulin_include))]
-impl fmt::Display for Value {
+impl<S> fmt::Display for HashedValue<S> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Static(s) => s.fmt(f),
@@ -253,7 +407,10 @@ impl fmt::Display for Value {
     }
 }
 
-impl Index<&str> for Value {
+impl<S> Index<&str> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Output = Self;
     #[inline]
     #[must_use]
@@ -262,7 +419,10 @@ impl Index<&str> for Value {
     }
 }
 
-impl Index<usize> for Value {
+impl<S> Index<usize> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     type Output = Self;
     #[inline]
     #[must_use]
@@ -271,7 +431,10 @@ impl Index<usize> for Value {
     }
 }
 
-impl IndexMut<&str> for Value {
+impl<S> IndexMut<&str> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn index_mut(&mut self, index: &str) -> &mut Self::Output {
@@ -279,7 +442,10 @@ impl IndexMut<&str> for Value {
     }
 }
 
-impl IndexMut<usize> for Value {
+impl<S> IndexMut<usize> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
@@ -287,7 +453,7 @@ impl IndexMut<usize> for Value {
     }
 }
 
-impl Default for Value {
+impl<S> Default for HashedValue<S> {
     #[inline]
     #[must_use]
     fn default() -> Self {
@@ -297,24 +463,37 @@ impl Default for Value {
 
 struct OwnedDeserializer<'de> {
     de: Deserializer<'de>,
+    // keys of the object being updated, kept around between objects
+    #[cfg(not(feature = "preserve_order"))]
+    keys: HashSet<&'de str>,
 }
 
 impl<'de> OwnedDeserializer<'de> {
     pub fn from_deserializer(de: Deserializer<'de>) -> Self {
-        Self { de }
+        Self {
+            de,
+            #[cfg(not(feature = "preserve_order"))]
+            keys: HashSet::new(),
+        }
     }
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    pub fn parse(&mut self) -> Value {
+    pub fn parse<S>(&mut self) -> HashedValue<S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
         match unsafe { self.de.next_() } {
-            Node::Static(s) => Value::Static(s),
-            Node::String(s) => Value::from(s),
+            Node::Static(s) => HashedValue::Static(s),
+            Node::String(s) => HashedValue::from(s),
             Node::Array(len, _) => self.parse_array(len),
             Node::Object(len, _) => self.parse_map(len),
         }
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    fn parse_array(&mut self, len: usize) -> Value {
+    fn parse_array<S>(&mut self, len: usize) -> HashedValue<S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
         // Rust doens't optimize the normal loop away here
         // so we write our own avoiding the lenght
         // checks during push
@@ -325,12 +504,15 @@ impl<'de> OwnedDeserializer<'de> {
                 std::ptr::write(res.get_unchecked_mut(i), self.parse())
             }
         }
-        Value::Array(res)
+        HashedValue::Array(res)
     }
 
     #[cfg_attr(not(feature = "no-inline"), inline(always))]
-    fn parse_map(&mut self, len: usize) -> Value {
-        let mut res = Object::with_capacity(len);
+    fn parse_map<S>(&mut self, len: usize) -> HashedValue<S>
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
+        let mut res = map::with_capacity(len);
 
         for _ in 0..len {
             if let Node::String(key) = unsafe { self.de.next_() } {
@@ -341,7 +523,113 @@ impl<'de> OwnedDeserializer<'de> {
                 unreachable!()
             }
         }
-        Value::from(res)
+        HashedValue::from(res)
+    }
+
+    /// Parses the next value into `place`, keeping the allocations of
+    /// `place` where the shapes match.
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn update<S>(&mut self, place: &mut HashedValue<S>)
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
+        match (unsafe { self.de.next_() }, place) {
+            (Node::String(s), HashedValue::String(old)) => {
+                old.clear();
+                old.push_str(s);
+            }
+            (Node::Array(len, _), HashedValue::Array(old)) => self.update_array(old, len),
+            (Node::Object(len, end), HashedValue::Object(old)) => self.update_map(old, len, end),
+            // the shape changed so we build this part from scratch
+            (Node::Static(s), place) => *place = HashedValue::Static(s),
+            (Node::String(s), place) => *place = HashedValue::from(s),
+            (Node::Array(len, _), place) => *place = self.parse_array(len),
+            (Node::Object(len, _), place) => *place = self.parse_map(len),
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    fn update_array<S>(&mut self, old: &mut Vec<HashedValue<S>>, len: usize)
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
+        old.truncate(len);
+        for v in old.iter_mut() {
+            self.update(v);
+        }
+        old.reserve(len - old.len());
+        for _ in old.len()..len {
+            old.push(self.parse());
+        }
+    }
+
+    #[cfg_attr(not(feature = "no-inline"), inline(always))]
+    #[cfg_attr(feature = "preserve_order", allow(unused_variables))]
+    fn update_map<S>(&mut self, old: &mut HashedObject<S>, len: usize, end: usize)
+    where
+        S: BuildHasher + Clone + Default + Send + Sync,
+    {
+        #[cfg(feature = "preserve_order")]
+        {
+            // the keys of the document are moved to the front in their
+            // order, so whatever is left behind them has to go
+            let mut pos = 0;
+            for _ in 0..len {
+                if let Node::String(key) = unsafe { self.de.next_() } {
+                    let i = if let Some((i, _, v)) = old.get_full_mut(key) {
+                        self.update(v);
+                        i
+                    } else {
+                        old.insert_full(key.into(), self.parse()).0
+                    };
+                    // a duplicate key is already in place
+                    if i >= pos {
+                        old.swap_indices(pos, i);
+                        pos += 1;
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            old.truncate(pos);
+        }
+        #[cfg(not(feature = "preserve_order"))]
+        {
+            let start = self.de.idx;
+            let fresh = old.is_empty();
+            for _ in 0..len {
+                if let Node::String(key) = unsafe { self.de.next_() } {
+                    if let Some(v) = old.get_mut(key) {
+                        self.update(v);
+                    } else {
+                        old.insert(key.into(), self.parse());
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            // every key of the document is in the object now, if there are
+            // more some of the old ones have to go. Keys can repeat in the
+            // document so we have to count the distinct ones.
+            if !fresh {
+                let tape = &self.de.tape;
+                let keys = &mut self.keys;
+                keys.clear();
+                let mut i = start + 1;
+                while i < end {
+                    if let Node::String(key) = tape[i] {
+                        keys.insert(key);
+                    }
+                    i = match tape[i + 1] {
+                        Node::Array(_, e) | Node::Object(_, e) => e,
+                        Node::String(_) | Node::Static(_) => i + 2,
+                    };
+                }
+                if old.len() != keys.len() {
+                    old.retain(|k, _| keys.contains(k.as_str()));
+                }
+            }
+        }
     }
 }
 
@@ -350,6 +638,106 @@ mod test {
     #![allow(clippy::cognitive_complexity)]
     use super::*;
 
+    #[test]
+    fn custom_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+        type Fixed = BuildHasherDefault<DefaultHasher>;
+        let mut d = br#"{"a": [1, {"b": null}], "c": "d"}"#.to_vec();
+        let mut v: HashedValue<Fixed> = to_value_with_hasher(&mut d).expect("failed to parse");
+        assert_eq!(v["a"][1]["b"], ());
+        assert_eq!(v.remove("c"), Ok(Some(HashedValue::from("d"))));
+        v["a"][0] = 2.into();
+        assert_eq!(v.encode(), r#"{"a":[2,{"b":null}]}"#);
+        let mut o = HashedObject::<Fixed>::default();
+        o.insert("x".to_string(), HashedValue::from(vec![1, 2]));
+        let expected: HashedValue<Fixed> = vec![("x", vec![1, 2])].into_iter().collect();
+        assert_eq!(HashedValue::from(o), expected);
+    }
+
+    #[test]
+    fn update_from_slice() {
+        let mut v = Value::default();
+        let mut d = br#"{"a": "long string", "b": [1, [2, 3]], "c": {"d": null}}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(
+            v,
+            json!({"a": "long string", "b": [1, [2, 3]], "c": {"d": null}})
+        );
+        let string = v["a"].as_str().expect("not a string").as_ptr();
+        let array = v["b"].as_array().expect("not an array").as_ptr();
+        let object: &Object = v["c"].as_object().expect("not an object");
+        let object = object as *const Object;
+
+        let mut d = br#"{"c": {"e": 1}, "b": [true, [4, "x"]], "a": "short"}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(
+            v,
+            json!({"a": "short", "b": [true, [4, "x"]], "c": {"e": 1}})
+        );
+        assert_eq!(v["a"].as_str().expect("not a string").as_ptr(), string);
+        assert_eq!(v["b"].as_array().expect("not an array").as_ptr(), array);
+        let again: &Object = v["c"].as_object().expect("not an object");
+        assert_eq!(again as *const Object, object);
+
+        // keys that are gone are dropped, other shapes are replaced
+        let mut d = br#"{"a": 1, "b": {}}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(v, json!({"a": 1, "b": {}}));
+        let mut d = br#"[1, "2"]"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(v, json!([1, "2"]));
+
+        let mut d = b"[1, ".to_vec();
+        assert!(v.update_from_slice(&mut d).is_err());
+        assert_eq!(v, json!([1, "2"]));
+
+        // repeated keys don't hide the ones that are gone
+        let mut d = br#"{"a": 1, "b": 2}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        let mut d = br#"{"a": 1, "a": 2}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(v, json!({"a": 2}));
+    }
+
+    #[test]
+    fn update_from_slice_with_buffers() {
+        let mut v = Value::default();
+        let mut input_buffer = AlignedBuf::with_capacity(16);
+        let mut string_buffer = vec![0; 64 + crate::SIMDJSON_PADDING];
+        for d in &[&br#"{"a": [1, "x"]}"#[..], br#"{"a": [2], "b": "a\ty"}"#] {
+            let mut d = d.to_vec();
+            let expected = to_value(&mut d.clone()).expect("failed to parse");
+            v.update_from_slice_with_buffers(&mut d, &mut input_buffer, &mut string_buffer)
+                .expect("failed to parse");
+            assert_eq!(v, expected);
+        }
+    }
+
+    #[cfg(feature = "preserve_order")]
+    #[test]
+    fn update_from_slice_order() {
+        let mut v = Value::default();
+        let mut d = br#"{"a": 1, "b": {"x": 1, "y": 2}, "c": 3}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        let mut d = br#"{"d": 4, "b": {"y": 2, "z": 3}, "a": 1, "a": 5}"#.to_vec();
+        v.update_from_slice(&mut d).expect("failed to parse");
+        assert_eq!(v.encode(), r#"{"d":4,"b":{"y":2,"z":3},"a":5}"#);
+    }
+
+    #[cfg(feature = "serde_impl")]
+    #[test]
+    fn custom_hasher_serde() {
+        use std::collections::hash_map::RandomState;
+        let mut d = br#"{"a": [1, {"b": null}], "c": "d"}"#.to_vec();
+        let v: HashedValue<RandomState> = crate::from_slice(&mut d).expect("failed to parse");
+        assert_eq!(v["a"][1]["b"], ());
+        let s = crate::to_string(&v).expect("failed to serialize");
+        let again: HashedValue<RandomState> =
+            crate::from_slice(&mut s.into_bytes()).expect("failed to parse");
+        assert_eq!(v, again);
+    }
+
     #[test]
     fn object_access() {
         let mut v = Value::null();
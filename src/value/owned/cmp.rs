@@ -1,16 +1,21 @@
-use super::Value;
-use crate::{prelude::*, BorrowedValue};
+use super::HashedValue;
+use crate::prelude::*;
+use crate::value::borrowed;
+use std::hash::BuildHasher;
 
 #[allow(clippy::cast_sign_loss, clippy::default_trait_access)]
-impl PartialEq<BorrowedValue<'_>> for Value {
+impl<S> PartialEq<borrowed::HashedValue<'_, S>> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn eq(&self, other: &BorrowedValue<'_>) -> bool {
+    fn eq(&self, other: &borrowed::HashedValue<'_, S>) -> bool {
         match (self, other) {
-            (Self::Static(s1), BorrowedValue::Static(s2)) => s1 == s2,
-            (Self::String(v1), BorrowedValue::String(v2)) => v1.eq(v2),
-            (Self::Array(v1), BorrowedValue::Array(v2)) => v1.eq(v2),
-            (Self::Object(v1), BorrowedValue::Object(v2)) => {
+            (Self::Static(s1), borrowed::HashedValue::Static(s2)) => s1 == s2,
+            (Self::String(v1), borrowed::HashedValue::String(v2)) => v1.eq(v2),
+            (Self::Array(v1), borrowed::HashedValue::Array(v2)) => v1.eq(v2),
+            (Self::Object(v1), borrowed::HashedValue::Object(v2)) => {
                 if v1.len() != v2.len() {
                     return false;
                 }
@@ -23,7 +28,10 @@ impl PartialEq<BorrowedValue<'_>> for Value {
 }
 
 #[allow(clippy::cast_sign_loss, clippy::default_trait_access)]
-impl PartialEq for Value {
+impl<S> PartialEq for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &Self) -> bool {
@@ -37,9 +45,10 @@ impl PartialEq for Value {
     }
 }
 
-impl<T> PartialEq<&T> for Value
+impl<T, S> PartialEq<&T> for HashedValue<S>
 where
-    Value: PartialEq<T>,
+    Self: PartialEq<T>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
@@ -48,7 +57,10 @@ where
     }
 }
 
-impl PartialEq<()> for Value {
+impl<S> PartialEq<()> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, _other: &()) -> bool {
@@ -56,7 +68,10 @@ impl PartialEq<()> for Value {
     }
 }
 
-impl PartialEq<bool> for Value {
+impl<S> PartialEq<bool> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &bool) -> bool {
@@ -64,7 +79,10 @@ impl PartialEq<bool> for Value {
     }
 }
 
-impl PartialEq<str> for Value {
+impl<S> PartialEq<str> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &str) -> bool {
@@ -72,7 +90,10 @@ impl PartialEq<str> for Value {
     }
 }
 
-impl PartialEq<&str> for Value {
+impl<S> PartialEq<&str> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &&str) -> bool {
@@ -80,7 +101,10 @@ impl PartialEq<&str> for Value {
     }
 }
 
-impl PartialEq<String> for Value {
+impl<S> PartialEq<String> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &String) -> bool {
@@ -88,7 +112,10 @@ impl PartialEq<String> for Value {
     }
 }
 
-impl PartialEq<i8> for Value {
+impl<S> PartialEq<i8> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i8) -> bool {
@@ -96,7 +123,10 @@ impl PartialEq<i8> for Value {
     }
 }
 
-impl PartialEq<i16> for Value {
+impl<S> PartialEq<i16> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i16) -> bool {
@@ -104,7 +134,10 @@ impl PartialEq<i16> for Value {
     }
 }
 
-impl PartialEq<i32> for Value {
+impl<S> PartialEq<i32> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i32) -> bool {
@@ -112,7 +145,10 @@ impl PartialEq<i32> for Value {
     }
 }
 
-impl PartialEq<i64> for Value {
+impl<S> PartialEq<i64> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i64) -> bool {
@@ -120,7 +156,10 @@ impl PartialEq<i64> for Value {
     }
 }
 
-impl PartialEq<i128> for Value {
+impl<S> PartialEq<i128> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &i128) -> bool {
@@ -128,7 +167,10 @@ impl PartialEq<i128> for Value {
     }
 }
 
-impl PartialEq<u8> for Value {
+impl<S> PartialEq<u8> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u8) -> bool {
@@ -136,7 +178,10 @@ impl PartialEq<u8> for Value {
     }
 }
 
-impl PartialEq<u16> for Value {
+impl<S> PartialEq<u16> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u16) -> bool {
@@ -144,7 +189,10 @@ impl PartialEq<u16> for Value {
     }
 }
 
-impl PartialEq<u32> for Value {
+impl<S> PartialEq<u32> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u32) -> bool {
@@ -152,7 +200,10 @@ impl PartialEq<u32> for Value {
     }
 }
 
-impl PartialEq<u64> for Value {
+impl<S> PartialEq<u64> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u64) -> bool {
@@ -160,7 +211,10 @@ impl PartialEq<u64> for Value {
     }
 }
 
-impl PartialEq<usize> for Value {
+impl<S> PartialEq<usize> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &usize) -> bool {
@@ -168,7 +222,10 @@ impl PartialEq<usize> for Value {
     }
 }
 
-impl PartialEq<u128> for Value {
+impl<S> PartialEq<u128> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &u128) -> bool {
@@ -176,7 +233,10 @@ impl PartialEq<u128> for Value {
     }
 }
 
-impl PartialEq<f32> for Value {
+impl<S> PartialEq<f32> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &f32) -> bool {
@@ -184,7 +244,10 @@ impl PartialEq<f32> for Value {
     }
 }
 
-impl PartialEq<f64> for Value {
+impl<S> PartialEq<f64> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn eq(&self, other: &f64) -> bool {
@@ -192,9 +255,10 @@ impl PartialEq<f64> for Value {
     }
 }
 
-impl<T> PartialEq<&[T]> for Value
+impl<T, S> PartialEq<&[T]> for HashedValue<S>
 where
-    Value: PartialEq<T>,
+    Self: PartialEq<T>,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
@@ -202,15 +266,16 @@ where
         self.as_array().map(|t| t.eq(other)).unwrap_or_default()
     }
 }
-impl<K, T, S> PartialEq<std::collections::HashMap<K, T, S>> for Value
+impl<K, T, H, S> PartialEq<std::collections::HashMap<K, T, H>> for HashedValue<S>
 where
     K: AsRef<str> + std::hash::Hash + Eq,
-    Value: PartialEq<T>,
-    S: std::hash::BuildHasher,
+    Self: PartialEq<T>,
+    H: BuildHasher,
+    S: BuildHasher + Clone + Default + Send + Sync,
 {
     #[inline]
     #[must_use]
-    fn eq(&self, other: &std::collections::HashMap<K, T, S>) -> bool {
+    fn eq(&self, other: &std::collections::HashMap<K, T, H>) -> bool {
         self.as_object().map_or(false, |object| {
             object.len() == other.len()
                 && other
@@ -1,32 +1,37 @@
-use super::{Object, Value};
-use crate::{BorrowedValue, StaticNode};
+use super::{HashedObject, HashedValue};
+use crate::value::borrowed;
+use crate::StaticNode;
+use std::hash::BuildHasher;
 use std::iter::FromIterator;
 
-impl From<crate::BorrowedValue<'_>> for Value {
+impl<S> From<borrowed::HashedValue<'_, S>> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn from(b: BorrowedValue<'_>) -> Self {
+    fn from(b: borrowed::HashedValue<'_, S>) -> Self {
         match b {
-            BorrowedValue::Static(s) => Self::from(s),
-            BorrowedValue::String(s) => Self::from(s.to_string()),
-            BorrowedValue::Array(a) => a.into_iter().collect(),
-            BorrowedValue::Object(m) => m.into_iter().collect(),
+            borrowed::HashedValue::Static(s) => Self::from(s),
+            borrowed::HashedValue::String(s) => Self::from(s.to_string()),
+            borrowed::HashedValue::Array(a) => a.into_iter().collect(),
+            borrowed::HashedValue::Object(m) => m.into_iter().collect(),
         }
     }
 }
 
-impl<T> From<Option<T>> for Value
+impl<T, S> From<Option<T>> for HashedValue<S>
 where
-    Value: From<T>,
+    Self: From<T>,
 {
     #[inline]
     #[must_use]
     fn from(s: Option<T>) -> Self {
-        s.map_or(Value::Static(StaticNode::Null), Value::from)
+        s.map_or(Self::Static(StaticNode::Null), Self::from)
     }
 }
 
-impl From<StaticNode> for Value {
+impl<S> From<StaticNode> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(s: StaticNode) -> Self {
@@ -35,7 +40,7 @@ impl From<StaticNode> for Value {
 }
 /********* str_ **********/
 
-impl From<&str> for Value {
+impl<S> From<&str> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(s: &str) -> Self {
@@ -43,7 +48,7 @@ impl From<&str> for Value {
     }
 }
 
-impl<'value> From<std::borrow::Cow<'value, str>> for Value {
+impl<'value, S> From<std::borrow::Cow<'value, str>> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(c: std::borrow::Cow<'value, str>) -> Self {
@@ -52,7 +57,7 @@ impl<'value> From<std::borrow::Cow<'value, str>> for Value {
 }
 
 #[cfg(feature = "beef")]
-impl<'value> From<beef::lean::Cow<'value, str>> for Value {
+impl<'value, S> From<beef::lean::Cow<'value, str>> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(c: beef::lean::Cow<'value, str>) -> Self {
@@ -60,7 +65,7 @@ impl<'value> From<beef::lean::Cow<'value, str>> for Value {
     }
 }
 
-impl From<String> for Value {
+impl<S> From<String> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(s: String) -> Self {
@@ -68,7 +73,7 @@ impl From<String> for Value {
     }
 }
 
-impl From<&String> for Value {
+impl<S> From<&String> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(s: &String) -> Self {
@@ -78,7 +83,7 @@ impl From<&String> for Value {
 
 /********* atoms **********/
 
-impl From<bool> for Value {
+impl<S> From<bool> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(b: bool) -> Self {
@@ -86,7 +91,7 @@ impl From<bool> for Value {
     }
 }
 
-impl From<()> for Value {
+impl<S> From<()> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(_b: ()) -> Self {
@@ -95,7 +100,7 @@ impl From<()> for Value {
 }
 
 /********* i_ **********/
-impl From<i8> for Value {
+impl<S> From<i8> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: i8) -> Self {
@@ -103,7 +108,7 @@ impl From<i8> for Value {
     }
 }
 
-impl From<i16> for Value {
+impl<S> From<i16> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: i16) -> Self {
@@ -111,7 +116,7 @@ impl From<i16> for Value {
     }
 }
 
-impl From<i32> for Value {
+impl<S> From<i32> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: i32) -> Self {
@@ -119,7 +124,7 @@ impl From<i32> for Value {
     }
 }
 
-impl From<i64> for Value {
+impl<S> From<i64> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: i64) -> Self {
@@ -127,7 +132,7 @@ impl From<i64> for Value {
     }
 }
 #[cfg(feature = "128bit")]
-impl From<i128> for Value {
+impl<S> From<i128> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: i128) -> Self {
@@ -136,7 +141,7 @@ impl From<i128> for Value {
 }
 
 /********* u_ **********/
-impl From<u8> for Value {
+impl<S> From<u8> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: u8) -> Self {
@@ -144,7 +149,7 @@ impl From<u8> for Value {
     }
 }
 
-impl From<u16> for Value {
+impl<S> From<u16> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: u16) -> Self {
@@ -152,7 +157,7 @@ impl From<u16> for Value {
     }
 }
 
-impl From<u32> for Value {
+impl<S> From<u32> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: u32) -> Self {
@@ -160,7 +165,7 @@ impl From<u32> for Value {
     }
 }
 
-impl From<u64> for Value {
+impl<S> From<u64> for HashedValue<S> {
     #[inline]
     #[must_use]
     #[allow(clippy::cast_possible_wrap)]
@@ -170,7 +175,7 @@ impl From<u64> for Value {
 }
 
 #[cfg(feature = "128bit")]
-impl From<u128> for Value {
+impl<S> From<u128> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: u128) -> Self {
@@ -178,7 +183,7 @@ impl From<u128> for Value {
     }
 }
 
-impl From<usize> for Value {
+impl<S> From<usize> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(i: usize) -> Self {
@@ -187,7 +192,7 @@ impl From<usize> for Value {
 }
 
 /********* f_ **********/
-impl From<f32> for Value {
+impl<S> From<f32> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(f: f32) -> Self {
@@ -195,7 +200,7 @@ impl From<f32> for Value {
     }
 }
 
-impl From<f64> for Value {
+impl<S> From<f64> for HashedValue<S> {
     #[inline]
     #[must_use]
     fn from(f: f64) -> Self {
@@ -203,18 +208,21 @@ impl From<f64> for Value {
     }
 }
 
-impl<S> From<Vec<S>> for Value
+impl<T, S> From<Vec<T>> for HashedValue<S>
 where
-    Value: From<S>,
+    Self: From<T>,
 {
     #[inline]
     #[must_use]
-    fn from(v: Vec<S>) -> Self {
+    fn from(v: Vec<T>) -> Self {
         v.into_iter().collect()
     }
 }
 
-impl<V: Into<Value>> FromIterator<V> for Value {
+impl<V, S> FromIterator<V> for HashedValue<S>
+where
+    V: Into<Self>,
+{
     #[inline]
     #[must_use]
     fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
@@ -222,7 +230,12 @@ impl<V: Into<Value>> FromIterator<V> for Value {
     }
 }
 
-impl<K: ToString, V: Into<Value>> FromIterator<(K, V)> for Value {
+impl<K, V, S> FromIterator<(K, V)> for HashedValue<S>
+where
+    K: ToString,
+    V: Into<Self>,
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
@@ -234,18 +247,33 @@ impl<K: ToString, V: Into<Value>> FromIterator<(K, V)> for Value {
     }
 }
 
-impl From<Object> for Value {
+impl<S> From<HashedObject<S>> for HashedValue<S> {
     #[inline]
     #[must_use]
-    fn from(v: Object) -> Self {
+    fn from(v: HashedObject<S>) -> Self {
         Self::Object(Box::new(v))
     }
 }
 
-impl From<std::collections::HashMap<String, Value>> for Value {
+#[cfg(feature = "preserve_order")]
+impl<S> From<halfbrown::HashMap<String, HashedValue<S>, S>> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
+    #[inline]
+    #[must_use]
+    fn from(v: halfbrown::HashMap<String, Self, S>) -> Self {
+        Self::from(HashedObject::from(v))
+    }
+}
+
+impl<H, S> From<std::collections::HashMap<String, HashedValue<S>, H>> for HashedValue<S>
+where
+    S: BuildHasher + Clone + Default + Send + Sync,
+{
     #[inline]
     #[must_use]
-    fn from(v: std::collections::HashMap<String, Self>) -> Self {
-        Self::from(v.into_iter().collect::<Object>())
+    fn from(v: std::collections::HashMap<String, Self, H>) -> Self {
+        Self::from(v.into_iter().collect::<HashedObject<S>>())
     }
 }
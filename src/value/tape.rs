@@ -1,6 +1,11 @@
 /// A tape of a parsed json, all values are extracted and validated and
 /// can be used without further computation.
 use value_trait::StaticNode;
+
+mod compact;
+pub(crate) use self::compact::CompactTapeBuilder;
+pub use self::compact::{CompactNodes, CompactTape};
+
 /// `Tape`
 pub struct Tape<'input>(Vec<Node<'input>>);
 
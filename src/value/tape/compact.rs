@@ -0,0 +1,493 @@
+use super::Node;
+use crate::{Handler, Result, StaticNode};
+use std::convert::TryFrom;
+use std::str;
+
+// Every node is one word, the type in the top byte and the payload in the
+// remaining 56 bits, so a node has the same index as on the `Node` tape.
+const TAG_SHIFT: u32 = 56;
+const PAYLOAD: u64 = (1 << TAG_SHIFT) - 1;
+
+const NULL: u64 = 0;
+const TRUE: u64 = 1;
+const FALSE: u64 = 2;
+// integers that fit into the payload
+const SMALL_I64: u64 = 3;
+const SMALL_U64: u64 = 4;
+// numbers in the payload are the index of their bits in `wide`
+const I64: u64 = 5;
+const U64: u64 = 6;
+const F64: u64 = 7;
+#[cfg(feature = "128bit")]
+const I128: u64 = 8;
+#[cfg(feature = "128bit")]
+const U128: u64 = 9;
+// the payload is the offset of the length prefix in `strings`
+const STRING: u64 = 10;
+// the payload holds the length in the upper 24 and the end in the lower
+// 32 bits, the `WIDE` variants point at the two of them in `wide`
+const ARRAY: u64 = 11;
+const OBJECT: u64 = 12;
+const WIDE_ARRAY: u64 = 13;
+const WIDE_OBJECT: u64 = 14;
+
+const LEN_BITS: u32 = 24;
+const END_BITS: u32 = 32;
+// prefix of strings whose length doesn't fit into an u32, followed by the
+// length as an u64
+const LONG_STRING: u32 = u32::MAX;
+
+/// A tape that stores every node in a single `u64` word instead of a
+/// 24 byte [`Node`], with strings copied into a buffer next to it. Small
+/// integers and the length and end of most arrays and objects are packed
+/// into the word, other numbers are kept in a side table.
+///
+/// Nodes have the same index as on the tape [`to_tape`](crate::to_tape)
+/// returns and are read back as a [`Node`] view with [`get`](Self::get)
+/// or [`iter`](Self::iter), so code written against `Node` keeps working.
+///
+/// [`to_compact_tape`](crate::to_compact_tape) writes the words as the
+/// parser reports the elements, a tape of `Node`s that is already at hand
+/// can be converted with `CompactTape::from`.
+///
+/// ```
+/// use simd_json::{Node, StaticNode};
+///
+/// let mut d = br#"{"a": [1, 2.5], "b": "c"}"#.to_vec();
+/// let tape = simd_json::to_compact_tape(&mut d).unwrap();
+/// // the first node is the root of the tape
+/// assert_eq!(tape.len(), 8);
+/// assert_eq!(tape.get(1), Some(Node::Object(2, 8)));
+/// assert_eq!(tape.get(2), Some(Node::String("a")));
+/// assert_eq!(tape.get(5), Some(Node::Static(StaticNode::F64(2.5))));
+///
+/// let mut d = br#"{"a": [1, 2.5], "b": "c"}"#.to_vec();
+/// let nodes = simd_json::to_tape(&mut d).unwrap();
+/// assert!(tape.iter().eq(nodes));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompactTape {
+    words: Vec<u64>,
+    wide: Vec<u64>,
+    strings: Vec<u8>,
+}
+
+impl CompactTape {
+    /// The number of nodes on the tape
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Returns true if there are no nodes on the tape
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The node at `idx`
+    #[must_use]
+    pub fn get(&self, idx: usize) -> Option<Node<'_>> {
+        self.words.get(idx).map(|w| self.decode(*w))
+    }
+
+    /// Iterates over the nodes of the tape
+    #[must_use]
+    pub fn iter(&self) -> CompactNodes<'_> {
+        CompactNodes {
+            tape: self,
+            words: self.words.iter(),
+        }
+    }
+
+    fn push(&mut self, tag: u64, payload: u64) {
+        debug_assert!(payload <= PAYLOAD);
+        self.words.push((tag << TAG_SHIFT) | payload);
+    }
+
+    fn push_wide(&mut self, tag: u64, values: &[u64]) {
+        let idx = self.wide.len() as u64;
+        self.wide.extend_from_slice(values);
+        self.push(tag, idx);
+    }
+
+    fn push_container(&mut self, tag: u64, wide_tag: u64, len: usize, end: usize) {
+        let word = self.container(tag, wide_tag, len, end);
+        self.words.push(word);
+    }
+
+    /// The word of a container, its length and end go to `wide` if they
+    /// can't be packed
+    fn container(&mut self, tag: u64, wide_tag: u64, len: usize, end: usize) -> u64 {
+        let (len, end) = (len as u64, end as u64);
+        if len >> LEN_BITS == 0 && end >> END_BITS == 0 {
+            (tag << TAG_SHIFT) | (len << END_BITS) | end
+        } else {
+            let idx = self.wide.len() as u64;
+            self.wide.extend_from_slice(&[len, end]);
+            (wide_tag << TAG_SHIFT) | idx
+        }
+    }
+
+    fn push_str(&mut self, s: &str) {
+        let offset = self.strings.len() as u64;
+        match u32::try_from(s.len()) {
+            Ok(len) if len != LONG_STRING => self.strings.extend_from_slice(&len.to_le_bytes()),
+            _ => {
+                self.strings.extend_from_slice(&LONG_STRING.to_le_bytes());
+                self.strings
+                    .extend_from_slice(&(s.len() as u64).to_le_bytes());
+            }
+        }
+        self.strings.extend_from_slice(s.as_bytes());
+        self.push(STRING, offset);
+    }
+
+    #[allow(clippy::cast_sign_loss)]
+    fn push_i64(&mut self, n: i64) {
+        // the payload holds it if the top 9 bits are all the same
+        if (n << 8) >> 8 == n {
+            self.push(SMALL_I64, n as u64 & PAYLOAD);
+        } else {
+            self.push_wide(I64, &[n as u64]);
+        }
+    }
+
+    fn push_u64(&mut self, n: u64) {
+        if n <= PAYLOAD {
+            self.push(SMALL_U64, n);
+        } else {
+            self.push_wide(U64, &[n]);
+        }
+    }
+
+    #[cfg(feature = "128bit")]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn push_i128(&mut self, n: i128) {
+        self.push_wide(I128, &[n as u64, (n >> 64) as u64]);
+    }
+
+    #[cfg(feature = "128bit")]
+    #[allow(clippy::cast_possible_truncation)]
+    fn push_u128(&mut self, n: u128) {
+        self.push_wide(U128, &[n as u64, (n >> 64) as u64]);
+    }
+
+    fn push_node(&mut self, node: Node) {
+        match node {
+            Node::Static(StaticNode::Null) => self.push(NULL, 0),
+            Node::Static(StaticNode::Bool(true)) => self.push(TRUE, 0),
+            Node::Static(StaticNode::Bool(false)) => self.push(FALSE, 0),
+            Node::Static(StaticNode::I64(n)) => self.push_i64(n),
+            Node::Static(StaticNode::U64(n)) => self.push_u64(n),
+            Node::Static(StaticNode::F64(n)) => self.push_wide(F64, &[n.to_bits()]),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::I128(n)) => self.push_i128(n),
+            #[cfg(feature = "128bit")]
+            Node::Static(StaticNode::U128(n)) => self.push_u128(n),
+            Node::String(s) => self.push_str(s),
+            Node::Array(len, end) => self.push_container(ARRAY, WIDE_ARRAY, len, end),
+            Node::Object(len, end) => self.push_container(OBJECT, WIDE_OBJECT, len, end),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+    fn decode(&self, word: u64) -> Node<'_> {
+        let payload = word & PAYLOAD;
+        let wide = |i: u64| self.wide[i as usize];
+        match word >> TAG_SHIFT {
+            NULL => Node::Static(StaticNode::Null),
+            TRUE => Node::Static(StaticNode::Bool(true)),
+            FALSE => Node::Static(StaticNode::Bool(false)),
+            // shift the sign back into place
+            SMALL_I64 => Node::Static(StaticNode::I64((payload << 8) as i64 >> 8)),
+            SMALL_U64 => Node::Static(StaticNode::U64(payload)),
+            I64 => Node::Static(StaticNode::I64(wide(payload) as i64)),
+            U64 => Node::Static(StaticNode::U64(wide(payload))),
+            F64 => Node::Static(StaticNode::F64(f64::from_bits(wide(payload)))),
+            #[cfg(feature = "128bit")]
+            I128 => Node::Static(StaticNode::I128(
+                ((u128::from(wide(payload + 1)) << 64) | u128::from(wide(payload))) as i128,
+            )),
+            #[cfg(feature = "128bit")]
+            U128 => Node::Static(StaticNode::U128(
+                (u128::from(wide(payload + 1)) << 64) | u128::from(wide(payload)),
+            )),
+            STRING => Node::String(self.string(payload as usize)),
+            ARRAY => Node::Array(
+                (payload >> END_BITS) as usize,
+                (payload & ((1 << END_BITS) - 1)) as usize,
+            ),
+            OBJECT => Node::Object(
+                (payload >> END_BITS) as usize,
+                (payload & ((1 << END_BITS) - 1)) as usize,
+            ),
+            WIDE_ARRAY => Node::Array(wide(payload) as usize, wide(payload + 1) as usize),
+            WIDE_OBJECT => Node::Object(wide(payload) as usize, wide(payload + 1) as usize),
+            _ => unreachable!(),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn string(&self, offset: usize) -> &str {
+        let mut prefix = [0; 4];
+        prefix.copy_from_slice(&self.strings[offset..offset + 4]);
+        let (start, len) = match u32::from_le_bytes(prefix) {
+            LONG_STRING => {
+                let mut len = [0; 8];
+                len.copy_from_slice(&self.strings[offset + 4..offset + 12]);
+                (offset + 12, u64::from_le_bytes(len) as usize)
+            }
+            len => (offset + 4, len as usize),
+        };
+        // we only ever copy whole `str`s into the buffer
+        unsafe { str::from_utf8_unchecked(&self.strings[start..start + len]) }
+    }
+}
+
+impl<'input> From<&[Node<'input>]> for CompactTape {
+    fn from(nodes: &[Node<'input>]) -> Self {
+        let mut tape = Self {
+            words: Vec::with_capacity(nodes.len()),
+            wide: Vec::new(),
+            strings: Vec::new(),
+        };
+        for node in nodes {
+            tape.push_node(*node);
+        }
+        tape
+    }
+}
+
+/// Writes the elements the parser reports straight into a
+/// [`CompactTape`], the same way the `Node` tape is built: the leading
+/// `null` stands in for the skipped zero index and containers get their
+/// length and end once they are closed.
+pub(crate) struct CompactTapeBuilder {
+    tape: CompactTape,
+    // `last_start` and `cnt` of the containers we are nested in
+    stack: Vec<(usize, usize)>,
+    // index of the container we are currently in on the tape
+    last_start: usize,
+    // number of elements in the current container so far
+    cnt: usize,
+}
+
+impl CompactTapeBuilder {
+    pub(crate) fn new() -> Self {
+        let mut tape = CompactTape::default();
+        tape.push(NULL, 0);
+        Self {
+            tape,
+            stack: Vec::new(),
+            last_start: 0,
+            cnt: 0,
+        }
+    }
+
+    pub(crate) fn into_tape(self) -> CompactTape {
+        self.tape
+    }
+
+    fn value(&mut self) -> &mut CompactTape {
+        self.cnt += 1;
+        &mut self.tape
+    }
+
+    fn start(&mut self, tag: u64) {
+        self.stack.push((self.last_start, self.cnt + 1));
+        self.last_start = self.tape.len();
+        self.cnt = 0;
+        // filled in by `end`
+        self.tape.push(tag, 0);
+    }
+
+    fn end(&mut self) {
+        let tape = &mut self.tape;
+        let end = tape.len();
+        let word = match tape.words[self.last_start] >> TAG_SHIFT {
+            ARRAY => tape.container(ARRAY, WIDE_ARRAY, self.cnt, end),
+            _ => tape.container(OBJECT, WIDE_OBJECT, self.cnt, end),
+        };
+        tape.words[self.last_start] = word;
+        if let Some((last_start, cnt)) = self.stack.pop() {
+            self.last_start = last_start;
+            self.cnt = cnt;
+        }
+    }
+}
+
+impl<'de> Handler<'de> for CompactTapeBuilder {
+    fn start_object(&mut self) -> Result<()> {
+        self.start(OBJECT);
+        Ok(())
+    }
+    fn key(&mut self, key: &'de str) -> Result<()> {
+        self.tape.push_str(key);
+        Ok(())
+    }
+    fn end_object(&mut self) -> Result<()> {
+        self.end();
+        Ok(())
+    }
+    fn start_array(&mut self) -> Result<()> {
+        self.start(ARRAY);
+        Ok(())
+    }
+    fn end_array(&mut self) -> Result<()> {
+        self.end();
+        Ok(())
+    }
+    fn string(&mut self, s: &'de str) -> Result<()> {
+        self.value().push_str(s);
+        Ok(())
+    }
+    fn null(&mut self) -> Result<()> {
+        self.value().push(NULL, 0);
+        Ok(())
+    }
+    fn bool(&mut self, b: bool) -> Result<()> {
+        self.value().push(if b { TRUE } else { FALSE }, 0);
+        Ok(())
+    }
+    fn i64(&mut self, n: i64) -> Result<()> {
+        self.value().push_i64(n);
+        Ok(())
+    }
+    fn u64(&mut self, n: u64) -> Result<()> {
+        self.value().push_u64(n);
+        Ok(())
+    }
+    fn f64(&mut self, n: f64) -> Result<()> {
+        self.value().push_wide(F64, &[n.to_bits()]);
+        Ok(())
+    }
+    #[cfg(feature = "128bit")]
+    fn i128(&mut self, n: i128) -> Result<()> {
+        self.value().push_i128(n);
+        Ok(())
+    }
+    #[cfg(feature = "128bit")]
+    fn u128(&mut self, n: u128) -> Result<()> {
+        self.value().push_u128(n);
+        Ok(())
+    }
+}
+
+impl<'tape> IntoIterator for &'tape CompactTape {
+    type Item = Node<'tape>;
+    type IntoIter = CompactNodes<'tape>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the nodes of a [`CompactTape`]
+pub struct CompactNodes<'tape> {
+    tape: &'tape CompactTape,
+    words: std::slice::Iter<'tape, u64>,
+}
+
+impl<'tape> Iterator for CompactNodes<'tape> {
+    type Item = Node<'tape>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let tape = self.tape;
+        self.words.next().map(|w| tape.decode(*w))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+impl<'tape> ExactSizeIterator for CompactNodes<'tape> {}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::CompactTape;
+    use crate::{to_compact_tape, to_tape, Node, StaticNode};
+
+    #[test]
+    fn same_as_tape() {
+        for file in &[
+            "data/citm_catalog.json",
+            "data/twitter.json",
+            "data/canada.json",
+        ] {
+            let data = std::fs::read(file).unwrap();
+            let mut d1 = data.clone();
+            let mut d2 = data;
+            let tape = to_tape(&mut d1).unwrap();
+            let compact = to_compact_tape(&mut d2).unwrap();
+            assert_eq!(compact.len(), tape.len());
+            assert!(compact.iter().eq(tape.iter().copied()), "{}", file);
+            assert_eq!(compact.get(tape.len() / 2), Some(tape[tape.len() / 2]));
+            assert_eq!(compact.get(tape.len()), None);
+        }
+    }
+
+    #[test]
+    fn numbers() {
+        let nodes = [
+            Node::Array(12, 13),
+            Node::Static(StaticNode::I64(0)),
+            Node::Static(StaticNode::I64(-1)),
+            Node::Static(StaticNode::I64((1 << 55) - 1)),
+            Node::Static(StaticNode::I64(1 << 55)),
+            Node::Static(StaticNode::I64(-(1 << 55))),
+            Node::Static(StaticNode::I64(-(1 << 55) - 1)),
+            Node::Static(StaticNode::I64(i64::MIN)),
+            Node::Static(StaticNode::I64(i64::MAX)),
+            Node::Static(StaticNode::U64((1 << 56) - 1)),
+            Node::Static(StaticNode::U64(1 << 56)),
+            Node::Static(StaticNode::U64(u64::MAX)),
+            Node::Static(StaticNode::F64(-0.5)),
+        ];
+        let compact = CompactTape::from(&nodes[..]);
+        assert!(compact.iter().eq(nodes.iter().copied()));
+    }
+
+    #[cfg(feature = "128bit")]
+    #[test]
+    fn numbers_128() {
+        let nodes = [
+            Node::Static(StaticNode::I128(i128::MIN)),
+            Node::Static(StaticNode::I128(-1)),
+            Node::Static(StaticNode::U128(u128::MAX)),
+        ];
+        let compact = CompactTape::from(&nodes[..]);
+        assert!(compact.iter().eq(nodes.iter().copied()));
+    }
+
+    #[test]
+    fn containers_and_strings() {
+        // the tape isn't checked, so containers too large to pack are easy
+        // to come by
+        let nodes = [
+            Node::Array((1 << 24) - 1, (1 << 32) - 1),
+            Node::Array(1 << 24, 2),
+            Node::Object(3, 1 << 32),
+            Node::Object(0, 4),
+            Node::String(""),
+            Node::String("snow ☃"),
+            Node::Static(StaticNode::Bool(true)),
+            Node::Static(StaticNode::Bool(false)),
+            Node::Static(StaticNode::Null),
+        ];
+        let compact = CompactTape::from(&nodes[..]);
+        assert!(compact.iter().eq(nodes.iter().copied()));
+        assert_eq!(compact.iter().len(), nodes.len());
+
+        let mut d = br#"[{}, [], {"a": [{"b": []}]}, "c", -1, 1.5, 2]"#.to_vec();
+        let built = to_compact_tape(&mut d.clone()).unwrap();
+        assert_eq!(built, CompactTape::from(&to_tape(&mut d).unwrap()[..]));
+        let mut d = br#"[1, {"a": 2}"#.to_vec();
+        assert!(to_compact_tape(&mut d).is_err());
+        assert!(CompactTape::default().is_empty());
+    }
+}
@@ -0,0 +1,487 @@
+//! Low level streaming JSON writer.
+//!
+//! This allows to produce JSON incrementally without having to build a
+//! `Value` or a serde `Serialize` type first. Escaping and number
+//! formatting are the same as for the `Writable` implementations of the
+//! value types.
+
+use crate::{Error, ErrorType, Escape, Result};
+use std::io::Write;
+use value_trait::generator::BaseGenerator;
+
+macro_rules! iomap {
+    ($e:expr) => {
+        ($e).map_err(|err| Error::generic(ErrorType::Io(err)))
+    };
+}
+
+struct Generator<W: Write> {
+    writer: W,
+    dent: u16,
+    spaces_per_indent: u16,
+    pretty: bool,
+    escape: Escape,
+}
+
+impl<W> BaseGenerator for Generator<W>
+where
+    W: Write,
+{
+    type T = W;
+    #[inline]
+    fn get_writer(&mut self) -> &mut Self::T {
+        &mut self.writer
+    }
+    #[inline]
+    fn write_min(&mut self, slice: &[u8], min: u8) -> std::io::Result<()> {
+        if self.pretty {
+            self.writer.write_all(slice)
+        } else {
+            self.writer.write_all(&[min])
+        }
+    }
+    #[inline]
+    fn new_line(&mut self) -> std::io::Result<()> {
+        if self.pretty {
+            stry!(self.write_char(b'\n'));
+            for _ in 0..(self.dent * self.spaces_per_indent) {
+                stry!(self.write_char(b' '));
+            }
+        }
+        Ok(())
+    }
+
+    fn indent(&mut self) {
+        self.dent += 1;
+    }
+
+    fn dedent(&mut self) {
+        self.dent -= 1;
+    }
+
+    #[inline]
+    fn write_string_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
+    }
+
+    #[inline]
+    fn write_simple_str_content(&mut self, string: &str) -> std::io::Result<()> {
+        self.escape.write_content(&mut self.writer, string)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+    /// An object, the flag is set once it has a key
+    Object(bool),
+    /// An array, the flag is set once it has an element
+    Array(bool),
+}
+
+/// Writes JSON to an `io::Write` one element at a time.
+///
+/// The writer checks that elements are nested correctly, so writing a
+/// value in an object without a key or closing an array with
+/// `end_object` returns an error instead of producing invalid JSON.
+///
+/// ```
+/// use simd_json::JsonWriter;
+///
+/// let mut w = JsonWriter::new(Vec::new());
+/// w.begin_object().unwrap();
+/// w.key("numbers").unwrap();
+/// w.begin_array().unwrap();
+/// w.u64(1).unwrap();
+/// w.f64(2.5).unwrap();
+/// w.end_array().unwrap();
+/// w.key("name").unwrap();
+/// w.string("snot").unwrap();
+/// w.end_object().unwrap();
+/// let out = w.finish().unwrap();
+/// assert_eq!(out, br#"{"numbers":[1,2.5],"name":"snot"}"#.to_vec());
+/// ```
+pub struct JsonWriter<W: Write> {
+    g: Generator<W>,
+    stack: Vec<Scope>,
+    /// Set when an object key was written and we wait for its value
+    has_key: bool,
+    /// Set when the top level value is complete
+    done: bool,
+}
+
+impl<W: Write> JsonWriter<W> {
+    /// Creates a writer that produces compact JSON
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self::with_generator(writer, false, 0)
+    }
+
+    /// Creates a writer that produces pretty printed JSON, indented
+    /// by two spaces the same way as `to_writer_pretty` and `write_pp`
+    #[must_use]
+    pub fn pretty(writer: W) -> Self {
+        Self::pretty_with_indent(writer, 2)
+    }
+
+    /// Creates a writer that produces pretty printed JSON, indented
+    /// by `spaces_per_indent` spaces
+    #[must_use]
+    pub fn pretty_with_indent(writer: W, spaces_per_indent: u16) -> Self {
+        Self::with_generator(writer, true, spaces_per_indent)
+    }
+
+    /// Sets which characters are escaped in keys and strings
+    #[must_use]
+    pub fn escape(mut self, escape: Escape) -> Self {
+        self.g.escape = escape;
+        self
+    }
+
+    fn with_generator(writer: W, pretty: bool, spaces_per_indent: u16) -> Self {
+        Self {
+            g: Generator {
+                writer,
+                dent: 0,
+                spaces_per_indent,
+                pretty,
+                escape: Escape::new(),
+            },
+            stack: Vec::new(),
+            has_key: false,
+            done: false,
+        }
+    }
+
+    /// Checks that a value can be written at this point and writes the
+    /// separator required in front of it
+    fn value_start(&mut self) -> Result<()> {
+        match self.stack.last_mut() {
+            None if self.done => Err(Error::generic(ErrorType::TrailingCharacters)),
+            None => Ok(()),
+            Some(Scope::Object(_)) => {
+                if self.has_key {
+                    self.has_key = false;
+                    Ok(())
+                } else {
+                    Err(Error::generic(ErrorType::ExpectedObjectKey))
+                }
+            }
+            Some(Scope::Array(has_elements)) => {
+                if *has_elements {
+                    stry!(iomap!(self.g.write(b",")));
+                } else {
+                    *has_elements = true;
+                }
+                iomap!(self.g.new_line())
+            }
+        }
+    }
+
+    /// Marks a value as written
+    fn value_end(&mut self) {
+        if self.stack.is_empty() {
+            self.done = true;
+        }
+    }
+
+    /// Writes a scalar value
+    fn scalar<F>(&mut self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Generator<W>) -> std::io::Result<()>,
+    {
+        stry!(self.value_start());
+        stry!(iomap!(f(&mut self.g)));
+        self.value_end();
+        Ok(())
+    }
+
+    /// Starts a new object
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn begin_object(&mut self) -> Result<()> {
+        stry!(self.value_start());
+        stry!(iomap!(self.g.write(b"{")));
+        self.g.indent();
+        self.stack.push(Scope::Object(false));
+        Ok(())
+    }
+
+    /// Writes the key of the next value in the current object
+    ///
+    /// # Errors
+    ///
+    /// if we are not in an object, the previous key has no value
+    /// yet or the write fails
+    pub fn key(&mut self, key: &str) -> Result<()> {
+        match self.stack.last_mut() {
+            Some(Scope::Object(has_keys)) if !self.has_key => {
+                if *has_keys {
+                    stry!(iomap!(self.g.write(b",")));
+                } else {
+                    *has_keys = true;
+                }
+                stry!(iomap!(self.g.new_line()));
+                stry!(iomap!(self.g.write_simple_string(key)));
+                stry!(iomap!(self.g.write_min(b": ", b':')));
+                self.has_key = true;
+                Ok(())
+            }
+            _ => Err(Error::generic(ErrorType::Syntax)),
+        }
+    }
+
+    /// Ends the current object
+    ///
+    /// # Errors
+    ///
+    /// if we are not in an object, the last key has no value or the
+    /// write fails
+    pub fn end_object(&mut self) -> Result<()> {
+        match self.stack.last() {
+            Some(Scope::Object(has_keys)) if !self.has_key => {
+                let has_keys = *has_keys;
+                self.end_scope(has_keys, b"}")
+            }
+            _ => Err(Error::generic(ErrorType::Syntax)),
+        }
+    }
+
+    /// Starts a new array
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn begin_array(&mut self) -> Result<()> {
+        stry!(self.value_start());
+        stry!(iomap!(self.g.write(b"[")));
+        self.g.indent();
+        self.stack.push(Scope::Array(false));
+        Ok(())
+    }
+
+    /// Ends the current array
+    ///
+    /// # Errors
+    ///
+    /// if we are not in an array or the write fails
+    pub fn end_array(&mut self) -> Result<()> {
+        match self.stack.last() {
+            Some(Scope::Array(has_elements)) => {
+                let has_elements = *has_elements;
+                self.end_scope(has_elements, b"]")
+            }
+            _ => Err(Error::generic(ErrorType::Syntax)),
+        }
+    }
+
+    fn end_scope(&mut self, has_content: bool, end: &[u8]) -> Result<()> {
+        self.stack.pop();
+        self.g.dedent();
+        if has_content {
+            stry!(iomap!(self.g.new_line()));
+        }
+        stry!(iomap!(self.g.write(end)));
+        self.value_end();
+        Ok(())
+    }
+
+    /// Writes a string
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn string(&mut self, s: &str) -> Result<()> {
+        self.scalar(|g| g.write_string(s))
+    }
+
+    /// Writes `null`
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn null(&mut self) -> Result<()> {
+        self.scalar(|g| g.write(b"null"))
+    }
+
+    /// Writes a boolean
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn bool(&mut self, b: bool) -> Result<()> {
+        self.scalar(|g| g.write(if b { b"true" } else { b"false" }))
+    }
+
+    /// Writes a signed integer
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn i64(&mut self, n: i64) -> Result<()> {
+        self.scalar(|g| g.write_int(n))
+    }
+
+    /// Writes an unsigned integer
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    pub fn u64(&mut self, n: u64) -> Result<()> {
+        self.scalar(|g| g.write_int(n))
+    }
+
+    /// Writes a signed 128 bit integer
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    #[cfg(feature = "128bit")]
+    pub fn i128(&mut self, n: i128) -> Result<()> {
+        self.scalar(|g| g.write_int(n))
+    }
+
+    /// Writes an unsigned 128 bit integer
+    ///
+    /// # Errors
+    ///
+    /// if no value is allowed at this point or the write fails
+    #[cfg(feature = "128bit")]
+    pub fn u128(&mut self, n: u128) -> Result<()> {
+        self.scalar(|g| g.write_int(n))
+    }
+
+    /// Writes a float
+    ///
+    /// # Errors
+    ///
+    /// if `n` is NaN or infinite as JSON can't represent them, if no
+    /// value is allowed at this point or the write fails
+    pub fn f64(&mut self, n: f64) -> Result<()> {
+        if !n.is_finite() {
+            return Err(Error::generic(ErrorType::InvalidNumber));
+        }
+        self.scalar(|g| g.write_float(n))
+    }
+
+    /// Finishes writing and returns the underlying writer
+    ///
+    /// # Errors
+    ///
+    /// if no complete value was written or the flush fails
+    pub fn finish(mut self) -> Result<W> {
+        if !self.done {
+            return Err(Error::generic(ErrorType::EarlyEnd));
+        }
+        stry!(iomap!(self.g.writer.flush()));
+        Ok(self.g.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use crate::prelude::*;
+    use crate::OwnedValue;
+
+    fn write_doc<W: Write>(w: &mut JsonWriter<W>) -> Result<()> {
+        w.begin_object()?;
+        w.key("a")?;
+        w.begin_array()?;
+        w.u64(1)?;
+        w.i64(-2)?;
+        w.f64(3.5)?;
+        w.begin_object()?;
+        w.end_object()?;
+        w.begin_array()?;
+        w.end_array()?;
+        w.end_array()?;
+        w.key("b\"")?;
+        w.string("snot\nbadger")?;
+        w.key("c")?;
+        w.begin_object()?;
+        w.key("d")?;
+        w.null()?;
+        w.key("e")?;
+        w.bool(true)?;
+        w.end_object()?;
+        w.end_object()
+    }
+
+    fn expected() -> OwnedValue {
+        json!({
+            "a": [1, -2, 3.5, {}, []],
+            "b\"": "snot\nbadger",
+            "c": {"d": null, "e": true}
+        })
+    }
+
+    #[test]
+    fn compact() {
+        let mut w = JsonWriter::new(Vec::new());
+        write_doc(&mut w).unwrap();
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        assert_eq!(out, expected().encode());
+    }
+
+    #[test]
+    fn pretty() {
+        let mut w = JsonWriter::pretty(Vec::new());
+        write_doc(&mut w).unwrap();
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        assert_eq!(out, expected().encode_pp());
+    }
+
+    #[test]
+    fn scalar_document() {
+        let mut w = JsonWriter::new(Vec::new());
+        w.string("snot").unwrap();
+        assert!(w.u64(1).is_err());
+        assert_eq!(w.finish().unwrap(), b"\"snot\"".to_vec());
+    }
+
+    #[test]
+    fn nesting_errors() {
+        let mut w = JsonWriter::new(Vec::new());
+        assert!(w.key("a").is_err());
+        assert!(w.end_array().is_err());
+        w.begin_object().unwrap();
+        assert!(w.u64(1).is_err());
+        assert!(w.end_array().is_err());
+        w.key("a").unwrap();
+        assert!(w.key("b").is_err());
+        assert!(w.end_object().is_err());
+        w.begin_array().unwrap();
+        assert!(w.key("c").is_err());
+        assert!(w.end_object().is_err());
+        w.end_array().unwrap();
+        assert!(w.f64(f64::NAN).is_err());
+        w.end_object().unwrap();
+        assert!(w.begin_array().is_err());
+        assert!(w.finish().is_ok());
+    }
+
+    #[test]
+    fn unfinished() {
+        assert!(JsonWriter::new(Vec::new()).finish().is_err());
+        let mut w = JsonWriter::new(Vec::new());
+        w.begin_array().unwrap();
+        assert!(w.finish().is_err());
+    }
+
+    #[test]
+    fn escape() {
+        let e = crate::Escape::new().html_safe(true);
+        let mut w = JsonWriter::new(Vec::new()).escape(e);
+        w.begin_object().unwrap();
+        w.key("<").unwrap();
+        w.string("</script>").unwrap();
+        w.end_object().unwrap();
+        assert_eq!(
+            w.finish().unwrap(),
+            br#"{"\u003c":"\u003c/script\u003e"}"#.to_vec()
+        );
+    }
+}
@@ -10,6 +10,11 @@ macro_rules! pass {
             let f = String::from(concat!("data/pass/", stringify!($file), ".json"));
             File::open(f).unwrap().read_to_end(&mut v1).unwrap();
             let mut v2 = v1.clone();
+            let mut t1 = v1.clone();
+            let mut t2 = v1.clone();
+            let tape = simd_json::to_tape(&mut t1).unwrap();
+            let compact = simd_json::to_compact_tape(&mut t2).unwrap();
+            assert!(compact.iter().eq(tape));
             let v1 = simd_json::to_borrowed_value(&mut v1);
             dbg!(&v1);
             assert!(v1.is_ok());